@@ -1,4 +1,6 @@
-use std::{cell::RefCell, collections::VecDeque, rc::Rc, time::Duration};
+use std::{
+    cell::RefCell, collections::VecDeque, fs, path::Path, rc::Rc, sync::Arc, time::Duration,
+};
 
 use color_eyre::{Result, eyre};
 use ratatui::{
@@ -7,6 +9,7 @@ use ratatui::{
     style::{Color, Style},
     widgets::{Block, Clear, Gauge, Paragraph},
 };
+use tracing::error;
 
 use crate::{
     core::{
@@ -16,9 +19,13 @@ use crate::{
         ui::{
             appscreen::{AppScreen, AppScreenEvent},
             dialog::Dialog,
+            external::ExternalView,
+            graphics::ImageProtocol,
+            keymap::Keymap,
             notification::{AppNotification, NotificationPriority},
         },
     },
+    mainui,
     ui::screens::{mainscreen::MainScreen, welcomedialog::WelcomeDialog},
 };
 
@@ -57,6 +64,11 @@ pub struct App {
     running: bool,
     library: Rc<RefCell<FeedLibrary>>,
     hooks: Rc<AppHooks>,
+    keymap: Rc<Keymap>,
+    images: ImageProtocol,
+    mouse: bool,
+    editor: Option<String>,
+    pager: Option<String>,
     current_state: Option<Box<dyn AppScreen>>,
     states_queue: VecDeque<Box<dyn AppScreen>>,
     dialog_queue: VecDeque<Box<dyn Dialog>>,
@@ -65,10 +77,19 @@ pub struct App {
 }
 
 impl App {
-    pub fn new(config: &Config) -> Self {
+    pub fn new(config: &Config, keymap: Keymap) -> Self {
+        let hooks = config.hooks.clone().unwrap_or_default();
+        let mut library = FeedLibrary::new(&config.datapath);
+        library.hooks = Arc::new(hooks.clone());
+
         Self {
-            library: Rc::new(RefCell::new(FeedLibrary::new(&config.datapath))),
-            hooks: Rc::new(config.hooks.clone().unwrap_or_default()),
+            library: Rc::new(RefCell::new(library)),
+            hooks: Rc::new(hooks),
+            keymap: Rc::new(keymap),
+            images: config.images,
+            mouse: config.mouse,
+            editor: config.editor.clone(),
+            pager: config.pager.clone(),
 
             running: true,
             current_state: None,
@@ -88,6 +109,8 @@ impl App {
         self.init(Box::new(MainScreen::new(
             self.library.clone(),
             self.hooks.clone(),
+            self.keymap.clone(),
+            self.images,
         )));
 
         if self.library.borrow().is_empty() {
@@ -227,6 +250,8 @@ impl App {
                     }
                 })?;
 
+                state.after_draw(!self.dialog_queue.is_empty());
+
                 // Checking the dialog or the state events
                 let event_available =
                     crossterm::event::poll(self.event_poll_timeout).unwrap_or(true);
@@ -260,6 +285,10 @@ impl App {
                             self.active_notification = Some(notification);
                         }
 
+                        AppScreenEvent::OpenExternal(view) => {
+                            self.open_external(&mut terminal, view)?;
+                        }
+
                         AppScreenEvent::ExitApp => {
                             self.running = false;
                         }
@@ -275,6 +304,9 @@ impl App {
             state.quit();
         }
 
+        self.library.borrow_mut().commit_changes();
+        self.library.borrow_mut().wait_for_hooks();
+
         Ok(())
     }
 
@@ -305,6 +337,62 @@ impl App {
         }
     }
 
+    /// Hands the terminal to an editor or pager until it exits. Entries edited meanwhile are
+    /// reloaded, and recorded as changes to the library.
+    fn open_external(&mut self, terminal: &mut DefaultTerminal, view: ExternalView) -> Result<()> {
+        let configured = match view {
+            ExternalView::Editor(_) => self.editor.as_deref(),
+            ExternalView::Pager(_) => self.pager.as_deref(),
+        };
+        let command = match view.command(configured, |key| std::env::var(key).ok()) {
+            Ok(command) => command,
+            Err(e) => {
+                self.active_notification = Some(AppNotification::new(
+                    e.to_string(),
+                    NotificationPriority::High,
+                ));
+                return Ok(());
+            }
+        };
+
+        let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+        let edited = match &view {
+            ExternalView::Editor(path) => Some((path, modified(path))),
+            ExternalView::Pager(_) => None,
+        };
+
+        if let Some(state) = self.current_state.as_mut() {
+            state.pause();
+        }
+
+        mainui::suspend_tui(self.mouse);
+        let result = view.run(&command);
+        mainui::resume_tui(terminal, self.mouse)?;
+
+        if let Err(e) = result {
+            error!("{:?}", e);
+            self.active_notification = Some(AppNotification::new(
+                e.to_string(),
+                NotificationPriority::High,
+            ));
+        }
+
+        if let Some((path, before)) = edited
+            && modified(path) != before
+        {
+            let mut library = self.library.borrow_mut();
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            library.record(format!("Edit {name}"));
+            library.bump_generation();
+        }
+
+        if let Some(state) = self.current_state.as_mut() {
+            state.unpause();
+        }
+
+        Ok(())
+    }
+
     fn get_work_status(&self) -> AppWorkStatus {
         if let Some(state) = self.current_state.as_ref() {
             let status = state.get_work_status();
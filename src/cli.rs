@@ -1,16 +1,30 @@
-use std::io::{self, Write};
+mod entries;
+mod output;
+
+use std::io;
 use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
-use clap::{Error, Parser, Subcommand};
-use tracing::{error, info};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use tracing::info;
 
 use crate::core::config::Config;
 use crate::core::config::ConfigStore;
-use crate::core::library::data::opml;
+use crate::core::defs;
+use crate::core::feed::greader::GReaderClient;
+use crate::core::hooks::FeedUpdate;
+use crate::core::library::data::gitrepo::{LibraryRepo, SyncResult};
+use crate::core::library::data::{newsboat, opml};
 use crate::core::library::feeditem::FeedItem;
-use crate::core::library::feedlibrary::FeedLibrary;
+use crate::core::library::feedlibrary::{FeedLibrary, ImportResult};
+use crate::core::library::readersync;
 use crate::dirs::Directories;
 
+use entries::EntriesCommands;
+use output::Output;
+pub use output::OutputFormat;
+
 #[derive(Parser)]
 #[command(name = "bulletty")]
 #[command(version, about = "Your TUI feed reader", long_about = None)]
@@ -20,6 +34,9 @@ pub struct Cli {
     /// Disable all hooks defined in config
     #[arg(long)]
     pub no_hooks: bool,
+    /// How command results are printed: text for people, json or tsv for scripts
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
 }
 
 #[derive(Subcommand)]
@@ -28,28 +45,78 @@ pub enum Commands {
     List,
     /// Add new feed
     Add {
-        /// The ATOM/RSS feed URL
+        /// The ATOM/RSS feed URL, or `exec:COMMAND` for a feed printed by a command
         url: String,
         #[arg()]
         /// The category to add under, if none is passed, it will be added to General
         category: Option<String>,
+        /// A command the feed goes through (on stdin) before being parsed
+        #[arg(long)]
+        filter: Option<String>,
     },
     /// Update all feeds
     Update,
+    /// Remove old entries according to the retention policy
+    Prune {
+        /// Only list the entries that would be removed
+        #[arg(long)]
+        dry_run: bool,
+    },
     /// Delete a feed
     Delete {
         /// The feed identifier (can be url, title or slug)
         ident: String,
     },
+    /// Move a feed to another category
+    Move {
+        /// The feed identifier (can be url, title or slug)
+        ident: String,
+        /// The category to move the feed to, it will be created if it doesn't exist
+        category: String,
+    },
+    /// Rename a feed
+    Rename {
+        /// The feed identifier (can be url, title or slug)
+        ident: String,
+        /// The new title of the feed
+        title: String,
+    },
+    /// List, read and mark entries
+    Entries {
+        #[command(subcommand)]
+        subcmd: EntriesCommands,
+    },
+    /// Manage categories
+    Category {
+        #[command(subcommand)]
+        subcmd: CategoryCommands,
+    },
+    /// Version the library with git and sync it with a remote
+    Sync {
+        #[command(subcommand)]
+        subcmd: Option<SyncCommands>,
+    },
     /// Show important directories
     Dirs {
         #[command(subcommand)]
         subcmd: Option<DirsCommands>,
     },
-    /// Import a list of feed sources through OPML
+    /// Import a list of feed sources through OPML or from newsboat
     Import {
-        /// The filepath of the OPML file
-        opml_file: String,
+        /// The filepath of the OPML file, or of newsboat's `urls` file
+        file: String,
+        /// The format of the file
+        #[arg(long, value_enum, default_value_t = ImportFormat::Opml)]
+        from: ImportFormat,
+        /// Newsboat's `cache.db`, to also import its articles and their read and flagged state
+        #[arg(long)]
+        cache: Option<PathBuf>,
+        /// Only list what would be imported
+        #[arg(long)]
+        dry_run: bool,
+        /// Fetch each feed while importing instead of on the next update
+        #[arg(long)]
+        fetch: bool,
     },
     /// Export all your sources to an OPML file
     Export {
@@ -58,6 +125,39 @@ pub enum Commands {
     },
 }
 
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum ImportFormat {
+    Opml,
+    Newsboat,
+}
+
+#[derive(Subcommand)]
+pub enum CategoryCommands {
+    /// Rename a category
+    Rename {
+        /// The current category name
+        category: String,
+        /// The new category name
+        new_name: String,
+    },
+    /// Delete a category and all of its feeds
+    Delete {
+        /// The category name
+        category: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SyncCommands {
+    /// Turn the library into a git repository, optionally setting the remote to sync with
+    Init {
+        /// The git remote, e.g. a path to a bare repository or an SSH URL
+        remote: Option<String>,
+    },
+    /// Sync subscriptions, entries and read state with a Google Reader API server
+    Reader,
+}
+
 #[derive(Subcommand)]
 pub enum DirsCommands {
     /// Show or update the library path
@@ -71,81 +171,311 @@ pub enum DirsCommands {
     LocalConfig,
 }
 
+/// A feed, as listed by `list` and reported by the commands that change feeds
+#[derive(Serialize)]
+struct FeedRecord {
+    status: &'static str,
+    category: String,
+    title: String,
+    slug: String,
+    url: String,
+    feed_url: String,
+    unread: Option<u16>,
+    error: Option<String>,
+}
+
+impl FeedRecord {
+    fn new(status: &'static str, feed: &FeedItem) -> Self {
+        Self {
+            status,
+            category: feed.category.clone(),
+            title: feed.title.clone(),
+            slug: feed.slug.clone(),
+            url: feed.url.clone(),
+            feed_url: feed.feed_url.clone(),
+            unread: None,
+            error: None,
+        }
+    }
+
+    fn failed(feed: &FeedItem, error: String) -> Self {
+        Self {
+            error: Some(error),
+            ..Self::new("failed", feed)
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct UpdateRecord {
+    status: &'static str,
+    category: String,
+    slug: String,
+    title: String,
+    new_entries: usize,
+    pruned: usize,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct PrunedRecord {
+    category: String,
+    feed: String,
+    path: String,
+    title: String,
+    date: String,
+}
+
+#[derive(Serialize)]
+struct ImportRecord {
+    status: &'static str,
+    url: String,
+    category: Option<String>,
+    title: Option<String>,
+    slug: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct CategoryRecord {
+    status: &'static str,
+    category: String,
+    new_name: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SyncRecord {
+    status: &'static str,
+    conflicts: usize,
+}
+
+#[derive(Serialize)]
+struct DirRecord {
+    name: &'static str,
+    path: String,
+}
+
 pub fn run_main_cli(
     cli: Cli,
     dirs: &Directories,
     config: &mut Config,
     config_store: &ConfigStore,
-) -> color_eyre::Result<()> {
+) -> color_eyre::Result<ExitCode> {
     info!("Initializing CLI");
 
     match &cli.command {
         Some(Commands::List) => command_list(&cli, &config.datapath),
-        Some(Commands::Add { url, category }) => command_add(&cli, url, category, &config.datapath),
-        Some(Commands::Update) => command_update(&cli, &config.datapath),
+        Some(Commands::Add {
+            url,
+            category,
+            filter,
+        }) => command_add(&cli, url, category, filter.as_deref(), &config.datapath),
+        Some(Commands::Update) => command_update(&cli, config),
+        Some(Commands::Prune { dry_run }) => command_prune(&cli, *dry_run, &config.datapath),
         Some(Commands::Delete { ident }) => command_delete(&cli, ident, &config.datapath),
+        Some(Commands::Move { ident, category }) => {
+            command_move(&cli, ident, category, &config.datapath)
+        }
+        Some(Commands::Rename { ident, title }) => {
+            command_rename(&cli, ident, title, &config.datapath)
+        }
+        Some(Commands::Entries { subcmd }) => entries::command_entries(&cli, subcmd, config),
+        Some(Commands::Category { subcmd }) => command_category(&cli, subcmd, &config.datapath),
+        Some(Commands::Sync { subcmd }) => command_sync(&cli, subcmd, config),
         Some(Commands::Dirs { subcmd }) => command_dirs(&cli, subcmd, dirs, config, config_store),
-        Some(Commands::Import { opml_file }) => command_import(&cli, opml_file, &config.datapath),
+        Some(Commands::Import {
+            file,
+            from,
+            cache,
+            dry_run,
+            fetch,
+        }) => command_import(
+            &cli,
+            file,
+            *from,
+            cache.as_deref(),
+            *dry_run,
+            *fetch,
+            &config.datapath,
+        ),
         Some(Commands::Export { opml_file }) => command_export(&cli, opml_file, &config.datapath),
-        None => Ok(()),
+        None => Ok(ExitCode::SUCCESS),
     }
 }
 
-fn command_list(_cli: &Cli, data_dir: &Path) -> color_eyre::Result<()> {
+fn command_list(cli: &Cli, data_dir: &Path) -> color_eyre::Result<ExitCode> {
     let library = FeedLibrary::new(data_dir);
-
-    println!("Feeds Registered\n\n");
-    for category in library.feedcategories.iter() {
-        println!("{}", category.title);
-        for feed in category.feeds.iter().as_ref() {
-            println!("\t-> {}: {}", feed.title, feed.slug);
+    let mut out = Output::new(cli.format);
+
+    if out.is_text() {
+        println!("Feeds Registered\n\n");
+        for category in library.feedcategories.iter() {
+            println!("{}", category.title);
+            for feed in category.feeds.iter().as_ref() {
+                println!("\t-> {}: {}", feed.title, feed.slug);
+            }
+            println!();
+        }
+    } else {
+        for category in library.feedcategories.iter() {
+            for feed in category.feeds.iter() {
+                let unread = library.data.get_unread_feed(&category.title, &feed.slug)?;
+                out.record(&FeedRecord {
+                    unread: Some(unread),
+                    ..FeedRecord::new("ok", feed)
+                });
+            }
         }
-        println!();
     }
 
-    Ok(())
+    out.finish()
 }
 
 fn command_add(
-    _cli: &Cli,
+    cli: &Cli,
     url: &str,
     category: &Option<String>,
+    filter: Option<&str>,
     data_dir: &Path,
-) -> color_eyre::Result<()> {
+) -> color_eyre::Result<ExitCode> {
     let mut library = FeedLibrary::new(data_dir);
-    match library.add_feed_from_url(url, category) {
+    let mut out = Output::new(cli.format);
+
+    match library.add_feed_from_url(url, category, filter) {
         Ok(feed) => {
-            info!("Feed added: {}", feed.title);
-            println!("Feed added: {}", feed.title);
+            out.message(format!("Feed added: {}", feed.title));
+            out.record(&FeedRecord::new("added", &feed));
         }
         Err(err) => {
-            error!("{err}");
-            println!("{err}");
+            out.fail(&err);
+            out.record(&FeedRecord::failed(
+                &FeedItem {
+                    feed_url: url.to_string(),
+                    category: category.clone().unwrap_or_default(),
+                    ..Default::default()
+                },
+                err.to_string(),
+            ));
         }
     }
 
-    Ok(())
+    library.commit_changes();
+    out.finish()
 }
 
-fn command_update(_cli: &Cli, data_dir: &Path) -> color_eyre::Result<()> {
-    let library = FeedLibrary::new(data_dir);
+fn command_update(cli: &Cli, config: &Config) -> color_eyre::Result<ExitCode> {
+    let mut library = FeedLibrary::new(&config.datapath);
+    let hooks = config.hooks.clone().unwrap_or_default();
+    let mut out = Output::new(cli.format);
+    let mut updates = vec![];
 
     for category in library.feedcategories.iter() {
         for feed in category.feeds.iter() {
-            info!("Updating {}", feed.title);
-            println!("Updating {}", feed.title);
-            library
+            out.message(format!("Updating {}", feed.title));
+
+            let result = library
                 .data
-                .update_feed_entries(&category.title, feed, None)?;
+                .update_feed_entries(&category.title, feed, None);
+
+            let mut update = FeedUpdate {
+                category: category.title.clone(),
+                feed: feed.clone(),
+                new_entries: 0,
+                failed: false,
+            };
+
+            let mut record = UpdateRecord {
+                status: "updated",
+                category: category.title.clone(),
+                slug: feed.slug.clone(),
+                title: feed.title.clone(),
+                new_entries: 0,
+                pruned: 0,
+                error: None,
+            };
+
+            match result {
+                Ok(new_entries) => {
+                    hooks.run_new_entries(&category.title, feed, &new_entries);
+                    record.new_entries = new_entries.len();
+
+                    // Pruning runs on its own, so failing to prune doesn't hide the new entries
+                    match library.prune_feed(feed, false) {
+                        Ok(pruned) if !pruned.is_empty() => {
+                            info!("Pruned {} entries from {}", pruned.len(), feed.title);
+                            out.message(format!("Pruned {} old entries", pruned.len()));
+                            record.pruned = pruned.len();
+                        }
+                        Ok(_) => {}
+                        Err(err) => {
+                            out.fail(format!("Couldn't prune {}: {err}", feed.title));
+                            record.error = Some(err.to_string());
+                        }
+                    }
+                }
+                Err(err) => {
+                    out.fail(format!("Couldn't update {}: {err}", feed.title));
+                    hooks.run_feed_error(&category.title, feed, &err.to_string());
+                    record.status = "failed";
+                    record.error = Some(err.to_string());
+                }
+            }
+
+            update.new_entries = record.new_entries;
+            update.failed = record.status == "failed";
+            updates.push(update);
+            out.record(&record);
         }
     }
 
-    Ok(())
+    hooks.run_update_finished(&updates);
+
+    library.record(String::from("Update feeds"));
+    library.commit_changes();
+    out.finish()
+}
+
+fn command_prune(cli: &Cli, dry_run: bool, data_dir: &Path) -> color_eyre::Result<ExitCode> {
+    let mut library = FeedLibrary::new(data_dir);
+    let mut out = Output::new(cli.format);
+    let pruned = library.prune(dry_run)?;
+
+    let categories_dir = data_dir.join(defs::DATA_CATEGORIES_DIR);
+    for entry in pruned.iter() {
+        let path = entry
+            .filepath
+            .strip_prefix(&categories_dir)
+            .unwrap_or(&entry.filepath);
+
+        if out.is_text() {
+            println!("\t-> {}", path.display());
+        }
+
+        let mut components = path.iter().map(|c| c.to_string_lossy().to_string());
+        out.record(&PrunedRecord {
+            category: components.next().unwrap_or_default(),
+            feed: components.next().unwrap_or_default(),
+            path: path.to_string_lossy().to_string(),
+            title: entry.title.clone(),
+            date: entry.date.to_rfc3339(),
+        });
+    }
+
+    if dry_run {
+        out.message(format!("{} entries would be pruned", pruned.len()));
+    } else {
+        out.message(format!("Pruned {} entries", pruned.len()));
+        library.commit_changes();
+    }
+
+    out.finish()
 }
 
-fn confirm_delete(title: &str) -> Result<bool, Error> {
-    print!("Are you sure you want to delete '{title}'? That can't be reverted. [y/N] ");
-    io::stdout().flush()?;
+fn confirm_delete(out: &Output, title: &str) -> io::Result<bool> {
+    out.prompt(format!(
+        "Are you sure you want to delete '{title}'? That can't be reverted. [y/N] "
+    ))?;
 
     let mut choice = String::new();
     io::stdin().read_line(&mut choice)?;
@@ -154,38 +484,36 @@ fn confirm_delete(title: &str) -> Result<bool, Error> {
     Ok(matches!(normalized_input.as_str(), "y" | "yes"))
 }
 
-fn command_delete(_cli: &Cli, ident: &str, data_dir: &Path) -> color_eyre::Result<()> {
-    let library = FeedLibrary::new(data_dir);
-
+/// Picks one feed out of the fuzzy matches for `ident`, asking the user when there's more than
+/// one. Returns `None`, failing the command, when nothing matched or the choice was invalid.
+fn select_feed<'a>(
+    out: &mut Output,
+    library: &'a FeedLibrary,
+    ident: &str,
+    action: &str,
+) -> color_eyre::Result<Option<&'a FeedItem>> {
     let matches: Vec<&FeedItem> = library.get_matching_feeds(ident);
     let matches_len = matches.len();
 
     match matches_len {
         0 => {
-            info!("No matching feeds exist");
-            println!("No matching feeds exist");
-        }
-        1 => {
-            let matched = matches[0];
-            if confirm_delete(&matched.title)? {
-                library.delete_feed(&matched.slug, &matched.category)?;
-                info!("Feed deleted: {}", &matched.title);
-                println!("Feed deleted: {}", &matched.title);
-            } else {
-                info!("Feed was not deleted: {}", &matched.title);
-                println!("Feed was not deleted: {}", &matched.title);
-            }
+            out.fail("No matching feeds exist");
+            Ok(None)
         }
+        1 => Ok(Some(matches[0])),
         _ => {
-            println!("There were {} feeds found with that identifier:", {
-                matches_len
-            });
-            let iter = matches.iter().enumerate();
-            for (i, feed) in iter {
-                println!("\t-> {}) {}/{}", i + 1, &feed.category, &feed.title);
+            let mut question =
+                format!("There were {matches_len} feeds found with that identifier:\n");
+            for (i, feed) in matches.iter().enumerate() {
+                question.push_str(&format!(
+                    "\t-> {}) {}/{}\n",
+                    i + 1,
+                    &feed.category,
+                    &feed.title
+                ));
             }
-            print!("Which one would you like to delete? ");
-            io::stdout().flush()?;
+            question.push_str(&format!("Which one would you like to {action}? "));
+            out.prompt(question)?;
 
             let mut choice = String::new();
             io::stdin().read_line(&mut choice)?;
@@ -193,141 +521,482 @@ fn command_delete(_cli: &Cli, ident: &str, data_dir: &Path) -> color_eyre::Resul
             let normalized_input = choice.trim();
 
             match normalized_input.parse::<usize>() {
+                Ok(ind) if ind >= 1 && ind <= matches_len => Ok(Some(matches[ind - 1])),
                 Ok(ind) => {
-                    if ind >= 1 && ind <= matches_len {
-                        let title =
-                            format!("{}/{}", &matches[ind - 1].category, &matches[ind - 1].title);
-
-                        if confirm_delete(&title)? {
-                            library
-                                .delete_feed(&matches[ind - 1].slug, &matches[ind - 1].category)?;
-                            info!("Feed deleted: {}", &matches[ind - 1].title);
-                            println!("Feed deleted: {}", &matches[ind - 1].title);
-                        } else {
-                            info!("Feed was not deleted: {}", &title);
-                            println!("Feed was not deleted: {}", &title);
-                        }
-                    } else {
-                        info!("Invalid input received: {ind}");
-                        println!("Invalid input received: {ind}");
-                    }
+                    out.fail(format!("Invalid input received: {ind}"));
+                    Ok(None)
                 }
                 Err(_) => {
-                    info!("Invalid input received: {normalized_input}");
-                    println!("Invalid input received: {normalized_input}");
+                    out.fail(format!("Invalid input received: {normalized_input}"));
+                    Ok(None)
                 }
             }
         }
     }
+}
 
-    Ok(())
+fn command_delete(cli: &Cli, ident: &str, data_dir: &Path) -> color_eyre::Result<ExitCode> {
+    let mut library = FeedLibrary::new(data_dir);
+    let mut out = Output::new(cli.format);
+
+    if let Some(matched) = select_feed(&mut out, &library, ident, "delete")?.cloned() {
+        let title = format!("{}/{}", &matched.category, &matched.title);
+
+        if confirm_delete(&out, &title)? {
+            match library.delete_feed(&matched.slug, &matched.category) {
+                Ok(()) => {
+                    out.message(format!("Feed deleted: {}", &matched.title));
+                    out.record(&FeedRecord::new("deleted", &matched));
+                }
+                Err(err) => {
+                    out.fail(&err);
+                    out.record(&FeedRecord::failed(&matched, err.to_string()));
+                }
+            }
+        } else {
+            out.message(format!("Feed was not deleted: {}", &title));
+            out.record(&FeedRecord::new("kept", &matched));
+        }
+    }
+
+    library.commit_changes();
+    out.finish()
+}
+
+fn command_move(
+    cli: &Cli,
+    ident: &str,
+    category: &str,
+    data_dir: &Path,
+) -> color_eyre::Result<ExitCode> {
+    let mut library = FeedLibrary::new(data_dir);
+    let mut out = Output::new(cli.format);
+
+    let Some(matched) = select_feed(&mut out, &library, ident, "move")?.cloned() else {
+        return out.finish();
+    };
+
+    match library.move_feed(&matched.slug, &matched.category, category) {
+        Ok(()) => {
+            out.message(format!("Feed moved: {} -> {}", &matched.title, category));
+            out.record(&FeedRecord {
+                category: category.to_string(),
+                ..FeedRecord::new("moved", &matched)
+            });
+        }
+        Err(err) => {
+            out.fail(&err);
+            out.record(&FeedRecord::failed(&matched, err.to_string()));
+        }
+    }
+
+    library.commit_changes();
+    out.finish()
+}
+
+fn command_rename(
+    cli: &Cli,
+    ident: &str,
+    title: &str,
+    data_dir: &Path,
+) -> color_eyre::Result<ExitCode> {
+    let mut library = FeedLibrary::new(data_dir);
+    let mut out = Output::new(cli.format);
+
+    let Some(matched) = select_feed(&mut out, &library, ident, "rename")?.cloned() else {
+        return out.finish();
+    };
+
+    match library.rename_feed(&matched.slug, &matched.category, title) {
+        Ok(feed) => {
+            out.message(format!(
+                "Feed renamed: {} -> {}",
+                &matched.title, &feed.title
+            ));
+            out.record(&FeedRecord::new("renamed", &feed));
+        }
+        Err(err) => {
+            out.fail(&err);
+            out.record(&FeedRecord::failed(&matched, err.to_string()));
+        }
+    }
+
+    library.commit_changes();
+    out.finish()
+}
+
+fn command_category(
+    cli: &Cli,
+    subcmd: &CategoryCommands,
+    data_dir: &Path,
+) -> color_eyre::Result<ExitCode> {
+    let mut library = FeedLibrary::new(data_dir);
+    let mut out = Output::new(cli.format);
+
+    match subcmd {
+        CategoryCommands::Rename { category, new_name } => {
+            let mut record = CategoryRecord {
+                status: "renamed",
+                category: category.clone(),
+                new_name: Some(new_name.clone()),
+                error: None,
+            };
+
+            match library.rename_category(category, new_name) {
+                Ok(()) => {
+                    out.message(format!("Category renamed: {} -> {}", category, new_name));
+                }
+                Err(err) => {
+                    out.fail(&err);
+                    record.status = "failed";
+                    record.error = Some(err.to_string());
+                }
+            }
+
+            out.record(&record);
+        }
+        CategoryCommands::Delete { category } => {
+            let mut record = CategoryRecord {
+                status: "deleted",
+                category: category.clone(),
+                new_name: None,
+                error: None,
+            };
+
+            if !library.data.category_exists(category) {
+                out.fail(format!("Category {category:?} doesn't exist"));
+                record.status = "failed";
+                record.error = Some(String::from("Category doesn't exist"));
+            } else if confirm_delete(&out, category)? {
+                match library.delete_category(category) {
+                    Ok(()) => {
+                        out.message(format!("Category deleted: {}", category));
+                    }
+                    Err(err) => {
+                        out.fail(&err);
+                        record.status = "failed";
+                        record.error = Some(err.to_string());
+                    }
+                }
+            } else {
+                out.message(format!("Category was not deleted: {}", category));
+                record.status = "kept";
+            }
+
+            out.record(&record);
+        }
+    }
+
+    library.commit_changes();
+    out.finish()
+}
+
+fn command_sync(
+    cli: &Cli,
+    subcmd: &Option<SyncCommands>,
+    config: &Config,
+) -> color_eyre::Result<ExitCode> {
+    let data_dir = config.datapath.as_path();
+    let mut out = Output::new(cli.format);
+
+    match subcmd {
+        Some(SyncCommands::Init { remote }) => {
+            LibraryRepo::init(data_dir, remote.as_deref())?;
+            out.message(format!(
+                "Library versioned with git at {}",
+                data_dir.display()
+            ));
+            out.record(&SyncRecord {
+                status: "initialized",
+                conflicts: 0,
+            });
+            return out.finish();
+        }
+        Some(SyncCommands::Reader) => return command_sync_reader(out, config),
+        None => {}
+    }
+
+    let Some(repo) = LibraryRepo::open(data_dir) else {
+        out.fail("The library isn't versioned yet. Run `bulletty sync init <remote>` first");
+        return out.finish();
+    };
+
+    // whatever is pending locally goes in before merging
+    repo.commit(&[String::from("Update library")])?;
+
+    let result = repo.sync()?;
+    let (status, conflicts, message) = match result {
+        SyncResult::Published => (
+            "published",
+            0,
+            String::from("Library published to the remote"),
+        ),
+        SyncResult::UpToDate => ("up_to_date", 0, String::from("Library already up to date")),
+        SyncResult::Pushed => ("pushed", 0, String::from("Local changes pushed")),
+        SyncResult::Merged(0) => ("merged", 0, String::from("Library synced")),
+        SyncResult::Merged(conflicts) => (
+            "merged",
+            conflicts,
+            format!("Library synced, {conflicts} conflicts resolved"),
+        ),
+    };
+
+    out.message(message);
+    out.record(&SyncRecord { status, conflicts });
+    out.finish()
+}
+
+fn command_sync_reader(mut out: Output, config: &Config) -> color_eyre::Result<ExitCode> {
+    let Some(reader) = config.greader.as_ref() else {
+        out.fail("No reader configured. Add a [greader] section to config.toml first");
+        return out.finish();
+    };
+
+    let mut library = FeedLibrary::new(&config.datapath);
+    let mut client = GReaderClient::login(reader)?;
+    let stats = readersync::sync_reader(&mut library, &mut client, reader.max_entries)?;
+
+    out.message(format!(
+        "Synced with {}: {} new feeds, {} new entries, {} changes pulled, {} pushed",
+        reader.url, stats.new_feeds, stats.new_entries, stats.pulled, stats.pushed
+    ));
+    out.record(&stats);
+
+    library.commit_changes();
+    out.finish()
 }
 
 fn command_dirs(
-    _cli: &Cli,
+    cli: &Cli,
     subcmd: &Option<DirsCommands>,
     dirs: &Directories,
     config: &mut Config,
     config_store: &ConfigStore,
-) -> color_eyre::Result<()> {
+) -> color_eyre::Result<ExitCode> {
+    let mut out = Output::new(cli.format);
+
     match subcmd {
-        Some(DirsCommands::Logs) => {
-            command_show_dir(dirs.log());
-            Ok(())
-        }
+        Some(DirsCommands::Logs) => command_show_dir(&mut out, "logs", dirs.log()),
         Some(DirsCommands::LocalConfig) => {
-            command_show_dir(dirs.config());
-            Ok(())
+            command_show_dir(&mut out, "local_config", dirs.config())
+        }
+        Some(DirsCommands::Library { path }) => {
+            command_dirs_library(&mut out, path, config, config_store)?
         }
-        Some(DirsCommands::Library { path }) => command_dirs_library(path, config, config_store),
         None => {
-            println!("bulletty directories\n");
-            println!("\t-> Library: {}", config.datapath.to_string_lossy());
-            println!("\t-> Logs:    {}", dirs.log().to_string_lossy());
-            println!("\t-> Local config:    {}", dirs.config().to_string_lossy());
-            println!();
+            if out.is_text() {
+                println!("bulletty directories\n");
+                println!("\t-> Library: {}", config.datapath.to_string_lossy());
+                println!("\t-> Logs:    {}", dirs.log().to_string_lossy());
+                println!("\t-> Local config:    {}", dirs.config().to_string_lossy());
+                println!();
+            }
 
-            Ok(())
+            for (name, path) in [
+                ("library", config.datapath.as_path()),
+                ("logs", dirs.log()),
+                ("local_config", dirs.config()),
+            ] {
+                out.record(&DirRecord {
+                    name,
+                    path: path.to_string_lossy().to_string(),
+                });
+            }
         }
     }
+
+    out.finish()
 }
 
 fn command_dirs_library(
+    out: &mut Output,
     path: &Option<PathBuf>,
     config: &mut Config,
     config_store: &ConfigStore,
 ) -> color_eyre::Result<()> {
-    match path {
-        Some(new_path) => {
-            if !new_path.exists() {
-                println!(
-                    "Error: path '{}' does not exist",
-                    new_path.to_string_lossy()
-                );
-                std::process::exit(1);
-            }
-
-            if !new_path.is_dir() {
-                println!(
-                    "Error: path '{}' is not a directory",
-                    new_path.to_string_lossy()
-                );
-                std::process::exit(1);
-            }
+    let Some(new_path) = path else {
+        command_show_dir(out, "library", &config.datapath);
+        return Ok(());
+    };
+
+    if !new_path.exists() {
+        out.fail(format!(
+            "Error: path '{}' does not exist",
+            new_path.to_string_lossy()
+        ));
+        return Ok(());
+    }
 
-            let absolute_path = new_path.canonicalize().unwrap_or_else(|e| {
-                println!(
-                    "Error: failed to resolve path '{}': {}",
-                    new_path.to_string_lossy(),
-                    e
-                );
-                std::process::exit(1);
-            });
+    if !new_path.is_dir() {
+        out.fail(format!(
+            "Error: path '{}' is not a directory",
+            new_path.to_string_lossy()
+        ));
+        return Ok(());
+    }
 
-            config.datapath = absolute_path.clone();
-            config_store.save(config)?;
-            println!(
-                "Library path updated to: {}",
-                absolute_path.to_string_lossy()
-            );
-        }
-        None => {
-            println!("{}", config.datapath.to_string_lossy());
+    let absolute_path = match new_path.canonicalize() {
+        Ok(path) => path,
+        Err(e) => {
+            out.fail(format!(
+                "Error: failed to resolve path '{}': {}",
+                new_path.to_string_lossy(),
+                e
+            ));
+            return Ok(());
         }
-    }
+    };
+
+    config.datapath = absolute_path.clone();
+    config_store.save(config)?;
+    out.message(format!(
+        "Library path updated to: {}",
+        absolute_path.to_string_lossy()
+    ));
+    out.record(&DirRecord {
+        name: "library",
+        path: absolute_path.to_string_lossy().to_string(),
+    });
 
     Ok(())
 }
 
-fn command_show_dir(dir: &Path) {
-    println!("{}", dir.to_string_lossy());
+fn command_show_dir(out: &mut Output, name: &'static str, dir: &Path) {
+    if out.is_text() {
+        println!("{}", dir.to_string_lossy());
+    }
+
+    out.record(&DirRecord {
+        name,
+        path: dir.to_string_lossy().to_string(),
+    });
 }
 
-fn command_import(_cli: &Cli, opml_file: &str, data_dir: &Path) -> color_eyre::Result<()> {
-    println!("Importing feeds");
-    let mut library = FeedLibrary::new(data_dir);
-    let opml_feeds = opml::get_opml_feeds(opml_file)?;
+fn command_import(
+    cli: &Cli,
+    file: &str,
+    from: ImportFormat,
+    cache: Option<&Path>,
+    dry_run: bool,
+    fetch: bool,
+    data_dir: &Path,
+) -> color_eyre::Result<ExitCode> {
+    let mut out = Output::new(cli.format);
+
+    if cache.is_some() && from != ImportFormat::Newsboat {
+        out.fail("--cache is only supported with --from newsboat");
+        return out.finish();
+    }
 
-    for feed in opml_feeds {
-        match library.add_feed_from_url(&feed.url, &feed.category) {
-            Ok(feed) => {
-                info!("Feed added: {}", feed.title);
-                println!("Feed added: {}", feed.title);
+    let mut library = FeedLibrary::new(data_dir);
+    let feeds = match from {
+        ImportFormat::Opml => opml::get_opml_feeds(file)?,
+        ImportFormat::Newsboat => {
+            let mut feeds = newsboat::get_urls_feeds(file)?;
+            if let Some(cache) = cache {
+                newsboat::complete_from_cache(&mut feeds, cache)?;
             }
-            Err(err) => {
-                error!("{err}");
-                println!("{err}");
+            feeds
+        }
+    };
+
+    out.message(format!("Importing {} feeds", feeds.len()));
+    let results = library.import_feeds(&feeds, fetch, dry_run);
+
+    let verb = if dry_run { "Would add" } else { "Feed added" };
+    let (mut added, mut skipped, mut failed) = (0, 0, 0);
+    for result in results.iter() {
+        let record = match result {
+            ImportResult::Added(feed) => {
+                added += 1;
+                out.message(format!("{verb}: {} ({})", feed.title, feed.category));
+                ImportRecord {
+                    status: if dry_run { "would_add" } else { "added" },
+                    url: feed.feed_url.clone(),
+                    category: Some(feed.category.clone()),
+                    title: Some(feed.title.clone()),
+                    slug: Some(feed.slug.clone()),
+                    error: None,
+                }
             }
+            ImportResult::AlreadySubscribed(url) => {
+                skipped += 1;
+                out.message(format!("Already subscribed: {url}"));
+                ImportRecord {
+                    status: "already_subscribed",
+                    url: url.clone(),
+                    category: None,
+                    title: None,
+                    slug: None,
+                    error: None,
+                }
+            }
+            ImportResult::Failed(url, err) => {
+                failed += 1;
+                out.fail(format!("{url}: {err}"));
+                ImportRecord {
+                    status: "failed",
+                    url: url.clone(),
+                    category: None,
+                    title: None,
+                    slug: None,
+                    error: Some(err.clone()),
+                }
+            }
+        };
+
+        out.record(&record);
+    }
+
+    if dry_run {
+        out.message(format!(
+            "{added} feeds would be added, {skipped} already subscribed, {failed} failed"
+        ));
+    } else {
+        out.message(format!(
+            "{added} feeds added, {skipped} already subscribed, {failed} failed"
+        ));
+    }
+
+    if let Some(cache) = cache {
+        let items = newsboat::read_cache(cache)?;
+        if dry_run {
+            let count = items
+                .iter()
+                .filter(|i| feeds.iter().any(|f| f.url == i.feed_url))
+                .count();
+            out.message(format!(
+                "{count} entries would be imported from {}",
+                cache.display()
+            ));
+        } else {
+            let imported = library.import_entries(&items)?;
+            out.message(format!(
+                "{imported} entries imported from {}",
+                cache.display()
+            ));
         }
     }
 
-    Ok(())
+    if !dry_run && !fetch && added > 0 {
+        out.message("Run `bulletty update` to fetch their entries");
+    }
+
+    library.commit_changes();
+    out.finish()
 }
 
-fn command_export(_cli: &Cli, opml_file: &str, data_dir: &Path) -> color_eyre::Result<()> {
+fn command_export(cli: &Cli, opml_file: &str, data_dir: &Path) -> color_eyre::Result<ExitCode> {
     let library = FeedLibrary::new(data_dir);
+    let mut out = Output::new(cli.format);
 
     opml::save_opml(&library.feedcategories, opml_file)?;
 
-    Ok(())
+    for category in library.feedcategories.iter() {
+        for feed in category.feeds.iter() {
+            out.record(&FeedRecord::new("exported", feed));
+        }
+    }
+
+    out.finish()
 }
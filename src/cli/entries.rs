@@ -0,0 +1,449 @@
+use std::path::{Component, Path};
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use clap::{Args, Subcommand};
+use color_eyre::eyre::{bail, eyre};
+use serde::Serialize;
+
+use super::{Cli, output::Output};
+use crate::core::config::Config;
+use crate::core::defs;
+use crate::core::feed::feedentry::FeedEntry;
+use crate::core::library::feedlibrary::FeedLibrary;
+use crate::ui::tools::tuimarkdown;
+
+#[derive(Subcommand)]
+pub enum EntriesCommands {
+    /// List entries, newest first
+    List {
+        #[command(flatten)]
+        filter: EntryFilter,
+        /// Only the entries in Read Later
+        #[arg(long)]
+        read_later: bool,
+        /// List at most this many entries
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Print an entry to stdout
+    Show {
+        /// The entry id, as printed by `entries list`
+        id: String,
+        /// Print the text as the reader renders it instead of Markdown
+        #[arg(long)]
+        rendered: bool,
+    },
+    /// Mark entries as read
+    MarkRead {
+        #[command(flatten)]
+        selection: EntrySelection,
+    },
+    /// Mark entries as unread
+    MarkUnread {
+        #[command(flatten)]
+        selection: EntrySelection,
+    },
+    /// Add entries to or remove them from Read Later
+    ReadLater {
+        #[command(subcommand)]
+        subcmd: ReadLaterCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ReadLaterCommands {
+    /// Add entries to Read Later
+    Add {
+        /// The entry ids, as printed by `entries list`
+        #[arg(required = true)]
+        ids: Vec<String>,
+    },
+    /// Remove entries from Read Later
+    Remove {
+        /// The entry ids, as printed by `entries list`
+        #[arg(required = true)]
+        ids: Vec<String>,
+    },
+}
+
+#[derive(Args)]
+pub struct EntryFilter {
+    /// Only the entries of this feed (its slug)
+    #[arg(long)]
+    feed: Option<String>,
+    /// Only the entries of this category
+    #[arg(long)]
+    category: Option<String>,
+    /// Only unread entries
+    #[arg(long)]
+    unread: bool,
+    /// Only entries published since then: a date (2024-05-01), a date and time (RFC 3339) or a
+    /// span of time ago (30m, 12h, 7d, 2w)
+    #[arg(long)]
+    since: Option<String>,
+    /// Only entries published before then, in the same formats as --since
+    #[arg(long)]
+    older_than: Option<String>,
+}
+
+#[derive(Args)]
+pub struct EntrySelection {
+    /// The entry ids, as printed by `entries list`
+    ids: Vec<String>,
+    #[command(flatten)]
+    filter: EntryFilter,
+    /// Select every entry matching the filters, even when there are none
+    #[arg(long)]
+    all: bool,
+}
+
+#[derive(Serialize)]
+struct EntryRecord {
+    id: String,
+    category: String,
+    feed: String,
+    title: String,
+    date: String,
+    url: String,
+    author: String,
+    seen: bool,
+    read_later: bool,
+}
+
+pub fn command_entries(
+    cli: &Cli,
+    subcmd: &EntriesCommands,
+    config: &Config,
+) -> color_eyre::Result<ExitCode> {
+    let data_dir = config.datapath.as_path();
+    let mut library = FeedLibrary::new(data_dir);
+    library.hooks = Arc::new(config.hooks.clone().unwrap_or_default());
+    let mut out = Output::new(cli.format);
+
+    if let Err(err) = run_entries(&mut library, &mut out, subcmd, data_dir) {
+        out.fail(err);
+    }
+
+    library.commit_changes();
+    library.wait_for_hooks();
+    out.finish()
+}
+
+fn run_entries(
+    library: &mut FeedLibrary,
+    out: &mut Output,
+    subcmd: &EntriesCommands,
+    data_dir: &Path,
+) -> color_eyre::Result<()> {
+    match subcmd {
+        EntriesCommands::List {
+            filter,
+            read_later,
+            limit,
+        } => {
+            let mut entries = if *read_later {
+                let later = library.get_read_later_feed_entries()?;
+                filter.apply(later)?
+            } else {
+                select_entries(library, filter)?
+            };
+
+            if let Some(limit) = limit {
+                entries.truncate(*limit);
+            }
+
+            for entry in entries.iter() {
+                let record = entry_record(library, data_dir, entry);
+                if out.is_text() {
+                    println!(
+                        "{} {} {}  {}",
+                        if record.seen { " " } else { "*" },
+                        entry.date.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+                        record.id,
+                        record.title
+                    );
+                }
+                out.record(&record);
+            }
+        }
+        EntriesCommands::Show { id, rendered } => {
+            let entry = load_entry(library, data_dir, id)?;
+
+            if out.is_text() {
+                println!("# {}\n", entry.title);
+                println!("{} | {}", entry.date.with_timezone(&Local), entry.author);
+                println!("{}\n", entry.url);
+
+                if *rendered {
+                    for line in tuimarkdown::from_str(&entry.text, None).lines {
+                        let text: String = line.spans.iter().map(|s| s.content.as_ref()).collect();
+                        println!("{text}");
+                    }
+                } else {
+                    println!("{}", entry.text);
+                }
+            }
+
+            let record = entry_record(library, data_dir, &entry);
+            out.record(&ShowRecord {
+                entry: record,
+                text: entry.text.clone(),
+            });
+        }
+        EntriesCommands::MarkRead { selection } | EntriesCommands::MarkUnread { selection } => {
+            let read = matches!(subcmd, EntriesCommands::MarkRead { .. });
+            let entries = selection.resolve(library, data_dir)?;
+
+            let changed: Vec<FeedEntry> = entries.into_iter().filter(|e| e.seen != read).collect();
+
+            if read {
+                library.set_entries_seen(&changed);
+            } else {
+                library.set_entries_unseen(&changed);
+            }
+
+            for entry in changed.iter() {
+                let mut record = entry_record(library, data_dir, entry);
+                record.seen = read;
+                out.record(&record);
+            }
+
+            let state = if read { "read" } else { "unread" };
+            out.message(format!("Marked {} entries as {state}", changed.len()));
+        }
+        EntriesCommands::ReadLater { subcmd } => {
+            let (ids, add) = match subcmd {
+                ReadLaterCommands::Add { ids } => (ids, true),
+                ReadLaterCommands::Remove { ids } => (ids, false),
+            };
+
+            for id in ids.iter() {
+                let result = load_entry(library, data_dir, id).and_then(|entry| {
+                    if add {
+                        library.add_to_read_later(&entry)?;
+                    } else {
+                        library.remove_from_read_later(&entry.filepath.to_string_lossy())?;
+                    }
+                    Ok(entry)
+                });
+
+                match result {
+                    Ok(entry) => {
+                        let action = if add { "Added to" } else { "Removed from" };
+                        out.message(format!("{action} Read Later: {}", entry.title));
+                        let record = entry_record(library, data_dir, &entry);
+                        out.record(&record);
+                    }
+                    Err(err) => out.fail(format!("{id}: {err}")),
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct ShowRecord {
+    #[serde(flatten)]
+    entry: EntryRecord,
+    text: String,
+}
+
+impl EntryFilter {
+    fn is_empty(&self) -> bool {
+        self.feed.is_none()
+            && self.category.is_none()
+            && !self.unread
+            && self.since.is_none()
+            && self.older_than.is_none()
+    }
+
+    fn apply(&self, entries: Vec<FeedEntry>) -> color_eyre::Result<Vec<FeedEntry>> {
+        let since = self.since.as_deref().map(parse_time).transpose()?;
+        let before = self.older_than.as_deref().map(parse_time).transpose()?;
+
+        Ok(entries
+            .into_iter()
+            .filter(|e| {
+                let (category, feed) = feed_of(e);
+                self.feed.as_ref().is_none_or(|f| *f == feed)
+                    && self.category.as_ref().is_none_or(|c| *c == category)
+                    && !(self.unread && e.seen)
+                    && since.is_none_or(|t| e.date >= t)
+                    && before.is_none_or(|t| e.date < t)
+            })
+            .collect())
+    }
+}
+
+impl EntrySelection {
+    fn resolve(
+        &self,
+        library: &FeedLibrary,
+        data_dir: &Path,
+    ) -> color_eyre::Result<Vec<FeedEntry>> {
+        if !self.ids.is_empty() {
+            if !self.filter.is_empty() || self.all {
+                bail!("Pass either entry ids or filters, not both");
+            }
+
+            return self
+                .ids
+                .iter()
+                .map(|id| load_entry(library, data_dir, id))
+                .collect();
+        }
+
+        if self.filter.is_empty() && !self.all {
+            bail!("Pass entry ids, filters like --feed or --category, or --all");
+        }
+
+        select_entries(library, &self.filter)
+    }
+}
+
+/// Loads the entries the filter is about, going through the narrowest listing available
+fn select_entries(
+    library: &FeedLibrary,
+    filter: &EntryFilter,
+) -> color_eyre::Result<Vec<FeedEntry>> {
+    let entries = if let Some(feed) = filter.feed.as_ref() {
+        library.get_feed_entries_by_item_slug(feed)?
+    } else if let Some(category) = filter.category.as_ref() {
+        library.get_feed_entries_by_category(category)?
+    } else {
+        let mut entries = vec![];
+        for category in library.feedcategories.iter() {
+            entries.extend(library.get_feed_entries_by_category(&category.title)?);
+        }
+        entries.sort_by_key(|e| std::cmp::Reverse(e.date));
+        entries
+    };
+
+    filter.apply(entries)
+}
+
+/// Entry ids are their paths relative to the categories directory, like in `.later.toml`
+fn load_entry(library: &FeedLibrary, data_dir: &Path, id: &str) -> color_eyre::Result<FeedEntry> {
+    let relative = Path::new(id);
+    if !relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+    {
+        bail!("Invalid entry id: {:?}", id);
+    }
+
+    let path = data_dir.join(defs::DATA_CATEGORIES_DIR).join(relative);
+    library
+        .data
+        .load_entry(&path)
+        .map_err(|_| eyre!("No entry with id {:?}", id))
+}
+
+fn entry_record(library: &mut FeedLibrary, data_dir: &Path, entry: &FeedEntry) -> EntryRecord {
+    let (category, feed) = feed_of(entry);
+    let id = entry
+        .filepath
+        .strip_prefix(data_dir.join(defs::DATA_CATEGORIES_DIR))
+        .unwrap_or(&entry.filepath)
+        .to_string_lossy()
+        .to_string();
+
+    EntryRecord {
+        read_later: library.is_in_read_later(&entry.filepath.to_string_lossy()),
+        id,
+        category,
+        feed,
+        title: entry.title.clone(),
+        date: entry.date.to_rfc3339(),
+        url: entry.url.clone(),
+        author: entry.author.clone(),
+        seen: entry.seen,
+    }
+}
+
+/// The category and feed slug of an entry, taken from where its file is
+fn feed_of(entry: &FeedEntry) -> (String, String) {
+    let feedir = entry.filepath.parent();
+    let name = |p: Option<&Path>| {
+        p.and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+
+    (name(feedir.and_then(|p| p.parent())), name(feedir))
+}
+
+/// Parses the points in time taken by `--since` and `--older-than`
+fn parse_time(value: &str) -> color_eyre::Result<DateTime<Utc>> {
+    parse_time_at(value, Utc::now())
+}
+
+fn parse_time_at(value: &str, now: DateTime<Utc>) -> color_eyre::Result<DateTime<Utc>> {
+    let value = value.trim();
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return date
+            .and_hms_opt(0, 0, 0)
+            .and_then(|t| t.and_local_timezone(Local).earliest())
+            .map(|t| t.with_timezone(&Utc))
+            .ok_or_else(|| eyre!("Invalid date: {:?}", value));
+    }
+
+    let invalid = || eyre!("Invalid time {:?}, use a date or a span like 7d", value);
+    let (amount, unit) = value
+        .char_indices()
+        .last()
+        .map(|(i, _)| value.split_at(i))
+        .ok_or_else(invalid)?;
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+
+    let span = match unit {
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        "w" => Duration::try_weeks(amount),
+        _ => None,
+    };
+
+    span.and_then(|span| now.checked_sub_signed(span))
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time() {
+        let now = DateTime::parse_from_rfc3339("2024-05-10T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(parse_time_at("7d", now).unwrap(), now - Duration::days(7));
+        assert_eq!(parse_time_at("2w", now).unwrap(), now - Duration::weeks(2));
+        assert_eq!(
+            parse_time_at("30m", now).unwrap(),
+            now - Duration::minutes(30)
+        );
+        assert_eq!(
+            parse_time_at("2024-05-01T08:00:00+02:00", now).unwrap(),
+            now - Duration::days(9) - Duration::hours(6)
+        );
+        assert!(parse_time_at("2024-05-01", now).is_ok());
+        assert!(parse_time_at("7y", now).is_err());
+        assert!(parse_time_at("soon", now).is_err());
+        assert!(parse_time_at("5é", now).is_err());
+        assert!(parse_time_at("99999999999999d", now).is_err());
+        assert!(parse_time_at("200000000w", now).is_err());
+        assert!(parse_time_at("9223372036854775807m", now).is_err());
+        assert!(parse_time_at("", now).is_err());
+    }
+}
@@ -0,0 +1,180 @@
+use std::{
+    fmt::Display,
+    io::{self, Write},
+    process::ExitCode,
+};
+
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{error, info};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    /// Messages meant to be read by people
+    #[default]
+    Text,
+    /// A JSON array with one object per record
+    Json,
+    /// Tab-separated values, with a header line
+    Tsv,
+}
+
+/// What a CLI command prints. In text mode messages go straight to stdout; otherwise only the
+/// records are kept and printed at the end, in the requested format, so scripts get a stable
+/// output no matter how the messages are worded.
+pub struct Output {
+    format: OutputFormat,
+    records: Vec<Value>,
+    failed: bool,
+}
+
+impl Output {
+    pub fn new(format: OutputFormat) -> Self {
+        Self {
+            format,
+            records: vec![],
+            failed: false,
+        }
+    }
+
+    pub fn is_text(&self) -> bool {
+        self.format == OutputFormat::Text
+    }
+
+    /// A message for people, logged and shown in text mode only
+    pub fn message(&self, message: impl Display) {
+        info!("{message}");
+        if self.is_text() {
+            println!("{message}");
+        }
+    }
+
+    /// Reports an error and makes the command exit with a failure code. Outside of text mode it
+    /// goes to stderr, so it doesn't get mixed with the records.
+    pub fn fail(&mut self, message: impl Display) {
+        error!("{message}");
+        if self.is_text() {
+            println!("{message}");
+        } else {
+            eprintln!("{message}");
+        }
+        self.failed = true;
+    }
+
+    /// Questions for the user go to stderr when stdout is meant for a script
+    pub fn prompt(&self, message: impl Display) -> io::Result<()> {
+        if self.is_text() {
+            print!("{message}");
+            io::stdout().flush()
+        } else {
+            eprint!("{message}");
+            io::stderr().flush()
+        }
+    }
+
+    pub fn record<T: Serialize>(&mut self, record: &T) {
+        if self.is_text() {
+            return;
+        }
+
+        match serde_json::to_value(record) {
+            Ok(value) => self.records.push(value),
+            Err(e) => error!("Couldn't serialize output record: {e}"),
+        }
+    }
+
+    /// Prints the records and returns the exit code of the command
+    pub fn finish(self) -> color_eyre::Result<ExitCode> {
+        match self.format {
+            OutputFormat::Text => {}
+            OutputFormat::Json => {
+                println!("{}", serde_json::to_string_pretty(&self.records)?);
+            }
+            OutputFormat::Tsv => print!("{}", to_tsv(&self.records)),
+        }
+
+        Ok(if self.failed {
+            ExitCode::FAILURE
+        } else {
+            ExitCode::SUCCESS
+        })
+    }
+}
+
+fn to_tsv(records: &[Value]) -> String {
+    let Some(Value::Object(first)) = records.first() else {
+        return String::new();
+    };
+
+    let columns: Vec<&String> = first.keys().collect();
+    let mut tsv = columns
+        .iter()
+        .map(|c| c.as_str())
+        .collect::<Vec<_>>()
+        .join("\t");
+    tsv.push('\n');
+
+    for record in records.iter() {
+        let row: Vec<String> = columns
+            .iter()
+            .map(|c| tsv_field(record.get(c.as_str()).unwrap_or(&Value::Null)))
+            .collect();
+        tsv.push_str(&row.join("\t"));
+        tsv.push('\n');
+    }
+
+    tsv
+}
+
+fn tsv_field(value: &Value) -> String {
+    let text = match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+
+    text.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Row {
+        title: String,
+        unread: usize,
+        error: Option<String>,
+    }
+
+    #[test]
+    fn test_tsv_keeps_field_order_and_escapes() {
+        let rows = [
+            Row {
+                title: String::from("Tabs\tand\nlines"),
+                unread: 3,
+                error: None,
+            },
+            Row {
+                title: String::from("Plain"),
+                unread: 0,
+                error: Some(String::from("C:\\oops")),
+            },
+        ];
+
+        let values: Vec<Value> = rows
+            .iter()
+            .map(|r| serde_json::to_value(r).unwrap())
+            .collect();
+
+        assert_eq!(
+            to_tsv(&values),
+            "title\tunread\terror\nTabs\\tand\\nlines\t3\t\nPlain\t0\tC:\\\\oops\n"
+        );
+        assert_eq!(to_tsv(&[]), "");
+    }
+}
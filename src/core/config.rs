@@ -5,13 +5,36 @@ use std::io::{ErrorKind, Write};
 use std::path::{Path, PathBuf};
 
 use crate::core::defs::CONFIG_FILE;
+use crate::core::feed::greader::GReaderConfig;
 use crate::core::hooks::AppHooks;
+use crate::core::ui::graphics::ImageProtocol;
+use crate::core::ui::keymap::KeyConfig;
 
 #[derive(Serialize, Deserialize)]
 pub struct Config {
     pub datapath: PathBuf,
     #[serde(default)]
     pub hooks: Option<AppHooks>,
+    #[serde(default)]
+    pub greader: Option<GReaderConfig>,
+    #[serde(default)]
+    pub keys: Option<KeyConfig>,
+    /// Whether the TUI captures the mouse. Turning it off leaves selecting text to the terminal.
+    #[serde(default = "default_mouse")]
+    pub mouse: bool,
+    /// How the reader draws article images: `auto`, `kitty`, `iterm2`, `sixel` or `off`
+    #[serde(default)]
+    pub images: ImageProtocol,
+    /// Command to edit entries with, instead of `$VISUAL` or `$EDITOR`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editor: Option<String>,
+    /// Command to read entries with, instead of `$PAGER`. It gets the Markdown on its input.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pager: Option<String>,
+}
+
+fn default_mouse() -> bool {
+    true
 }
 
 pub struct ConfigStore {
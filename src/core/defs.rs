@@ -7,3 +7,7 @@ pub const DATA_FEED: &str = ".feed.toml";
 pub const LOG_BASE_DIR: &str = "bulletty";
 pub const LOG_SUBDIR: &str = "logs";
 pub const DATA_READ_LATER: &str = ".later.toml";
+pub const DATA_PRUNED: &str = ".pruned.toml";
+pub const DATA_FEED_STATE: &str = ".state.toml";
+pub const DATA_GREADER: &str = ".greader.toml";
+pub const DATA_CATEGORY_FOLDERS: &str = ".folders.toml";
@@ -13,7 +13,19 @@ pub struct FeedEntry {
     pub text: String,
 
     pub lastupdated: DateTime<Utc>,
+
+    /// The file attached to the entry, like a podcast episode, and its MIME type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enclosure_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enclosure_type: Option<String>,
+
+    /// Comes from the feed's state file. Entry files written by older versions still have it in
+    /// their front matter, which is only read to migrate it.
+    #[serde(default, skip_serializing)]
     pub seen: bool,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub read_at: Option<DateTime<Utc>>,
 
     #[serde(skip_serializing, skip_deserializing)]
     pub filepath: PathBuf,
@@ -1,4 +1,10 @@
-use std::{path::PathBuf, str::FromStr};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    str::FromStr,
+    thread,
+};
 
 use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use color_eyre::eyre::{bail, eyre};
@@ -11,30 +17,50 @@ use tracing::error;
 use url::Url;
 
 use crate::core::{
-    feed::{feedentry::FeedEntry, feedutils, html},
+    feed::{feedentry::FeedEntry, feedutils, html, jsonfeed},
     library::feeditem::FeedItem,
 };
 
-pub fn get_feed_with_data(url: &str) -> color_eyre::Result<(FeedItem, String)> {
-    let client = Client::builder()
-        .user_agent(format!("bulletty/{}", env!("CARGO_PKG_VERSION")))
-        .build()?;
+const MEDIA_RSS_NAMESPACE: &str = "http://search.yahoo.com/mrss/";
 
-    let response = client.get(url).send()?;
+/// The files read from directory sources
+const FEED_FILE_EXTENSIONS: [&str; 4] = ["xml", "rss", "atom", "json"];
 
-    if !response.status().is_success() {
-        return Err(eyre!(
-            "Request to \"{}\" returned status code {:?}",
-            url,
-            response.status()
-        ));
+/// Feeds whose URL starts with this are generated by running the rest of it as a shell command,
+/// its output being the feed
+pub const EXEC_PREFIX: &str = "exec:";
+
+pub fn get_feed_with_data(url: &str) -> color_eyre::Result<(FeedItem, Option<String>)> {
+    get_filtered_feed_with_data(url, None)
+}
+
+/// Like `get_feed_with_data`, passing the fetched body through `filter` before parsing it. The
+/// text is None for directories, since they're made of several feeds: their entries come from
+/// `get_feed_entries`.
+pub fn get_filtered_feed_with_data(
+    url: &str,
+    filter: Option<&str>,
+) -> color_eyre::Result<(FeedItem, Option<String>)> {
+    let local = local_path(url);
+
+    // relative paths wouldn't mean the same thing once the feed is in the library
+    let url = match local.as_ref() {
+        Some(path) if url.starts_with('.') => path.to_string_lossy().to_string(),
+        _ => url.to_string(),
+    };
+
+    if let Some(dir) = local.as_ref().filter(|p| p.is_dir()) {
+        let mut feed = directory_feed(dir, &url);
+        feed.filter = filter.map(String::from);
+        return Ok((feed, None));
     }
 
-    let body = response.text()?;
+    let body = fetch_source(&url, filter)?;
 
     // If the response is HTML try to follow metadata feed links
-    if html::is_html(&body) {
-        let url = Url::from_str(url)?; // Fails with same error as the reqwest send() above
+    if local.is_none() && !url.starts_with(EXEC_PREFIX) && filter.is_none() && html::is_html(&body)
+    {
+        let url = Url::from_str(&url)?; // Fails with same error as the reqwest send() above
         let link_parser = match html::LinkParser::new(&body, &url) {
             Ok(p) => p,
             Err(html::ParseError::TooLarge) => {
@@ -44,12 +70,159 @@ pub fn get_feed_with_data(url: &str) -> color_eyre::Result<(FeedItem, String)> {
 
         return link_parser
             .into_iter()
+            .filter(|feed_url| is_web_url(feed_url))
             .take(3)
             .find_map(|feed_url| get_feed_with_data(&feed_url).ok())
             .ok_or_else(|| eyre!("No embedded RSS/Atom feed links found at \"{url}\""));
     }
 
-    Ok((parse(&body, url)?, body))
+    let mut feed = parse(&body, &url)?;
+    feed.filter = filter.map(String::from);
+
+    Ok((feed, Some(body)))
+}
+
+/// Whether a feed URL is fetched over HTTP. Sources that come from somewhere else than the user,
+/// like an imported OPML file, a sync server or an HTML page, have to be, as `exec:` and local
+/// sources would let them run commands or read files.
+pub fn is_web_url(url: &str) -> bool {
+    Url::parse(url).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+/// The path of local feeds: `file://` URLs, and absolute paths or paths starting with `~` or `.`
+pub fn local_path(url: &str) -> Option<PathBuf> {
+    if url.starts_with("file://") {
+        return Url::parse(url).ok()?.to_file_path().ok();
+    }
+
+    if url.contains("://") || url.starts_with(EXEC_PREFIX) {
+        return None;
+    }
+
+    if let Some(rest) = url.strip_prefix("~/") {
+        return std::env::home_dir().map(|home| home.join(rest));
+    }
+
+    let path = Path::new(url);
+    if path.is_absolute() || url.starts_with('.') {
+        return std::path::absolute(path).ok();
+    }
+
+    None
+}
+
+/// A directory of feed files is a single feed, named after the directory
+fn directory_feed(dir: &Path, url: &str) -> FeedItem {
+    let title = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| url.to_string());
+
+    FeedItem {
+        description: format!("Feeds in {}", dir.display()),
+        url: Url::from_directory_path(dir)
+            .map(|u| u.to_string())
+            .unwrap_or_else(|_| url.to_string()),
+        feed_url: url.to_string(),
+        author: title.clone(),
+        slug: slugify(&title),
+        title,
+        ..Default::default()
+    }
+}
+
+/// The feed files in a directory, the ones a generator dropped there
+fn directory_feed_files(dir: &Path) -> color_eyre::Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|e| FEED_FILE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+        })
+        .collect();
+
+    files.sort();
+    Ok(files)
+}
+
+/// Gets the text of a feed: by running its command for `exec:` sources, reading it for local
+/// files, or else over HTTP. Then it goes through the filter, if there's one.
+fn fetch_source(url: &str, filter: Option<&str>) -> color_eyre::Result<String> {
+    let body = if let Some(command) = url.strip_prefix(EXEC_PREFIX) {
+        run_source_command(command.trim(), None)?
+    } else if let Some(path) = local_path(url) {
+        std::fs::read_to_string(&path)
+            .map_err(|e| eyre!("Couldn't read \"{}\": {}", path.display(), e))?
+    } else {
+        fetch_url(url)?
+    };
+
+    match filter {
+        Some(filter) => run_source_command(filter, Some(&body)),
+        None => Ok(body),
+    }
+}
+
+fn fetch_url(url: &str) -> color_eyre::Result<String> {
+    let client = Client::builder()
+        .user_agent(format!("bulletty/{}", env!("CARGO_PKG_VERSION")))
+        .build()?;
+
+    let response = client.get(url).send()?;
+
+    if !response.status().is_success() {
+        return Err(eyre!(
+            "Request to \"{}\" returned status code {:?}",
+            url,
+            response.status()
+        ));
+    }
+
+    Ok(response.text()?)
+}
+
+/// Runs a command through `sh -c`, feeding it `input`, and returns what it printed. Fails when
+/// the command does, with what it printed to stderr.
+fn run_source_command(command: &str, input: Option<&str>) -> color_eyre::Result<String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| eyre!("Couldn't run {:?}: {}", command, e))?;
+
+    // written from another thread, so a command printing before it read all of its input
+    // doesn't block on a full pipe
+    let output = thread::scope(|scope| {
+        if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+            scope.spawn(move || {
+                // the command may stop reading early, which is up to it
+                let _ = stdin.write_all(input.as_bytes());
+            });
+        }
+
+        child.wait_with_output()
+    })?;
+
+    if !output.status.success() {
+        bail!(
+            "{:?} exited with {}: {}",
+            command,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
 pub fn get_feed(url: &str) -> color_eyre::Result<FeedItem> {
@@ -58,6 +231,10 @@ pub fn get_feed(url: &str) -> color_eyre::Result<FeedItem> {
 }
 
 fn parse(doc: &str, feed_url: &str) -> color_eyre::Result<FeedItem> {
+    if jsonfeed::is_json_feed(doc) {
+        return jsonfeed::parse(doc, feed_url);
+    }
+
     let mut feed = FeedItem::default();
 
     let doc = roxmltree::Document::parse(doc)?;
@@ -117,21 +294,23 @@ fn parse(doc: &str, feed_url: &str) -> color_eyre::Result<FeedItem> {
 }
 
 pub fn get_feed_entries(feed: &FeedItem) -> color_eyre::Result<Vec<FeedEntry>> {
-    let client = Client::builder()
-        .user_agent(format!("bulletty/{}", env!("CARGO_PKG_VERSION")))
-        .build()?;
+    if let Some(dir) = local_path(&feed.feed_url).filter(|p| p.is_dir()) {
+        let mut entries = vec![];
 
-    let response = client.get(&feed.feed_url).send()?;
+        // a file that doesn't parse may still be being written, so it's only skipped
+        for file in directory_feed_files(&dir)? {
+            match fetch_source(&file.to_string_lossy(), feed.filter.as_deref())
+                .and_then(|body| get_feed_entries_doc(&body, &feed.author))
+            {
+                Ok(file_entries) => entries.extend(file_entries),
+                Err(e) => error!("Skipping feed file {}: {}", file.display(), e),
+            }
+        }
 
-    if !response.status().is_success() {
-        return Err(eyre!(
-            "Request to \"{}\" returned status code {:?}",
-            feed.feed_url,
-            response.status()
-        ));
+        return Ok(entries);
     }
 
-    let body = response.text()?;
+    let body = fetch_source(&feed.feed_url, feed.filter.as_deref())?;
     get_feed_entries_doc(&body, &feed.author)
 }
 
@@ -139,6 +318,10 @@ pub fn get_feed_entries_doc(
     doctxt: &str,
     defaultauthor: &str,
 ) -> color_eyre::Result<Vec<FeedEntry>> {
+    if jsonfeed::is_json_feed(doctxt) {
+        return jsonfeed::get_feed_entries_doc(doctxt, defaultauthor);
+    }
+
     let doc = roxmltree::Document::parse(doctxt)?;
 
     let feed_tag = doc.root();
@@ -220,6 +403,21 @@ pub fn get_feed_entries_doc(
             .unwrap_or("NOURL")
             .to_string();
 
+        // enclosure extraction: RSS `<enclosure>`, Atom `<link rel="enclosure">` or Media RSS
+        let enclosure = entry.descendants().find_map(|t| {
+            let url = match t.tag_name().name() {
+                "enclosure" => t.attribute("url"),
+                "link" if t.attribute("rel") == Some("enclosure") => t.attribute("href"),
+                "content" if t.tag_name().namespace() == Some(MEDIA_RSS_NAMESPACE) => {
+                    t.attribute("url")
+                }
+                _ => None,
+            }?;
+
+            Some((url.to_string(), t.attribute("type").map(String::from)))
+        });
+        let (enclosure_url, enclosure_type) = enclosure.unzip();
+
         // feed creation
         let fe = FeedEntry {
             title: entry
@@ -236,7 +434,10 @@ pub fn get_feed_entries_doc(
                 .unwrap_or_default(),
             description: desc,
             lastupdated: Utc::now(),
+            enclosure_url,
+            enclosure_type: enclosure_type.flatten(),
             seen: false,
+            read_at: None,
             filepath: PathBuf::default(),
         };
 
@@ -342,6 +543,15 @@ fn get_description_content(entry: &Node) -> (String, String) {
         .find(|t| t.tag_name().name() == "description" || t.tag_name().name() == "summary")
         .and_then(|t| t.text());
 
+    description_and_content(content, description)
+}
+
+/// Turns the HTML content and description of an entry into its Markdown text and a short,
+/// plain description. Either one is used in place of the other when missing.
+pub fn description_and_content(
+    content: Option<&str>,
+    description: Option<&str>,
+) -> (String, String) {
     let content_text = match content.as_ref() {
         Some(text) => parse_html(text),
         None => match description.as_ref() {
@@ -575,6 +785,129 @@ mod tests {
         assert_eq!(b.date, expected_b_date);
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn exec_sources_and_filters_produce_the_feed() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let script = dir.path().join("feed.xml");
+        std::fs::write(
+            &script,
+            r#"<rss version="2.0"><channel><title>Build log</title>
+            <item><title>Build 1 brokne</title><link>https://ci.example/1</link></item>
+            </channel></rss>"#,
+        )
+        .unwrap();
+
+        let url = format!("exec: cat '{}'", script.display());
+        let (feed, _) = get_filtered_feed_with_data(&url, Some("sed s/brokne/broken/")).unwrap();
+        assert_eq!(feed.title, "Build log");
+        assert_eq!(feed.feed_url, url);
+        assert_eq!(feed.filter.as_deref(), Some("sed s/brokne/broken/"));
+
+        let entries = get_feed_entries(&feed).unwrap();
+        assert_eq!(entries[0].title, "Build 1 broken");
+
+        let failing = FeedItem {
+            feed_url: String::from("exec:echo oops >&2; exit 3"),
+            ..Default::default()
+        };
+        let error = get_feed_entries(&failing).unwrap_err().to_string();
+        assert!(error.contains("oops"), "{error}");
+    }
+
+    #[test]
+    fn local_files_and_directories_are_feeds() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let feeds = dir.path().join("ci-feeds");
+        std::fs::create_dir(&feeds).unwrap();
+
+        std::fs::write(
+            feeds.join("nightly.xml"),
+            r#"<rss version="2.0"><channel><title>Nightly</title>
+            <item><title>Nightly passed</title><link>https://ci.example/n/1</link></item>
+            </channel></rss>"#,
+        )
+        .unwrap();
+        std::fs::write(
+            feeds.join("release.json"),
+            r#"{"version": "https://jsonfeed.org/version/1.1", "title": "Release",
+                "items": [{"id": "1", "title": "Release built", "content_text": "ok"}]}"#,
+        )
+        .unwrap();
+        std::fs::write(feeds.join("partial.xml"), "<rss><chan").unwrap();
+        std::fs::write(feeds.join("notes.txt"), "not a feed").unwrap();
+
+        let file_url = Url::from_file_path(feeds.join("nightly.xml")).unwrap();
+        let (feed, text) = get_feed_with_data(file_url.as_str()).unwrap();
+        assert_eq!(feed.title, "Nightly");
+        assert!(text.is_some());
+
+        let (feed, text) = get_feed_with_data(&feeds.to_string_lossy()).unwrap();
+        assert_eq!(feed.title, "ci-feeds");
+        assert_eq!(feed.feed_url, feeds.to_string_lossy());
+        assert!(text.is_none());
+
+        let mut titles: Vec<String> = get_feed_entries(&feed)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.title)
+            .collect();
+        titles.sort();
+        assert_eq!(titles, vec!["Nightly passed", "Release built"]);
+
+        assert_eq!(local_path("https://example.com/feed"), None);
+        assert_eq!(local_path("exec:cat /tmp/feed.xml"), None);
+        assert!(local_path("./feeds/a.xml").unwrap().is_absolute());
+    }
+
+    #[test]
+    fn get_feed_entries_doc_parses_enclosures() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0">
+  <channel>
+    <title>Podcast</title>
+    <item>
+      <title>Episode 1</title>
+      <link>https://example.com/ep1</link>
+      <enclosure url="https://example.com/ep1.mp3" length="1234" type="audio/mpeg"/>
+    </item>
+    <item>
+      <title>Show notes</title>
+      <link>https://example.com/notes</link>
+    </item>
+  </channel>
+</rss>"#;
+
+        let entries = get_feed_entries_doc(xml, "Dan").expect("failed to parse RSS entries");
+        assert_eq!(
+            entries[0].enclosure_url.as_deref(),
+            Some("https://example.com/ep1.mp3")
+        );
+        assert_eq!(entries[0].enclosure_type.as_deref(), Some("audio/mpeg"));
+        assert_eq!(entries[1].enclosure_url, None);
+
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Papers</title>
+  <entry>
+    <title>A paper</title>
+    <link href="https://example.org/paper"/>
+    <link rel="enclosure" href="https://example.org/paper.pdf" type="application/pdf"/>
+    <updated>2003-12-13T18:30:02Z</updated>
+  </entry>
+</feed>"#;
+
+        let entries = get_feed_entries_doc(xml, "Eve").expect("failed to parse Atom entries");
+        assert_eq!(
+            entries[0].enclosure_url.as_deref(),
+            Some("https://example.org/paper.pdf")
+        );
+        assert_eq!(
+            entries[0].enclosure_type.as_deref(),
+            Some("application/pdf")
+        );
+    }
+
     #[test]
     fn get_feed_entries_doc_parses_atom_entries_variants() {
         let xml = r#"<?xml version="1.0" encoding="utf-8"?>
@@ -0,0 +1,352 @@
+use std::process::Command;
+
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::{bail, eyre};
+use reqwest::blocking::{Client, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use url::{Url, form_urlencoded};
+
+use crate::core::feed::{feedparser, feedutils};
+
+pub const TAG_READ: &str = "user/-/state/com.google/read";
+pub const TAG_STARRED: &str = "user/-/state/com.google/starred";
+const READING_LIST: &str = "user/-/state/com.google/reading-list";
+const LABEL_PREFIX: &str = "user/-/label/";
+const PAGE_SIZE: usize = 100;
+
+/// The `[greader]` table of `config.toml`
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct GReaderConfig {
+    /// Base URL of the API, e.g. `https://freshrss.example.com/api/greader.php`
+    pub url: String,
+    pub username: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// Shell command printing the password, so it doesn't have to live in the config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_command: Option<String>,
+    /// How many of the latest entries are pulled on each sync
+    #[serde(default = "default_max_entries")]
+    pub max_entries: usize,
+}
+
+fn default_max_entries() -> usize {
+    200
+}
+
+impl GReaderConfig {
+    fn password(&self) -> color_eyre::Result<String> {
+        if let Some(password) = &self.password {
+            return Ok(password.clone());
+        }
+
+        let Some(cmd) = &self.password_command else {
+            bail!("Set either `password` or `password_command` under [greader]");
+        };
+
+        let output = Command::new("sh").arg("-c").arg(cmd).output()?;
+        if !output.status.success() {
+            bail!("`password_command` exited with status {}", output.status);
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+}
+
+pub struct Subscription {
+    pub id: String,
+    pub title: String,
+    pub feed_url: String,
+    pub url: String,
+    pub category: Option<String>,
+}
+
+pub struct ReaderItem {
+    pub id: String,
+    pub feed_id: String,
+    pub title: String,
+    pub url: String,
+    pub author: String,
+    pub date: DateTime<Utc>,
+    pub description: String,
+    pub text: String,
+    pub read: bool,
+    pub starred: bool,
+}
+
+#[derive(Deserialize)]
+struct SubscriptionList {
+    #[serde(default)]
+    subscriptions: Vec<SubscriptionJson>,
+}
+
+#[derive(Deserialize)]
+struct SubscriptionJson {
+    id: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    url: String,
+    #[serde(default, rename = "htmlUrl")]
+    html_url: String,
+    #[serde(default)]
+    categories: Vec<CategoryJson>,
+}
+
+#[derive(Deserialize)]
+struct CategoryJson {
+    id: String,
+    #[serde(default)]
+    label: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StreamContents {
+    #[serde(default)]
+    items: Vec<ItemJson>,
+    #[serde(default)]
+    continuation: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ItemJson {
+    id: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    published: i64,
+    #[serde(default)]
+    author: String,
+    #[serde(default)]
+    canonical: Vec<LinkJson>,
+    #[serde(default)]
+    alternate: Vec<LinkJson>,
+    #[serde(default)]
+    summary: Option<ContentJson>,
+    #[serde(default)]
+    content: Option<ContentJson>,
+    #[serde(default)]
+    categories: Vec<String>,
+    origin: OriginJson,
+}
+
+#[derive(Deserialize)]
+struct LinkJson {
+    href: String,
+}
+
+#[derive(Deserialize)]
+struct ContentJson {
+    #[serde(default)]
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct OriginJson {
+    #[serde(rename = "streamId")]
+    stream_id: String,
+}
+
+/// A client for the Google Reader API, as implemented by FreshRSS, Miniflux and others
+pub struct GReaderClient {
+    base: String,
+    client: Client,
+    auth: String,
+    token: Option<String>,
+}
+
+impl GReaderClient {
+    pub fn login(config: &GReaderConfig) -> color_eyre::Result<Self> {
+        let client = Client::builder()
+            .user_agent(format!("bulletty/{}", env!("CARGO_PKG_VERSION")))
+            .build()?;
+
+        let base = config.url.trim_end_matches('/').to_string();
+        let body = form_urlencoded::Serializer::new(String::new())
+            .append_pair("Email", &config.username)
+            .append_pair("Passwd", &config.password()?)
+            .finish();
+
+        let response = client
+            .post(format!("{base}/accounts/ClientLogin"))
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body(body)
+            .send()?;
+
+        if !response.status().is_success() {
+            bail!("Login to {} failed with status {}", base, response.status());
+        }
+
+        let text = response.text()?;
+        let auth = text
+            .lines()
+            .find_map(|l| l.strip_prefix("Auth="))
+            .ok_or_else(|| eyre!("Login to {} didn't return an auth token", base))?
+            .to_string();
+
+        info!("Logged in to {}", base);
+
+        Ok(Self {
+            base,
+            client,
+            auth,
+            token: None,
+        })
+    }
+
+    pub fn subscriptions(&self) -> color_eyre::Result<Vec<Subscription>> {
+        let list: SubscriptionList = self.get_json("subscription/list", &[("output", "json")])?;
+
+        Ok(list
+            .subscriptions
+            .into_iter()
+            .map(|s| Subscription {
+                category: s.categories.iter().find_map(|c| {
+                    c.label
+                        .clone()
+                        .or_else(|| c.id.strip_prefix(LABEL_PREFIX).map(String::from))
+                }),
+                id: s.id,
+                title: s.title,
+                feed_url: s.url,
+                url: s.html_url,
+            })
+            .collect())
+    }
+
+    /// The latest `max` entries of the reading list, newest first
+    pub fn items(&self, max: usize) -> color_eyre::Result<Vec<ReaderItem>> {
+        let mut items = vec![];
+        let mut continuation: Option<String> = None;
+
+        while items.len() < max {
+            let count = PAGE_SIZE.min(max - items.len()).to_string();
+            let mut query = vec![("output", "json"), ("n", count.as_str())];
+            if let Some(c) = continuation.as_deref() {
+                query.push(("c", c));
+            }
+
+            let page: StreamContents =
+                self.get_json(&format!("stream/contents/{READING_LIST}"), &query)?;
+
+            let last_page = page.items.is_empty() || page.continuation.is_none();
+            items.extend(page.items.into_iter().map(ReaderItem::from));
+
+            if last_page {
+                break;
+            }
+            continuation = page.continuation;
+        }
+
+        Ok(items)
+    }
+
+    /// Adds or removes `tag` from the given items
+    pub fn edit_tag(&mut self, ids: &[String], tag: &str, add: bool) -> color_eyre::Result<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let token = self.token()?;
+        let mut body = form_urlencoded::Serializer::new(String::new());
+        for id in ids {
+            body.append_pair("i", id);
+        }
+        body.append_pair(if add { "a" } else { "r" }, tag);
+        body.append_pair("T", &token);
+
+        let response = self
+            .request(self.client.post(self.api_url("edit-tag", &[])?))
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body(body.finish())
+            .send()?;
+
+        if !response.status().is_success() {
+            bail!(
+                "Editing tag {} failed with status {}",
+                tag,
+                response.status()
+            );
+        }
+
+        Ok(())
+    }
+
+    fn token(&mut self) -> color_eyre::Result<String> {
+        if let Some(token) = &self.token {
+            return Ok(token.clone());
+        }
+
+        let response = self
+            .request(self.client.get(self.api_url("token", &[])?))
+            .send()?;
+        if !response.status().is_success() {
+            bail!(
+                "Getting a write token failed with status {}",
+                response.status()
+            );
+        }
+
+        let token = response.text()?.trim().to_string();
+        self.token = Some(token.clone());
+        Ok(token)
+    }
+
+    fn get_json<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> color_eyre::Result<T> {
+        let url = self.api_url(path, query)?;
+        let response = self.request(self.client.get(url.clone())).send()?;
+
+        if !response.status().is_success() {
+            bail!(
+                "Request to {} failed with status {}",
+                url,
+                response.status()
+            );
+        }
+
+        Ok(serde_json::from_str(&response.text()?)?)
+    }
+
+    fn api_url(&self, path: &str, query: &[(&str, &str)]) -> color_eyre::Result<Url> {
+        let mut url = Url::parse(&format!("{}/reader/api/0/{}", self.base, path))?;
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+        Ok(url)
+    }
+
+    fn request(&self, builder: RequestBuilder) -> RequestBuilder {
+        builder.header("Authorization", format!("GoogleLogin auth={}", self.auth))
+    }
+}
+
+impl From<ItemJson> for ReaderItem {
+    fn from(item: ItemJson) -> Self {
+        let content = item.content.as_ref().map(|c| c.content.as_str());
+        let summary = item.summary.as_ref().map(|c| c.content.as_str());
+        let (description, text) = feedparser::description_and_content(content.or(summary), summary);
+
+        ReaderItem {
+            read: item.categories.iter().any(|c| c == TAG_READ),
+            starred: item.categories.iter().any(|c| c == TAG_STARRED),
+            url: item
+                .canonical
+                .first()
+                .or(item.alternate.first())
+                .map(|l| l.href.clone())
+                .unwrap_or_default(),
+            date: DateTime::from_timestamp(item.published, 0).unwrap_or_default(),
+            title: feedutils::normalize_and_truncate(&item.title, 256),
+            author: item.author,
+            feed_id: item.origin.stream_id,
+            id: item.id,
+            description,
+            text,
+        }
+    }
+}
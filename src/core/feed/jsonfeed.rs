@@ -0,0 +1,208 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use slug::slugify;
+
+use crate::core::{
+    feed::{feedentry::FeedEntry, feedparser, feedutils},
+    library::feeditem::FeedItem,
+};
+
+/// A feed in the JSON Feed format (https://jsonfeed.org), versions 1 and 1.1
+#[derive(Deserialize)]
+struct JsonFeed {
+    version: String,
+    title: String,
+    home_page_url: Option<String>,
+    description: Option<String>,
+    author: Option<JsonAuthor>,
+    #[serde(default)]
+    authors: Vec<JsonAuthor>,
+    #[serde(default)]
+    items: Vec<JsonItem>,
+}
+
+#[derive(Deserialize)]
+struct JsonAuthor {
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct JsonItem {
+    id: serde_json::Value,
+    url: Option<String>,
+    external_url: Option<String>,
+    title: Option<String>,
+    content_html: Option<String>,
+    content_text: Option<String>,
+    summary: Option<String>,
+    date_published: Option<String>,
+    date_modified: Option<String>,
+    author: Option<JsonAuthor>,
+    #[serde(default)]
+    authors: Vec<JsonAuthor>,
+    #[serde(default)]
+    attachments: Vec<JsonAttachment>,
+}
+
+#[derive(Deserialize)]
+struct JsonAttachment {
+    url: String,
+    mime_type: Option<String>,
+}
+
+/// JSON feeds are told apart from XML ones by their first character
+pub fn is_json_feed(doc: &str) -> bool {
+    doc.trim_start().starts_with('{')
+}
+
+fn parse_doc(doc: &str) -> color_eyre::Result<JsonFeed> {
+    let feed: JsonFeed = serde_json::from_str(doc)?;
+
+    if !feed.version.starts_with("https://jsonfeed.org/version/") {
+        color_eyre::eyre::bail!("Unknown JSON Feed version {:?}", feed.version);
+    }
+
+    Ok(feed)
+}
+
+/// The first author name of either the 1.1 `authors` list or the 1.0 `author` field
+fn author_name(authors: &[JsonAuthor], author: &Option<JsonAuthor>) -> Option<String> {
+    authors
+        .iter()
+        .chain(author.iter())
+        .find_map(|a| a.name.clone())
+}
+
+pub fn parse(doc: &str, feed_url: &str) -> color_eyre::Result<FeedItem> {
+    let json = parse_doc(doc)?;
+    let title = feedutils::normalize_and_truncate(&json.title, 256);
+
+    Ok(FeedItem {
+        description: json.description.clone().unwrap_or_else(|| title.clone()),
+        url: json
+            .home_page_url
+            .clone()
+            .unwrap_or_else(|| feed_url.to_string()),
+        feed_url: feed_url.to_string(),
+        author: author_name(&json.authors, &json.author).unwrap_or_else(|| title.clone()),
+        slug: slugify(&title),
+        title,
+        ..Default::default()
+    })
+}
+
+pub fn get_feed_entries_doc(doc: &str, defaultauthor: &str) -> color_eyre::Result<Vec<FeedEntry>> {
+    let json = parse_doc(doc)?;
+    let feedauthor = author_name(&json.authors, &json.author);
+
+    Ok(json
+        .items
+        .into_iter()
+        .map(|item| {
+            let content = item
+                .content_html
+                .as_deref()
+                .or(item.content_text.as_deref());
+            let (description, text) =
+                feedparser::description_and_content(content, item.summary.as_deref());
+
+            let url = item
+                .url
+                .or(item.external_url)
+                .or_else(|| item.id.as_str().map(String::from))
+                .unwrap_or_else(|| String::from("NOURL"));
+
+            let date = item
+                .date_published
+                .or(item.date_modified)
+                .and_then(|d| DateTime::parse_from_rfc3339(&d).ok())
+                .map(|d| d.with_timezone(&Utc))
+                .unwrap_or_default();
+
+            let attachment = item.attachments.into_iter().next();
+
+            FeedEntry {
+                title: feedutils::normalize_and_truncate(
+                    item.title.as_deref().unwrap_or(&description),
+                    256,
+                ),
+                description,
+                date,
+                url,
+                author: author_name(&item.authors, &item.author)
+                    .or(feedauthor.clone())
+                    .unwrap_or_else(|| defaultauthor.to_string()),
+                text,
+                lastupdated: Utc::now(),
+                enclosure_url: attachment.as_ref().map(|a| a.url.clone()),
+                enclosure_type: attachment.and_then(|a| a.mime_type),
+                seen: false,
+                read_at: None,
+                filepath: PathBuf::default(),
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEED: &str = r#"{
+        "version": "https://jsonfeed.org/version/1.1",
+        "title": "CI builds",
+        "home_page_url": "https://ci.example",
+        "authors": [{ "name": "Build bot" }],
+        "items": [
+            {
+                "id": "2",
+                "url": "https://ci.example/builds/2",
+                "title": "Build 2 failed",
+                "content_html": "<p>Tests <b>failed</b></p>",
+                "date_published": "2024-05-02T10:00:00Z",
+                "attachments": [{ "url": "https://ci.example/builds/2/log.txt", "mime_type": "text/plain" }]
+            },
+            {
+                "id": "https://ci.example/builds/1",
+                "content_text": "All green",
+                "date_modified": "2024-05-01T10:00:00+02:00",
+                "author": { "name": "Someone" }
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_parse_json_feed() {
+        assert!(is_json_feed(FEED));
+
+        let feed = parse(FEED, "/srv/feeds/ci.json").unwrap();
+        assert_eq!(feed.title, "CI builds");
+        assert_eq!(feed.url, "https://ci.example");
+        assert_eq!(feed.feed_url, "/srv/feeds/ci.json");
+        assert_eq!(feed.author, "Build bot");
+        assert_eq!(feed.slug, "ci-builds");
+
+        let entries = get_feed_entries_doc(FEED, "Default").unwrap();
+        assert_eq!(entries.len(), 2);
+
+        assert_eq!(entries[0].title, "Build 2 failed");
+        assert_eq!(entries[0].url, "https://ci.example/builds/2");
+        assert!(entries[0].text.contains("**failed**"));
+        assert_eq!(entries[0].author, "Build bot");
+        assert_eq!(entries[0].date.to_rfc3339(), "2024-05-02T10:00:00+00:00");
+        assert_eq!(entries[0].enclosure_type.as_deref(), Some("text/plain"));
+
+        assert_eq!(entries[1].title, "All green");
+        assert_eq!(entries[1].url, "https://ci.example/builds/1");
+        assert_eq!(entries[1].author, "Someone");
+        assert_eq!(entries[1].date.to_rfc3339(), "2024-05-01T08:00:00+00:00");
+    }
+
+    #[test]
+    fn test_rejects_other_json() {
+        assert!(parse(r#"{"version": "2", "title": "Nope"}"#, "x").is_err());
+        assert!(parse("{}", "x").is_err());
+    }
+}
@@ -1,4 +1,6 @@
 pub mod feedentry;
 pub mod feedparser;
 pub mod feedutils;
+pub mod greader;
 mod html;
+pub mod jsonfeed;
@@ -1,12 +1,278 @@
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::io::Write;
 use std::process::{Command, Stdio};
 use tracing::{error, info};
 
+use crate::core::{feed::feedentry::FeedEntry, library::feeditem::FeedItem};
+
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct AppHooks {
     pub before_tui: Option<String>,
     pub after_tui: Option<String>,
     pub open_link: Option<String>,
+    pub open_link_rules: Option<Vec<OpenLinkRule>>,
+    pub on_new_entry: Option<EventHook>,
+    pub on_update_finished: Option<EventHook>,
+    pub on_feed_error: Option<EventHook>,
+    pub on_entry_read: Option<EventHook>,
+}
+
+/// Picks the command to open some links with: the ones whose URL matches the `url` regex and
+/// whose MIME type matches `mime`, like `application/pdf` or `video/*`
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct OpenLinkRule {
+    pub command: String,
+    pub url: Option<String>,
+    pub mime: Option<String>,
+}
+
+impl OpenLinkRule {
+    fn matches(&self, link: &LinkContext) -> bool {
+        if self.url.is_none() && self.mime.is_none() {
+            return false;
+        }
+
+        let url_matches = self.url.as_ref().is_none_or(|pattern| {
+            Regex::new(pattern)
+                .map_err(|e| error!("Invalid open_link_rules url {:?}: {}", pattern, e))
+                .is_ok_and(|re| re.is_match(&link.url))
+        });
+
+        let mime_matches = self.mime.as_ref().is_none_or(|pattern| {
+            link.mime_type()
+                .is_some_and(|mime| match pattern.strip_suffix("/*") {
+                    Some(kind) => mime.split('/').next() == Some(kind),
+                    None => mime.eq_ignore_ascii_case(pattern),
+                })
+        });
+
+        url_matches && mime_matches
+    }
+}
+
+/// A link to open and the entry it comes from, for the `open_link` placeholders
+#[derive(Debug, Default, Clone)]
+pub struct LinkContext {
+    pub url: String,
+    pub title: String,
+    pub feed: String,
+    pub category: String,
+    pub author: String,
+    pub path: String,
+    pub enclosure_url: Option<String>,
+    pub enclosure_type: Option<String>,
+}
+
+impl LinkContext {
+    pub fn for_entry(url: &str, entry: &FeedEntry, feed: Option<(String, &FeedItem)>) -> Self {
+        let (category, feed) = feed
+            .map(|(category, feed)| (category, feed.title.clone()))
+            .unwrap_or_default();
+
+        Self {
+            url: url.to_string(),
+            title: entry.title.clone(),
+            feed,
+            category,
+            author: entry.author.clone(),
+            path: entry.filepath.to_string_lossy().to_string(),
+            enclosure_url: entry.enclosure_url.clone(),
+            enclosure_type: entry.enclosure_type.clone(),
+        }
+    }
+
+    /// The enclosure says what it is; other links are guessed from their extension
+    fn mime_type(&self) -> Option<String> {
+        if self.enclosure_url.as_deref() == Some(self.url.as_str())
+            && let Some(mime) = &self.enclosure_type
+        {
+            return Some(mime.clone());
+        }
+
+        let path = url::Url::parse(&self.url)
+            .map(|u| u.path().to_string())
+            .unwrap_or_else(|_| self.url.clone());
+        mime_guess::from_path(path)
+            .first()
+            .map(|m| m.essence_str().to_string())
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        Some(match name {
+            "url" => &self.url,
+            "title" => &self.title,
+            "feed" => &self.feed,
+            "category" => &self.category,
+            "author" => &self.author,
+            "path" => &self.path,
+            "enclosure" => self.enclosure_url.as_deref().unwrap_or_default(),
+            _ => return None,
+        })
+    }
+
+    /// Fills the placeholders in one pass, so braces in the values are left as they are
+    fn fill(&self, word: &str) -> String {
+        let mut filled = String::with_capacity(word.len());
+        let mut rest = word;
+
+        while let Some(start) = rest.find(['%', '{']) {
+            filled.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            if let Some(after) = rest.strip_prefix("%s") {
+                filled.push_str(&self.url);
+                rest = after;
+                continue;
+            }
+
+            let placeholder = rest.strip_prefix('{').and_then(|inner| {
+                let end = inner.find('}')?;
+                Some((self.value(&inner[..end])?, &inner[end + 1..]))
+            });
+            match placeholder {
+                Some((value, after)) => {
+                    filled.push_str(value);
+                    rest = after;
+                }
+                None => {
+                    filled.push_str(&rest[..1]);
+                    rest = &rest[1..];
+                }
+            }
+        }
+
+        filled.push_str(rest);
+        filled
+    }
+}
+
+/// An event hook is either a single command, run for every feed, or a list of commands scoped to
+/// some feeds or categories
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum EventHook {
+    Command(String),
+    Scoped(Vec<ScopedHook>),
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct ScopedHook {
+    pub command: String,
+    /// Feed slugs or titles. With no feeds and no categories, the command runs for every feed.
+    #[serde(default)]
+    pub feeds: Vec<String>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+}
+
+impl ScopedHook {
+    fn is_global(&self) -> bool {
+        self.feeds.is_empty() && self.categories.is_empty()
+    }
+
+    fn matches(&self, category: &str, feed: &FeedItem) -> bool {
+        self.is_global()
+            || self.categories.iter().any(|c| c == category)
+            || self
+                .feeds
+                .iter()
+                .any(|f| *f == feed.slug || *f == feed.title)
+    }
+}
+
+impl EventHook {
+    fn hooks(&self) -> Vec<ScopedHook> {
+        match self {
+            EventHook::Command(command) => vec![ScopedHook {
+                command: command.clone(),
+                ..Default::default()
+            }],
+            EventHook::Scoped(hooks) => hooks.clone(),
+        }
+    }
+}
+
+/// What event hooks receive: as JSON on stdin and as `BULLETTY_<FIELD>` environment variables.
+/// Fields that don't apply to an event are left out.
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct HookEvent {
+    pub event: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feed_title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feed_slug: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feed_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_feeds: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed_feeds: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_entries: Option<usize>,
+}
+
+impl HookEvent {
+    fn feed(event: &'static str, category: &str, feed: &FeedItem) -> Self {
+        Self {
+            event,
+            category: Some(category.to_string()),
+            feed_title: Some(feed.title.clone()),
+            feed_slug: Some(feed.slug.clone()),
+            feed_url: Some(feed.feed_url.clone()),
+            ..Default::default()
+        }
+    }
+
+    fn entry(event: &'static str, category: &str, feed: &FeedItem, entry: &FeedEntry) -> Self {
+        Self {
+            entry_title: Some(entry.title.clone()),
+            entry_url: Some(entry.url.clone()),
+            entry_author: Some(entry.author.clone()),
+            entry_date: Some(entry.date.to_rfc3339()),
+            entry_path: Some(entry.filepath.to_string_lossy().to_string()),
+            ..Self::feed(event, category, feed)
+        }
+    }
+
+    fn env_vars(&self) -> Vec<(String, String)> {
+        let Ok(serde_json::Value::Object(fields)) = serde_json::to_value(self) else {
+            return vec![];
+        };
+
+        fields
+            .into_iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    serde_json::Value::String(s) => s,
+                    other => other.to_string(),
+                };
+                (format!("BULLETTY_{}", key.to_uppercase()), value)
+            })
+            .collect()
+    }
+}
+
+/// How updating a feed went, for `on_update_finished`
+pub struct FeedUpdate {
+    pub category: String,
+    pub feed: FeedItem,
+    pub new_entries: usize,
+    pub failed: bool,
 }
 
 impl AppHooks {
@@ -22,21 +288,21 @@ impl AppHooks {
         }
     }
 
-    pub fn run_open_link(&self, url: &str) -> bool {
-        let Some(ref template) = self.open_link else {
+    /// Opens a link with the command of the first matching `open_link_rules` entry, or with
+    /// `open_link`. Returns false when neither applies, so the system browser is used instead.
+    pub fn run_open_link(&self, link: &LinkContext) -> bool {
+        let Some(args) = self.open_link_command(link) else {
             return false;
         };
-        let cmd = template.replace("%s", url);
-        let mut parts = cmd.split_whitespace();
-        let Some(program) = parts.next() else {
+
+        let Some((program, args)) = args.split_first() else {
             error!("open_link hook is empty after substitution");
             return true;
         };
-        let args: Vec<&str> = parts.collect();
 
-        info!("Running hook 'open_link': {}", cmd);
+        info!("Running hook 'open_link': {} {:?}", program, args);
         match Command::new(program)
-            .args(&args)
+            .args(args)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .output()
@@ -62,6 +328,154 @@ impl AppHooks {
         true
     }
 
+    /// The arguments to open a link with. The template is split into words first, so the values
+    /// put into the placeholders always stay single arguments, whatever characters they have.
+    fn open_link_command(&self, link: &LinkContext) -> Option<Vec<String>> {
+        let rule = self
+            .open_link_rules
+            .iter()
+            .flatten()
+            .find(|rule| rule.matches(link));
+
+        let template = match rule {
+            Some(rule) => &rule.command,
+            None => self.open_link.as_ref()?,
+        };
+
+        let words = match shell_words::split(template) {
+            Ok(words) => words,
+            Err(e) => {
+                error!("Invalid open_link command {:?}: {}", template, e);
+                return Some(vec![]);
+            }
+        };
+
+        Some(words.iter().map(|word| link.fill(word)).collect())
+    }
+
+    pub fn has_entry_read(&self) -> bool {
+        self.on_entry_read.is_some()
+    }
+
+    /// Runs `on_new_entry` for each entry. The first update of a feed brings in its whole
+    /// backlog, so it doesn't count as new entries.
+    pub fn run_new_entries(&self, category: &str, feed: &FeedItem, entries: &[FeedEntry]) {
+        if feed.never_updated() {
+            return;
+        }
+
+        for entry in entries.iter() {
+            let event = HookEvent::entry("new_entry", category, feed, entry);
+            Self::run_event(&self.on_new_entry, category, feed, "on_new_entry", &event);
+        }
+    }
+
+    pub fn run_entry_read(&self, category: &str, feed: &FeedItem, entry: &FeedEntry) {
+        let event = HookEvent::entry("entry_read", category, feed, entry);
+        Self::run_event(&self.on_entry_read, category, feed, "on_entry_read", &event);
+    }
+
+    pub fn run_feed_error(&self, category: &str, feed: &FeedItem, error: &str) {
+        let event = HookEvent {
+            error: Some(error.to_string()),
+            ..HookEvent::feed("feed_error", category, feed)
+        };
+        Self::run_event(&self.on_feed_error, category, feed, "on_feed_error", &event);
+    }
+
+    /// Runs once the update of all feeds is done. Scoped commands only count the feeds in their
+    /// scope, and don't run when none of them was updated.
+    pub fn run_update_finished(&self, updates: &[FeedUpdate]) {
+        let Some(hook) = &self.on_update_finished else {
+            return;
+        };
+
+        for scoped in hook.hooks() {
+            let updates: Vec<&FeedUpdate> = updates
+                .iter()
+                .filter(|u| scoped.matches(&u.category, &u.feed))
+                .collect();
+
+            if updates.is_empty() && !scoped.is_global() {
+                continue;
+            }
+
+            let event = HookEvent {
+                event: "update_finished",
+                updated_feeds: Some(updates.iter().filter(|u| !u.failed).count()),
+                failed_feeds: Some(updates.iter().filter(|u| u.failed).count()),
+                new_entries: Some(updates.iter().map(|u| u.new_entries).sum()),
+                ..Default::default()
+            };
+            Self::run_event_command(&scoped.command, "on_update_finished", &event);
+        }
+    }
+
+    fn run_event(
+        hook: &Option<EventHook>,
+        category: &str,
+        feed: &FeedItem,
+        hook_name: &str,
+        event: &HookEvent,
+    ) {
+        let Some(hook) = hook else {
+            return;
+        };
+
+        for scoped in hook.hooks().iter().filter(|h| h.matches(category, feed)) {
+            Self::run_event_command(&scoped.command, hook_name, event);
+        }
+    }
+
+    /// Event hooks run through `sh -c` like the TUI hooks, but their output is logged instead of
+    /// printed, since they may run while the TUI is drawing
+    fn run_event_command(cmd: &str, hook_name: &str, event: &HookEvent) {
+        info!("Running hook '{}': {}", hook_name, cmd);
+
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg(cmd)
+            .envs(event.env_vars())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+
+        let mut child = match child {
+            Ok(child) => child,
+            Err(e) => {
+                error!("Failed to execute hook '{}': {}", hook_name, e);
+                return;
+            }
+        };
+
+        if let Some(mut stdin) = child.stdin.take() {
+            let json = serde_json::to_string(event).unwrap_or_default();
+            // the command may not read its stdin at all, which is fine
+            let _ = stdin.write_all(json.as_bytes());
+        }
+
+        match child.wait_with_output() {
+            Ok(output) => {
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                let stderr = String::from_utf8_lossy(&output.stderr);
+
+                if !stdout.trim().is_empty() {
+                    info!("{} stdout: {}", hook_name, stdout.trim());
+                }
+
+                if !stderr.trim().is_empty() {
+                    error!("{} stderr: {}", hook_name, stderr.trim());
+                }
+
+                if !output.status.success() {
+                    error!("Hook '{}' exited with status: {}", hook_name, output.status);
+                }
+            }
+            Err(e) => error!("Failed to wait for hook '{}': {}", hook_name, e),
+        }
+    }
+
     fn run_shell_command(cmd: &str, hook_name: &str) {
         info!("Running hook '{}': {}", hook_name, cmd);
         match Command::new("sh").arg("-c").arg(cmd).status() {
@@ -71,3 +485,197 @@ impl AppHooks {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(unix)]
+    use tempfile::TempDir;
+
+    #[cfg(unix)]
+    fn feed(slug: &str) -> FeedItem {
+        FeedItem {
+            title: format!("The {slug}"),
+            slug: slug.to_string(),
+            feed_url: format!("https://{slug}.example/feed"),
+            lastupdated: chrono::Utc::now(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_open_link_template_keeps_values_as_single_arguments() {
+        let hooks = AppHooks {
+            open_link: Some(String::from(
+                r#"notify "Opening from {feed}" --title='{title}' %s"#,
+            )),
+            ..Default::default()
+        };
+
+        let link = LinkContext {
+            url: String::from("https://example.com/a b?q=\"x\""),
+            title: String::from("It's \"quoted\"; rm -rf ~"),
+            feed: String::from("My Blog"),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            hooks.open_link_command(&link).unwrap(),
+            vec![
+                "notify",
+                "Opening from My Blog",
+                "--title=It's \"quoted\"; rm -rf ~",
+                "https://example.com/a b?q=\"x\"",
+            ]
+        );
+
+        assert!(AppHooks::default().open_link_command(&link).is_none());
+    }
+
+    #[test]
+    fn test_placeholders_in_values_are_not_expanded() {
+        let link = LinkContext {
+            url: String::from("https://example.com/post"),
+            title: String::from("Why {url} and %s matter"),
+            feed: String::from("{title}"),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            link.fill("{feed}: {title} <%s> {unknown} 100%"),
+            "{title}: Why {url} and %s matter <https://example.com/post> {unknown} 100%"
+        );
+    }
+
+    #[test]
+    fn test_open_link_rules_pick_a_handler() {
+        let hooks: AppHooks = toml::from_str(
+            r#"
+            open_link = "firefox {url}"
+
+            [[open_link_rules]]
+            url = "youtube\\.com/watch|youtu\\.be/"
+            command = "mpv {url}"
+
+            [[open_link_rules]]
+            mime = "application/pdf"
+            command = "zathura {url}"
+
+            [[open_link_rules]]
+            mime = "audio/*"
+            command = "mpv --no-video {enclosure}"
+            "#,
+        )
+        .unwrap();
+
+        let command = |url: &str, enclosure: Option<(&str, &str)>| {
+            let link = LinkContext {
+                url: url.to_string(),
+                enclosure_url: enclosure.map(|(u, _)| u.to_string()),
+                enclosure_type: enclosure.map(|(_, t)| t.to_string()),
+                ..Default::default()
+            };
+            hooks.open_link_command(&link).unwrap().join(" ")
+        };
+
+        assert_eq!(
+            command("https://www.youtube.com/watch?v=abc", None),
+            "mpv https://www.youtube.com/watch?v=abc"
+        );
+        assert_eq!(
+            command("https://example.com/paper.PDF?download=1", None),
+            "zathura https://example.com/paper.PDF?download=1"
+        );
+        assert_eq!(
+            command(
+                "https://example.com/episode",
+                Some(("https://example.com/episode", "audio/mpeg"))
+            ),
+            "mpv --no-video https://example.com/episode"
+        );
+        assert_eq!(
+            command("https://example.com/post", None),
+            "firefox https://example.com/post"
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_event_hooks_get_json_and_env_and_respect_scope() {
+        let dir = TempDir::new().unwrap();
+        let out = dir.path().join("out");
+
+        let hooks: AppHooks = toml::from_str(&format!(
+            r#"
+            on_feed_error = "cat > {out}.json"
+
+            [[on_new_entry]]
+            command = "echo \"$BULLETTY_CATEGORY $BULLETTY_FEED_SLUG $BULLETTY_ENTRY_TITLE\" >> {out}.env"
+            feeds = ["rust"]
+
+            [[on_new_entry]]
+            command = "echo news >> {out}.env"
+            categories = ["News"]
+            "#,
+            out = out.display()
+        ))
+        .unwrap();
+
+        let entry = FeedEntry {
+            title: String::from("Hello"),
+            ..Default::default()
+        };
+        hooks.run_new_entries("Tech", &feed("rust"), std::slice::from_ref(&entry));
+        hooks.run_new_entries("Tech", &feed("go"), std::slice::from_ref(&entry));
+        let imported = FeedItem {
+            lastupdated: Default::default(),
+            ..feed("rust")
+        };
+        hooks.run_new_entries("Tech", &imported, std::slice::from_ref(&entry));
+        hooks.run_feed_error("News", &feed("go"), "timed out");
+
+        let env = std::fs::read_to_string(out.with_extension("env")).unwrap();
+        assert_eq!(env, "Tech rust Hello\n");
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(out.with_extension("json")).unwrap())
+                .unwrap();
+        assert_eq!(json["event"], "feed_error");
+        assert_eq!(json["feed_slug"], "go");
+        assert_eq!(json["error"], "timed out");
+        assert!(json.get("entry_title").is_none());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_update_finished_counts_feeds_in_scope() {
+        let dir = TempDir::new().unwrap();
+        let out = dir.path().join("out");
+
+        let hooks: AppHooks = toml::from_str(&format!(
+            r#"
+            on_update_finished = [
+                {{ command = "echo all $BULLETTY_NEW_ENTRIES $BULLETTY_FAILED_FEEDS >> {out}" }},
+                {{ command = "echo tech $BULLETTY_NEW_ENTRIES >> {out}", categories = ["Tech"] }},
+                {{ command = "echo none >> {out}", feeds = ["missing"] }},
+            ]
+            "#,
+            out = out.display()
+        ))
+        .unwrap();
+
+        let update = |category: &str, slug: &str, new_entries, failed| FeedUpdate {
+            category: category.to_string(),
+            feed: feed(slug),
+            new_entries,
+            failed,
+        };
+        hooks.run_update_finished(&[
+            update("Tech", "rust", 2, false),
+            update("News", "daily", 5, false),
+            update("News", "broken", 0, true),
+        ]);
+
+        assert_eq!(std::fs::read_to_string(out).unwrap(), "all 7 1\ntech 2\n");
+    }
+}
@@ -0,0 +1,118 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::eyre;
+use serde::{Deserialize, Serialize};
+
+use crate::core::defs::DATA_FEED_STATE;
+
+/// What the user did with an entry. Only entries that have been read get a record.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntryState {
+    pub seen: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_at: Option<DateTime<Utc>>,
+}
+
+/// Per-feed user state, stored in the feed's `.state.toml` and keyed by the entry file name.
+/// Keeping it apart from the entries means the entry files are never rewritten after ingest.
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct FeedState {
+    #[serde(default)]
+    pub entries: BTreeMap<String, EntryState>,
+
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl FeedState {
+    pub fn path(feedir: &Path) -> PathBuf {
+        feedir.join(DATA_FEED_STATE)
+    }
+
+    /// Loads the state of a feed. Returns `None` when the feed has no state file yet, which is
+    /// the case for libraries that still keep `seen` in the entries' front matter.
+    pub fn load(feedir: &Path) -> color_eyre::Result<Option<Self>> {
+        let path = Self::path(feedir);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let contents = fs::read_to_string(&path)?;
+        let mut state: FeedState = toml::from_str(&contents)
+            .map_err(|e| eyre!("Failed to parse feed state {}: {}", path.display(), e))?;
+
+        state.path = path;
+        Ok(Some(state))
+    }
+
+    pub fn new(feedir: &Path) -> Self {
+        Self {
+            entries: BTreeMap::new(),
+            path: Self::path(feedir),
+        }
+    }
+
+    pub fn set_seen(&mut self, name: &str, seen: bool) {
+        if seen {
+            let state = self.entries.entry(name.to_string()).or_default();
+            if !state.seen {
+                state.seen = true;
+                state.read_at = Some(Utc::now());
+            }
+        } else {
+            self.entries.remove(name);
+        }
+    }
+
+    /// Three-way merge used when syncing. An entry changed on one side only takes that change;
+    /// when both sides changed it, the most recent read wins.
+    pub fn merge(base: &FeedState, ours: &FeedState, theirs: &FeedState) -> Self {
+        let mut merged = FeedState::default();
+
+        let names: BTreeSet<&String> = ours.entries.keys().chain(theirs.entries.keys()).collect();
+        for name in names {
+            let (b, o, t) = (
+                base.entries.get(name),
+                ours.entries.get(name),
+                theirs.entries.get(name),
+            );
+
+            let state = if o == t || t == b {
+                o
+            } else if o == b {
+                t
+            } else {
+                match (o, t) {
+                    (Some(o), Some(t)) if t.read_at > o.read_at => Some(t),
+                    (Some(o), _) => Some(o),
+                    (None, t) => t,
+                }
+            };
+
+            if let Some(state) = state {
+                merged.entries.insert(name.clone(), state.clone());
+            }
+        }
+
+        merged
+    }
+
+    /// Writes to a temporary file first and renames it over the state file, so a failed write
+    /// never leaves a truncated state behind.
+    pub fn save(&self) -> color_eyre::Result<()> {
+        let temp_path = self.path.with_extension("toml.tmp");
+        let toml_str =
+            toml::to_string(self).map_err(|e| eyre!("Failed to serialize feed state: {}", e))?;
+
+        fs::write(&temp_path, toml_str)
+            .map_err(|e| eyre!("Failed to write file {}: {}", temp_path.display(), e))?;
+
+        fs::rename(&temp_path, &self.path)
+            .map_err(|e| eyre!("Failed to replace file {}: {}", self.path.display(), e))
+    }
+}
@@ -0,0 +1,471 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use color_eyre::eyre::{bail, eyre};
+use serde::{Serialize, de::DeserializeOwned};
+use tracing::{info, warn};
+
+use crate::core::{
+    defs::{DATA_FEED_STATE, DATA_PRUNED, DATA_READ_LATER},
+    library::data::{
+        feedstate::FeedState,
+        librarydata::{PrunedData, ReadLaterData},
+    },
+};
+
+const REMOTE: &str = "origin";
+const BRANCH: &str = "main";
+const IGNORED: &[&str] = &["*.tmp", ".cache/", ".session.toml"];
+/// Files that only make sense on this device, kept out of commits even if `.gitignore` is edited
+const LOCAL_ONLY: &[&str] = &[".cache", ".session.toml"];
+
+/// What `bulletty sync` ended up doing
+#[derive(Debug, PartialEq)]
+pub enum SyncResult {
+    /// The remote had nothing yet, so the library was pushed to it
+    Published,
+    UpToDate,
+    /// Remote changes were merged; the number is how many conflicts had to be resolved
+    Merged(usize),
+    /// Only local commits had to be pushed
+    Pushed,
+}
+
+/// The library directory as a git repository. It's opt-in: libraries only get one through
+/// `bulletty sync init`, and everything else just checks whether there's a `.git` in there.
+pub struct LibraryRepo {
+    path: PathBuf,
+    has_identity: bool,
+}
+
+impl LibraryRepo {
+    /// Returns the repository of the library, if it's versioned at all
+    pub fn open(datapath: &Path) -> Option<Self> {
+        if !datapath.join(".git").exists() {
+            return None;
+        }
+
+        let repo = Self::new(datapath);
+        if let Err(e) = repo.update_gitignore() {
+            warn!("Couldn't update the library's .gitignore: {}", e);
+        }
+        Some(repo)
+    }
+
+    fn new(datapath: &Path) -> Self {
+        // commits shouldn't fail just because this machine has no git identity
+        let has_identity = Command::new("git")
+            .arg("-C")
+            .arg(datapath)
+            .args(["config", "user.email"])
+            .output()
+            .is_ok_and(|o| o.status.success());
+
+        Self {
+            path: datapath.to_path_buf(),
+            has_identity,
+        }
+    }
+
+    /// Turns the library into a repository, committing everything that's in there. Running it on
+    /// an existing repository only updates the remote.
+    pub fn init(datapath: &Path, remote: Option<&str>) -> color_eyre::Result<Self> {
+        let repo = Self::new(datapath);
+
+        if !datapath.join(".git").exists() {
+            repo.git(&["init", "--quiet"])?;
+            repo.git(&["symbolic-ref", "HEAD", &format!("refs/heads/{BRANCH}")])?;
+        }
+
+        repo.update_gitignore()?;
+
+        if let Some(remote) = remote {
+            if repo.git(&["remote", "get-url", REMOTE]).is_ok() {
+                repo.git(&["remote", "set-url", REMOTE, remote])?;
+            } else {
+                repo.git(&["remote", "add", REMOTE, remote])?;
+            }
+        }
+
+        repo.commit(&["Initialize library".to_string()])?;
+        Ok(repo)
+    }
+
+    /// Commits everything that changed in the library. The first change is the subject, the rest
+    /// go into the body. Returns false when there was nothing to commit.
+    pub fn commit(&self, changes: &[String]) -> color_eyre::Result<bool> {
+        self.git(&["add", "--all"])?;
+        self.untrack_local_files()?;
+
+        let staged = self.git(&["diff", "--cached", "--name-status"])?;
+        if staged.trim().is_empty() {
+            return Ok(false);
+        }
+
+        let new_entries = staged
+            .lines()
+            .filter(|l| l.starts_with('A') && l.ends_with(".md"))
+            .count();
+
+        let subject = match changes {
+            [] => String::from("Update library"),
+            [change] => change.clone(),
+            [change, rest @ ..] => format!("{change} and {} more changes", rest.len()),
+        };
+
+        let mut body: Vec<String> = if changes.len() > 1 {
+            changes.iter().map(|c| format!("- {c}")).collect()
+        } else {
+            vec![]
+        };
+
+        if new_entries > 0 {
+            body.push(format!("{new_entries} new entries"));
+        }
+
+        let message = if body.is_empty() {
+            subject
+        } else {
+            format!("{subject}\n\n{}", body.join("\n"))
+        };
+
+        self.git(&["commit", "--quiet", "-m", &message])?;
+        info!("Committed library changes: {}", message);
+        Ok(true)
+    }
+
+    /// Pulls the remote changes, merging them with the local ones, and pushes the result
+    pub fn sync(&self) -> color_eyre::Result<SyncResult> {
+        if self.git(&["remote", "get-url", REMOTE]).is_err() {
+            bail!("No remote configured. Run `bulletty sync init <remote>` first");
+        }
+
+        self.git(&["fetch", "--quiet", REMOTE])?;
+
+        let remote_branch = format!("{REMOTE}/{BRANCH}");
+        if self
+            .git(&["rev-parse", "--verify", "--quiet", &remote_branch])
+            .is_err()
+        {
+            self.push()?;
+            return Ok(SyncResult::Published);
+        }
+
+        let behind = self.count_commits(&format!("HEAD..{remote_branch}"))?;
+        let ahead = self.count_commits(&format!("{remote_branch}..HEAD"))?;
+
+        let result = match (behind, ahead) {
+            (0, 0) => return Ok(SyncResult::UpToDate),
+            (0, _) => SyncResult::Pushed,
+            _ => SyncResult::Merged(self.merge(&remote_branch)?),
+        };
+
+        if self.count_commits(&format!("{remote_branch}..HEAD"))? > 0 {
+            self.push()?;
+        }
+
+        Ok(result)
+    }
+
+    fn merge(&self, remote_branch: &str) -> color_eyre::Result<usize> {
+        let merge = self.run(&[
+            "merge",
+            "--no-edit",
+            "--quiet",
+            "--allow-unrelated-histories",
+            remote_branch,
+        ])?;
+
+        if merge.status.success() {
+            return Ok(0);
+        }
+
+        let conflicts: Vec<String> = self
+            .git(&["diff", "--name-only", "--diff-filter=U", "-z"])?
+            .split('\0')
+            .filter(|p| !p.is_empty())
+            .map(String::from)
+            .collect();
+
+        if conflicts.is_empty() {
+            let _ = self.run(&["merge", "--abort"]);
+            bail!(
+                "Merging {} failed: {}",
+                remote_branch,
+                String::from_utf8_lossy(&merge.stderr).trim()
+            );
+        }
+
+        for path in conflicts.iter() {
+            if let Err(e) = self.resolve(path) {
+                let _ = self.run(&["merge", "--abort"]);
+                return Err(e);
+            }
+        }
+
+        self.git(&["commit", "--quiet", "--no-edit"])?;
+        Ok(conflicts.len())
+    }
+
+    /// Resolves a conflicted file. The state files are merged entry by entry, for anything else
+    /// our version wins: entries are never edited, so both sides only differ in timestamps.
+    fn resolve(&self, path: &str) -> color_eyre::Result<()> {
+        let base = self.stage(1, path);
+        let ours = self.stage(2, path);
+        let theirs = self.stage(3, path);
+
+        let name = Path::new(path)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+
+        let merged = match (ours, theirs) {
+            (None, _) => None,
+            (Some(ours), None) => Some(ours),
+            (Some(ours), Some(theirs)) => Some(match name {
+                DATA_READ_LATER => merge_toml(&base, &ours, &theirs, ReadLaterData::merge)?,
+                DATA_FEED_STATE => merge_toml(&base, &ours, &theirs, FeedState::merge)?,
+                DATA_PRUNED => {
+                    merge_toml(&base, &ours, &theirs, |_, o, t| PrunedData::merge(o, t))?
+                }
+                _ => ours,
+            }),
+        };
+
+        warn!("Resolving sync conflict in {}", path);
+
+        match merged {
+            Some(contents) => {
+                fs::write(self.path.join(path), contents)?;
+                self.git(&["add", "--", path])?;
+            }
+            None => {
+                self.git(&["rm", "--quiet", "--force", "--", path])?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn stage(&self, stage: u8, path: &str) -> Option<String> {
+        self.git(&["show", &format!(":{stage}:{path}")]).ok()
+    }
+
+    fn push(&self) -> color_eyre::Result<()> {
+        self.git(&[
+            "push",
+            "--quiet",
+            "--set-upstream",
+            REMOTE,
+            &format!("HEAD:{BRANCH}"),
+        ])?;
+        Ok(())
+    }
+
+    fn count_commits(&self, range: &str) -> color_eyre::Result<usize> {
+        self.git(&["rev-list", "--count", range])?
+            .trim()
+            .parse()
+            .map_err(|e| eyre!("Unexpected output from git rev-list: {}", e))
+    }
+
+    /// Adds the ignore lines the library is missing, so libraries made by older versions pick up
+    /// new ones.
+    fn update_gitignore(&self) -> color_eyre::Result<()> {
+        let gitignore = self.path.join(".gitignore");
+        let mut contents = fs::read_to_string(&gitignore).unwrap_or_default();
+
+        let missing: Vec<&str> = IGNORED
+            .iter()
+            .copied()
+            .filter(|line| !contents.lines().any(|l| l.trim() == *line))
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            contents.push('\n');
+        }
+        for line in missing {
+            contents.push_str(line);
+            contents.push('\n');
+        }
+        fs::write(&gitignore, contents)?;
+        Ok(())
+    }
+
+    /// Drops the local-only files from the index, including ones committed before they were
+    /// ignored. Only the index changes: the files themselves stay.
+    fn untrack_local_files(&self) -> color_eyre::Result<()> {
+        let mut untrack = vec!["rm", "-r", "--cached", "--quiet", "--ignore-unmatch", "--"];
+        untrack.extend(LOCAL_ONLY);
+        self.git(&untrack)?;
+        Ok(())
+    }
+
+    fn git(&self, args: &[&str]) -> color_eyre::Result<String> {
+        let output = self.run(args)?;
+
+        if !output.status.success() {
+            bail!(
+                "git {} failed: {}",
+                args.first().unwrap_or(&""),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn run(&self, args: &[&str]) -> color_eyre::Result<Output> {
+        let mut command = Command::new("git");
+        command.arg("-C").arg(&self.path);
+
+        if !self.has_identity {
+            command.args([
+                "-c",
+                "user.name=bulletty",
+                "-c",
+                "user.email=bulletty@localhost",
+            ]);
+        }
+
+        command
+            .args(args)
+            .output()
+            .map_err(|e| eyre!("Couldn't run git, is it installed? {}", e))
+    }
+}
+
+fn merge_toml<T, F>(
+    base: &Option<String>,
+    ours: &str,
+    theirs: &str,
+    merge: F,
+) -> color_eyre::Result<String>
+where
+    T: Serialize + DeserializeOwned + Default,
+    F: Fn(&T, &T, &T) -> T,
+{
+    let base: T = match base {
+        Some(base) => toml::from_str(base)?,
+        None => T::default(),
+    };
+
+    let merged = merge(&base, &toml::from_str(ours)?, &toml::from_str(theirs)?);
+    Ok(toml::to_string(&merged)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    fn library(remote: &Path, entry: &str, body: &str) -> (TempDir, LibraryRepo) {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+
+        write(root, "categories/General/blog/a.md", body);
+        write(root, &format!("categories/General/blog/{entry}"), body);
+        write(
+            root,
+            "categories/General/blog/.state.toml",
+            &format!("[entries.\"{entry}\"]\nseen = true\n"),
+        );
+        write(
+            root,
+            DATA_READ_LATER,
+            &format!("read_later = [\"General/blog/{entry}\"]\n"),
+        );
+
+        let repo = LibraryRepo::init(root, remote.to_str()).unwrap();
+        (dir, repo)
+    }
+
+    #[test]
+    fn test_sync_merges_state_from_both_sides() {
+        let remote = TempDir::new().unwrap();
+        Command::new("git")
+            .args(["init", "--quiet", "--bare"])
+            .arg(remote.path())
+            .status()
+            .unwrap();
+
+        let (laptop_dir, laptop) = library(remote.path(), "b.md", "laptop");
+        let (desktop_dir, desktop) = library(remote.path(), "c.md", "desktop");
+
+        assert_eq!(laptop.sync().unwrap(), SyncResult::Published);
+        assert_eq!(desktop.sync().unwrap(), SyncResult::Merged(3));
+        assert_eq!(laptop.sync().unwrap(), SyncResult::Merged(0));
+        assert_eq!(laptop.sync().unwrap(), SyncResult::UpToDate);
+
+        for root in [laptop_dir.path(), desktop_dir.path()] {
+            let later: ReadLaterData =
+                toml::from_str(&fs::read_to_string(root.join(DATA_READ_LATER)).unwrap()).unwrap();
+            assert_eq!(
+                later.read_later,
+                vec!["General/blog/c.md", "General/blog/b.md"]
+            );
+
+            let state = FeedState::load(&root.join("categories/General/blog"))
+                .unwrap()
+                .unwrap();
+            assert!(state.entries["b.md"].seen);
+            assert!(state.entries["c.md"].seen);
+
+            let entry = fs::read_to_string(root.join("categories/General/blog/a.md")).unwrap();
+            assert_eq!(entry, "desktop");
+        }
+
+        fs::remove_file(laptop_dir.path().join("categories/General/blog/b.md")).unwrap();
+        assert!(laptop.commit(&[String::from("Remove b")]).unwrap());
+        assert!(!laptop.commit(&[String::from("Nothing")]).unwrap());
+        assert_eq!(laptop.sync().unwrap(), SyncResult::Pushed);
+    }
+
+    #[test]
+    fn test_gitignore_is_completed_and_local_files_stay_out() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+
+        // A library versioned before the cache and the session existed
+        write(root, "categories/General/blog/a.md", "entry");
+        write(root, ".session.toml", "entry = \"a.md\"\n");
+        write(root, ".gitignore", "*.tmp\nnotes/");
+        let repo = LibraryRepo::new(root);
+        repo.git(&["init", "--quiet"]).unwrap();
+        repo.git(&["add", "--all"]).unwrap();
+        repo.git(&["commit", "--quiet", "-m", "Old library"])
+            .unwrap();
+
+        let repo = LibraryRepo::open(root).unwrap();
+        assert_eq!(
+            fs::read_to_string(root.join(".gitignore")).unwrap(),
+            "*.tmp\nnotes/\n.cache/\n.session.toml\n"
+        );
+
+        write(root, ".cache/images/x.png", "png");
+        assert!(repo.commit(&[String::from("Update")]).unwrap());
+        let tracked = repo.git(&["ls-files"]).unwrap();
+        assert!(tracked.contains("categories/General/blog/a.md"));
+        assert!(!tracked.contains(".session.toml"));
+        assert!(!tracked.contains(".cache"));
+        assert!(root.join(".session.toml").exists());
+
+        // Local files stay out even when the user removes the lines again
+        write(root, ".gitignore", "*.tmp\n");
+        write(root, ".session.toml", "entry = \"b.md\"\n");
+        assert!(repo.commit(&[String::from("Edit .gitignore")]).unwrap());
+        assert!(!repo.git(&["ls-files"]).unwrap().contains(".session.toml"));
+    }
+}
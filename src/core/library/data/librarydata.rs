@@ -1,5 +1,6 @@
 use std::io::Write;
 use std::{
+    collections::BTreeMap,
     fs::{self, OpenOptions},
     path::{Path, PathBuf},
 };
@@ -11,9 +12,13 @@ use tracing::{error, info};
 
 use crate::core::feed::feedentry::FeedEntry;
 use crate::core::feed::feedparser;
+use crate::core::library::data::feedstate::{EntryState, FeedState};
 use crate::core::library::feedcategory::FeedCategory;
+use crate::core::library::settings::retention::RetentionPolicy;
 use crate::{
-    core::defs::{self, DATA_CATEGORIES_DIR, DATA_FEED, DATA_READ_LATER},
+    core::defs::{
+        self, DATA_CATEGORIES_DIR, DATA_CATEGORY_FOLDERS, DATA_FEED, DATA_PRUNED, DATA_READ_LATER,
+    },
     core::library::feeditem::FeedItem,
 };
 use serde::{Deserialize, Serialize};
@@ -21,6 +26,9 @@ use serde::{Deserialize, Serialize};
 #[cfg(test)]
 use tempfile::TempDir;
 
+/// The Read Later list, in the library's `.later.toml`. Unlike `seen` it stays out of the feed
+/// states: it's one list across all feeds, kept in the order entries were added, and it's merged
+/// as a list when syncing. It never lived in the entry files either.
 #[derive(Default, Debug, Serialize, Deserialize)]
 pub struct ReadLaterData {
     pub read_later: Vec<String>,
@@ -28,6 +36,54 @@ pub struct ReadLaterData {
     pub loaded: bool,
 }
 
+impl ReadLaterData {
+    /// Three-way merge used when syncing: keeps our order, drops what either side removed since
+    /// `base` and appends what they added.
+    pub fn merge(base: &ReadLaterData, ours: &ReadLaterData, theirs: &ReadLaterData) -> Self {
+        let removed = |p: &String, side: &ReadLaterData| {
+            base.read_later.contains(p) && !side.read_later.contains(p)
+        };
+
+        let mut read_later: Vec<String> = ours
+            .read_later
+            .iter()
+            .filter(|p| !removed(p, theirs))
+            .cloned()
+            .collect();
+
+        for p in theirs.read_later.iter() {
+            if !read_later.contains(p) && !removed(p, ours) {
+                read_later.push(p.clone());
+            }
+        }
+
+        Self {
+            read_later,
+            loaded: false,
+        }
+    }
+}
+
+/// Entries removed by the retention policy, kept per feed so the next update doesn't download
+/// them again
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct PrunedData {
+    pub pruned: Vec<String>,
+}
+
+impl PrunedData {
+    pub fn merge(ours: &PrunedData, theirs: &PrunedData) -> Self {
+        let mut pruned = ours.pruned.clone();
+        for name in theirs.pruned.iter() {
+            if !pruned.contains(name) {
+                pruned.push(name.clone());
+            }
+        }
+
+        Self { pruned }
+    }
+}
+
 pub struct LibraryData {
     pub path: PathBuf,
     pub read_later: ReadLaterData,
@@ -36,10 +92,12 @@ pub struct LibraryData {
 impl LibraryData {
     pub fn new(datapath: &Path) -> LibraryData {
         load_or_create(datapath);
-        LibraryData {
+        let data = LibraryData {
             path: PathBuf::from(datapath),
             read_later: ReadLaterData::default(),
-        }
+        };
+        data.migrate_feed_states();
+        data
     }
 
     #[cfg(test)]
@@ -85,6 +143,262 @@ impl LibraryData {
         }
     }
 
+    /// Moves a feed directory to another category, creating the category if needed.
+    pub fn move_feed(
+        &mut self,
+        slug: &str,
+        category: &str,
+        new_category: &str,
+    ) -> color_eyre::Result<()> {
+        validate_category_name(new_category)?;
+
+        if category == new_category {
+            return Ok(());
+        }
+
+        self.relocate(
+            &Path::new(category).join(slug),
+            &Path::new(new_category).join(slug),
+        )
+    }
+
+    /// Changes the title of a feed. The feed directory follows the new slug.
+    pub fn rename_feed(
+        &mut self,
+        slug: &str,
+        category: &str,
+        new_title: &str,
+    ) -> color_eyre::Result<FeedItem> {
+        let new_title = new_title.trim();
+        let new_slug = slugify(new_title);
+        if new_slug.is_empty() {
+            return Err(eyre!("Invalid feed title: {:?}", new_title));
+        }
+
+        let mut feed = self.load_feed(slug, category)?;
+        feed.title = new_title.to_string();
+        feed.slug = new_slug;
+        feed.category = category.to_string();
+        self.relocate_feed(slug, category, &feed)?;
+
+        Ok(feed)
+    }
+
+    /// Changes the title, feed URL and category of a feed together, moving its directory once,
+    /// straight to where it ends up
+    pub fn edit_feed(
+        &mut self,
+        slug: &str,
+        category: &str,
+        title: &str,
+        feed_url: &str,
+        new_category: &str,
+    ) -> color_eyre::Result<FeedItem> {
+        validate_category_name(new_category)?;
+
+        let title = title.trim();
+        let mut feed = self.load_feed(slug, category)?;
+
+        if title != feed.title {
+            let new_slug = slugify(title);
+            if new_slug.is_empty() {
+                return Err(eyre!("Invalid feed title: {:?}", title));
+            }
+            feed.title = title.to_string();
+            feed.slug = new_slug;
+        }
+        feed.feed_url = feed_url.to_string();
+        feed.category = new_category.to_string();
+        self.relocate_feed(slug, category, &feed)?;
+
+        Ok(feed)
+    }
+
+    /// Moves the feed at `category/slug` to where `feed` belongs and writes its `.feed.toml`
+    /// there. If the write fails, the directory is moved back.
+    fn relocate_feed(
+        &mut self,
+        slug: &str,
+        category: &str,
+        feed: &FeedItem,
+    ) -> color_eyre::Result<()> {
+        let from = Path::new(category).join(slug);
+        let to = Path::new(&feed.category).join(&feed.slug);
+        let moved = from != to;
+
+        if moved {
+            self.relocate(&from, &to)?;
+        }
+
+        if let Err(e) = self.feed_create(feed) {
+            if moved && let Err(rollback) = self.relocate(&to, &from) {
+                error!(
+                    "Couldn't move {} back to {}: {}",
+                    to.display(),
+                    from.display(),
+                    rollback
+                );
+            }
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    pub fn rename_category(
+        &mut self,
+        category: &str,
+        new_category: &str,
+    ) -> color_eyre::Result<()> {
+        validate_category_name(new_category)?;
+
+        if category == new_category {
+            return Ok(());
+        }
+
+        self.relocate(Path::new(category), Path::new(new_category))?;
+
+        // A renamed category isn't the imported folder anymore
+        let folders = self
+            .path
+            .join(DATA_CATEGORIES_DIR)
+            .join(new_category)
+            .join(DATA_CATEGORY_FOLDERS);
+        if folders.exists() {
+            fs::remove_file(&folders)?;
+        }
+
+        Ok(())
+    }
+
+    /// Records the nested folders an imported category stands for
+    pub fn set_category_folders(
+        &self,
+        category: &str,
+        folders: &[String],
+    ) -> color_eyre::Result<()> {
+        let path = self
+            .path
+            .join(DATA_CATEGORIES_DIR)
+            .join(category)
+            .join(DATA_CATEGORY_FOLDERS);
+        let toml_str = toml::to_string(&CategoryFolders {
+            folders: folders.to_vec(),
+        })?;
+
+        fs::write(&path, toml_str)
+            .map_err(|e| eyre!("Failed to write file {}: {}", path.display(), e))
+    }
+
+    /// Deletes a category with all its feeds and drops their Read Later references.
+    pub fn delete_category(&mut self, category: &str) -> color_eyre::Result<()> {
+        validate_category_name(category)?;
+
+        let category_dir = self.path.join(DATA_CATEGORIES_DIR).join(category);
+        if !category_dir.exists() {
+            return Err(eyre!("Category {:?} doesn't exist", category));
+        }
+
+        let mut read_later = self.load_read_later()?;
+
+        fs::remove_dir_all(&category_dir).map_err(|e| {
+            eyre!(
+                "Failed to delete category directory {}: {}",
+                category_dir.display(),
+                e
+            )
+        })?;
+
+        let original_len = read_later.read_later.len();
+        read_later
+            .read_later
+            .retain(|rel| !Path::new(rel).starts_with(category));
+
+        if read_later.read_later.len() < original_len {
+            self.save_read_later(&read_later)?;
+        }
+        self.read_later = read_later;
+
+        Ok(())
+    }
+
+    pub fn category_exists(&self, category: &str) -> bool {
+        self.path.join(DATA_CATEGORIES_DIR).join(category).is_dir()
+    }
+
+    fn load_feed(&self, slug: &str, category: &str) -> color_eyre::Result<FeedItem> {
+        let feeddata = self
+            .path
+            .join(DATA_CATEGORIES_DIR)
+            .join(category)
+            .join(slug)
+            .join(DATA_FEED);
+
+        let file = fs::read_to_string(&feeddata)
+            .map_err(|e| eyre!("Couldn't read feed file {}: {}", feeddata.display(), e))?;
+        let mut feed: FeedItem =
+            toml::from_str(&file).map_err(|e| eyre!("Error: feed file can't be parsed: {}", e))?;
+        feed.category = category.to_string();
+
+        Ok(feed)
+    }
+
+    /// Renames a path relative to the categories directory and rewrites the Read Later
+    /// references pointing inside it. The directory is moved back if the references can't be
+    /// saved, so the library never ends up half moved.
+    fn relocate(&mut self, from: &Path, to: &Path) -> color_eyre::Result<()> {
+        let catpath = self.path.join(DATA_CATEGORIES_DIR);
+        let source = catpath.join(from);
+        let destination = catpath.join(to);
+
+        if !source.exists() {
+            return Err(eyre!("{} doesn't exist", from.display()));
+        }
+
+        if destination.exists() {
+            return Err(eyre!("{} already exists", to.display()));
+        }
+
+        // Loaded before the rename, otherwise the cleanup would drop the entries being moved
+        let mut read_later = self.load_read_later()?;
+
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::rename(&source, &destination).map_err(|e| {
+            eyre!(
+                "Failed to move {} to {}: {}",
+                source.display(),
+                destination.display(),
+                e
+            )
+        })?;
+
+        let mut changed = false;
+        for rel in read_later.read_later.iter_mut() {
+            if let Ok(rest) = Path::new(rel.as_str()).strip_prefix(from) {
+                *rel = to.join(rest).to_string_lossy().to_string();
+                changed = true;
+            }
+        }
+
+        if changed && let Err(e) = self.save_read_later(&read_later) {
+            if let Err(rollback) = fs::rename(&destination, &source) {
+                error!(
+                    "Couldn't move {} back to {}: {}",
+                    destination.display(),
+                    source.display(),
+                    rollback
+                );
+            }
+            return Err(e);
+        }
+
+        self.read_later = read_later;
+        Ok(())
+    }
+
     pub fn feed_create(&self, feed: &FeedItem) -> color_eyre::Result<()> {
         let feedir = self
             .path
@@ -120,13 +434,14 @@ impl LibraryData {
                 let cat = FeedCategory {
                     title: String::from(name),
                     feeds: self.load_feeds_from_category(name, path.as_path())?,
+                    folders: load_category_folders(&path),
                 };
 
                 categories.push(cat);
             }
         }
 
-        categories.sort_by(|a, b| a.title.to_lowercase().cmp(&b.title.to_lowercase()));
+        categories.sort_by_key(|c| c.title.to_lowercase());
         Ok(categories)
     }
 
@@ -156,19 +471,20 @@ impl LibraryData {
             }
         }
 
-        feeds.sort_by(|a, b| a.title.to_lowercase().cmp(&b.title.to_lowercase()));
+        feeds.sort_by_key(|f| f.title.to_lowercase());
         Ok(feeds)
     }
 
+    /// Downloads the new entries of a feed, returning the ones that were written
     pub fn update_feed_entries(
         &self,
         category: &str,
         feed: &FeedItem,
         feedxml: Option<String>,
-    ) -> color_eyre::Result<()> {
+    ) -> color_eyre::Result<Vec<FeedEntry>> {
         // TODO: hard coding 5 minutes for now
         if Utc::now().signed_duration_since(feed.lastupdated) < Duration::minutes(5) {
-            return Ok(());
+            return Ok(vec![]);
         }
 
         let mut feedentries = if let Some(txt) = feedxml {
@@ -177,58 +493,162 @@ impl LibraryData {
             feedparser::get_feed_entries(feed)
         }?;
 
-        feedentries.iter_mut().for_each(|e| {
-            let entrypath = self
-                .path
-                .join(defs::DATA_CATEGORIES_DIR)
-                .join(category)
-                .join(&feed.slug);
+        let feedir = self
+            .path
+            .join(defs::DATA_CATEGORIES_DIR)
+            .join(category)
+            .join(&feed.slug);
 
-            let item_slug = {
-                let base_path = entrypath.to_string_lossy();
-                let max_slug_len = 250usize.saturating_sub(base_path.len() + 1);
-                let slug = slugify(&e.title);
-                let slug_cut = &slug[..slug.len().min(max_slug_len)];
-                slug_cut.to_string()
-            };
+        feedentries
+            .iter_mut()
+            .for_each(|e| e.filepath = entry_path(&feedir, &e.title));
 
-            e.filepath = entrypath.join(format!("{item_slug}.md"));
-        });
+        let mut pruned = self.load_pruned(&feedir)?;
+        if !pruned.pruned.is_empty() {
+            // only remember what the feed still lists, otherwise the list would grow forever
+            let original_len = pruned.pruned.len();
+            pruned
+                .pruned
+                .retain(|name| feedentries.iter().any(|e| entry_file_name(e) == name));
+
+            if pruned.pruned.len() < original_len {
+                self.save_pruned(&feedir, &pruned)?;
+            }
+
+            feedentries.retain(|e| !pruned.pruned.iter().any(|name| entry_file_name(e) == name));
+        }
 
         self.update_entries(feed, feedentries)
     }
 
-    fn update_entries(&self, feed: &FeedItem, entries: Vec<FeedEntry>) -> color_eyre::Result<()> {
-        for entry in entries.iter().as_ref() {
-            // if it exists, it means the entry has been setup already
-            if !entry.filepath.exists() {
-                let mut file = match OpenOptions::new()
-                    .write(true)
-                    .create_new(true)
-                    .open(&entry.filepath)
-                {
-                    Ok(file) => file,
-                    Err(error) => {
-                        error!(
-                            "Error creating file '{}': {}",
-                            entry.filepath.display(),
-                            error
-                        );
-
-                        break;
-                    }
-                };
+    /// Removes the entries of a feed that fall outside of `policy`. Entries in Read Later are
+    /// never pruned. Returns the pruned entries; with `dry_run` nothing is removed.
+    pub fn prune_feed_entries(
+        &self,
+        feed: &FeedItem,
+        policy: &RetentionPolicy,
+        dry_run: bool,
+    ) -> color_eyre::Result<Vec<FeedEntry>> {
+        if !policy.is_active() {
+            return Ok(vec![]);
+        }
 
-                let mut entryclone = (*entry).clone();
-                entryclone.text = String::new();
+        let feedir = self
+            .path
+            .join(DATA_CATEGORIES_DIR)
+            .join(&feed.category)
+            .join(&feed.slug);
 
-                let entrytext = format!(
-                    "+++\n{}+++\n\n{}",
-                    toml::to_string(&entryclone).unwrap_or(String::new()),
-                    &entry.text
-                );
+        let mut entries = self.load_entries_in(&feedir)?;
+        entries.sort_by_key(|e| std::cmp::Reverse(e.date));
+
+        let read_later = self.load_read_later()?;
+        let now = Utc::now();
+
+        let expired: Vec<FeedEntry> = entries
+            .into_iter()
+            .enumerate()
+            .filter(|(index, e)| policy.expires(*index, e.date, now))
+            .map(|(_, e)| e)
+            .filter(|e| e.seen || !policy.keep_unread())
+            .filter(|e| {
+                let rel_path =
+                    self.absolute_path_to_relative_path(e.filepath.to_str().unwrap_or_default());
+                !read_later.read_later.contains(&rel_path)
+            })
+            .collect();
+
+        if dry_run || expired.is_empty() {
+            return Ok(expired);
+        }
+
+        let mut pruned = self.load_pruned(&feedir)?;
+        let mut removed = Vec::with_capacity(expired.len());
+
+        for entry in expired {
+            if let Err(e) = fs::remove_file(&entry.filepath) {
+                error!("Couldn't prune '{}': {}", entry.filepath.display(), e);
+                continue;
+            }
+
+            let name = entry_file_name(&entry).to_string();
+            if !pruned.pruned.contains(&name) {
+                pruned.pruned.push(name);
+            }
+            removed.push(entry);
+        }
+
+        self.save_pruned(&feedir, &pruned)?;
 
-                file.write_all(&entrytext.into_bytes())?;
+        let mut state = self.load_feed_state(&feedir)?;
+        for entry in removed.iter() {
+            state.set_seen(entry_file_name(entry), false);
+        }
+        state.save()?;
+
+        Ok(removed)
+    }
+
+    fn load_pruned(&self, feedir: &Path) -> color_eyre::Result<PrunedData> {
+        let pruned_path = feedir.join(DATA_PRUNED);
+        if !pruned_path.exists() {
+            return Ok(PrunedData::default());
+        }
+
+        let contents = fs::read_to_string(&pruned_path)?;
+        toml::from_str(&contents).map_err(|e| eyre!("Failed to parse pruned entries: {}", e))
+    }
+
+    fn save_pruned(&self, feedir: &Path, pruned: &PrunedData) -> color_eyre::Result<()> {
+        let pruned_path = feedir.join(DATA_PRUNED);
+        let toml_str = toml::to_string(pruned)
+            .map_err(|e| eyre!("Failed to serialize pruned entries: {}", e))?;
+
+        fs::write(&pruned_path, toml_str)
+            .map_err(|e| eyre!("Failed to write file {}: {}", pruned_path.display(), e))
+    }
+
+    /// Stores an entry that didn't come from the feed itself, like one pulled from a sync
+    /// service. Returns the path of the entry file and whether it's new, or `None` if the entry
+    /// was pruned before.
+    pub fn store_entry(
+        &self,
+        feedir: &Path,
+        mut entry: FeedEntry,
+    ) -> color_eyre::Result<Option<(PathBuf, bool)>> {
+        entry.filepath = entry_path(feedir, &entry.title);
+
+        let pruned = self.load_pruned(feedir)?;
+        if pruned
+            .pruned
+            .iter()
+            .any(|name| entry_file_name(&entry) == name)
+        {
+            return Ok(None);
+        }
+
+        let new = !entry.filepath.exists();
+        if new {
+            self.write_feed_entry(&entry)?;
+        }
+
+        Ok(Some((entry.filepath, new)))
+    }
+
+    fn update_entries(
+        &self,
+        feed: &FeedItem,
+        entries: Vec<FeedEntry>,
+    ) -> color_eyre::Result<Vec<FeedEntry>> {
+        let mut written = vec![];
+        for entry in entries.into_iter() {
+            // if it exists, it means the entry has been setup already
+            if !entry.filepath.exists() {
+                if let Err(error) = self.write_feed_entry(&entry) {
+                    error!("{}", error);
+                    break;
+                }
+                written.push(entry);
             }
         }
 
@@ -236,27 +656,17 @@ impl LibraryData {
         feed.lastupdated = Utc::now();
         self.feed_create(&feed)?;
 
-        Ok(())
+        Ok(written)
     }
 
-    pub fn save_feed_entry(&self, entry: &FeedEntry) -> color_eyre::Result<()> {
-        info!("Saving {:?}", entry.filepath);
-
-        let mut file = match OpenOptions::new()
+    /// Writes a new entry file. Entry files are never rewritten afterwards, the user state goes
+    /// to the feed's state file instead.
+    pub fn write_feed_entry(&self, entry: &FeedEntry) -> color_eyre::Result<()> {
+        let mut file = OpenOptions::new()
             .write(true)
-            .create(true)
-            .truncate(true)
+            .create_new(true)
             .open(&entry.filepath)
-        {
-            Ok(file) => file,
-            Err(error) => {
-                return Err(eyre!(
-                    "Error creating file '{}': {}",
-                    entry.filepath.display(),
-                    error
-                ));
-            }
-        };
+            .map_err(|e| eyre!("Error creating file '{}': {}", entry.filepath.display(), e))?;
 
         let mut entryclone = (*entry).clone();
         entryclone.text = String::new();
@@ -277,15 +687,30 @@ impl LibraryData {
         category: &FeedCategory,
         item: &FeedItem,
     ) -> color_eyre::Result<Vec<FeedEntry>> {
-        let mut entries = vec![];
-
         let feedir = self
             .path
             .join(DATA_CATEGORIES_DIR)
             .join(&category.title)
             .join(&item.slug);
 
-        for entry in fs::read_dir(&feedir)? {
+        self.load_entries_in(&feedir)
+    }
+
+    fn load_entries_in(&self, feedir: &Path) -> color_eyre::Result<Vec<FeedEntry>> {
+        let state = self.load_feed_state(feedir)?;
+        let mut entries = self.parse_entries_in(feedir)?;
+
+        for entry in entries.iter_mut() {
+            apply_entry_state(entry, &state);
+        }
+
+        Ok(entries)
+    }
+
+    fn parse_entries_in(&self, feedir: &Path) -> color_eyre::Result<Vec<FeedEntry>> {
+        let mut entries = vec![];
+
+        for entry in fs::read_dir(feedir)? {
             let entry = entry?;
             let path = entry.path();
             if path.is_file() {
@@ -299,31 +724,84 @@ impl LibraryData {
         Ok(entries)
     }
 
+    /// Gives a state file to the feeds that still keep `seen` in the front matter of their
+    /// entries. Runs when the library is opened, so reading the library never writes to it.
+    pub fn migrate_feed_states(&self) {
+        let Ok(categories) = fs::read_dir(self.path.join(DATA_CATEGORIES_DIR)) else {
+            return;
+        };
+
+        let feedirs = categories
+            .flatten()
+            .filter_map(|category| fs::read_dir(category.path()).ok())
+            .flat_map(|feeds| feeds.flatten().map(|feed| feed.path()))
+            .filter(|feedir| feedir.join(DATA_FEED).exists());
+
+        for feedir in feedirs {
+            if FeedState::path(&feedir).exists() {
+                continue;
+            }
+
+            info!("Migrating read state of {:?}", feedir);
+            if let Err(e) = self.load_feed_state(&feedir).and_then(|state| state.save()) {
+                error!("Couldn't migrate the read state of {:?}: {}", feedir, e);
+            }
+        }
+    }
+
+    /// Loads the state of a feed. Feeds without a state file take it from the `seen` field in
+    /// the front matter of their entries, and get the file on their first write.
+    fn load_feed_state(&self, feedir: &Path) -> color_eyre::Result<FeedState> {
+        if let Some(state) = FeedState::load(feedir)? {
+            return Ok(state);
+        }
+
+        let mut state = FeedState::new(feedir);
+        if !feedir.is_dir() {
+            return Ok(state);
+        }
+
+        for entry in self.parse_entries_in(feedir)?.iter().filter(|e| e.seen) {
+            state.entries.insert(
+                entry_file_name(entry).to_string(),
+                EntryState {
+                    seen: true,
+                    read_at: None,
+                },
+            );
+        }
+
+        Ok(state)
+    }
+
     // TODO: this needs to be cached and only updated every now and then, since it's beeing pretty
     // intensive now
     pub fn get_unread_feed(&self, category: &str, feed_slug: &str) -> color_eyre::Result<u16> {
-        let mut unread: u16 = 0;
-
         let feedir = self
             .path
             .join(DATA_CATEGORIES_DIR)
             .join(category)
             .join(feed_slug);
 
-        for entry in fs::read_dir(feedir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_file() {
-                let contents = std::fs::read_to_string(&path)?;
-                if let Ok(entry) = self.parse_feed_entry(&contents, &path)
-                    && !entry.seen
-                {
-                    unread += 1;
-                }
-            }
+        let unread = self
+            .load_entries_in(&feedir)?
+            .iter()
+            .filter(|e| !e.seen)
+            .count();
+
+        Ok(unread.try_into().unwrap_or(u16::MAX))
+    }
+
+    /// Loads a single entry along with its read state
+    pub fn load_entry(&self, filepath: &Path) -> color_eyre::Result<FeedEntry> {
+        let contents = fs::read_to_string(filepath)?;
+        let mut entry = self.parse_feed_entry(&contents, filepath)?;
+
+        if let Some(feedir) = filepath.parent() {
+            apply_entry_state(&mut entry, &self.load_feed_state(feedir)?);
         }
 
-        Ok(unread)
+        Ok(entry)
     }
 
     fn parse_feed_entry(&self, contents: &str, path: &Path) -> color_eyre::Result<FeedEntry> {
@@ -346,19 +824,34 @@ impl LibraryData {
 
     pub fn set_entry_seen(&self, entry: &FeedEntry) {
         if !entry.seen {
-            let mut entry = entry.clone();
-            entry.seen = true;
-            if let Err(e) = self.save_feed_entry(&entry) {
-                error!("Couldn't set entry seen: {:?}", e);
-            }
+            self.set_entries_seen(std::slice::from_ref(entry), true);
         }
     }
 
     pub fn toggle_entry_seen(&self, entry: &FeedEntry) {
-        let mut entry = entry.clone();
-        entry.seen = !entry.seen;
-        if let Err(e) = self.save_feed_entry(&entry) {
-            error!("Couldn't toggle entry seen: {:?}", e);
+        self.set_entries_seen(std::slice::from_ref(entry), !entry.seen);
+    }
+
+    /// Updates the state files of the feeds the entries belong to, writing each of them once
+    pub fn set_entries_seen(&self, entries: &[FeedEntry], seen: bool) {
+        let mut by_feed: BTreeMap<&Path, Vec<&FeedEntry>> = BTreeMap::new();
+        for entry in entries.iter() {
+            if let Some(feedir) = entry.filepath.parent() {
+                by_feed.entry(feedir).or_default().push(entry);
+            }
+        }
+
+        for (feedir, entries) in by_feed {
+            let result = self.load_feed_state(feedir).and_then(|mut state| {
+                for entry in entries {
+                    state.set_seen(entry_file_name(entry), seen);
+                }
+                state.save()
+            });
+
+            if let Err(e) = result {
+                error!("Couldn't set entries seen: {:?}", e);
+            }
         }
     }
 
@@ -398,11 +891,20 @@ impl LibraryData {
         let read_later_list = self.load_read_later()?;
         let mut feed_entries: Vec<FeedEntry> = Vec::new();
 
+        let mut states: BTreeMap<PathBuf, FeedState> = BTreeMap::new();
+
         for rel in read_later_list.read_later {
             let full_path = self.path.join(DATA_CATEGORIES_DIR).join(rel);
             if let Ok(contents) = std::fs::read_to_string(&full_path)
-                && let Ok(fe) = self.parse_feed_entry(&contents, &full_path)
+                && let Ok(mut fe) = self.parse_feed_entry(&contents, &full_path)
             {
+                if let Some(feedir) = full_path.parent() {
+                    if !states.contains_key(feedir) {
+                        states.insert(feedir.to_path_buf(), self.load_feed_state(feedir)?);
+                    }
+                    apply_entry_state(&mut fe, &states[feedir]);
+                }
+
                 feed_entries.push(fe);
             }
         }
@@ -425,7 +927,7 @@ impl LibraryData {
         Ok(())
     }
 
-    fn load_read_later(&mut self) -> color_eyre::Result<ReadLaterData> {
+    fn load_read_later(&self) -> color_eyre::Result<ReadLaterData> {
         let read_later_path = self.path.join(DATA_READ_LATER);
         if !read_later_path.exists() {
             return Ok(ReadLaterData::default());
@@ -451,8 +953,11 @@ impl LibraryData {
         Ok(read_later)
     }
 
+    /// Writes to a temporary file first and renames it over `.later.toml`, so a failed write
+    /// never leaves a truncated list behind.
     fn save_read_later(&self, read_later_list: &ReadLaterData) -> color_eyre::Result<()> {
         let read_later_path = self.path.join(DATA_READ_LATER);
+        let temp_path = self.path.join(format!("{DATA_READ_LATER}.tmp"));
         let toml_str = toml::to_string(read_later_list)
             .map_err(|e| eyre!("Failed to serialize read later data: {}", e))?;
 
@@ -460,11 +965,11 @@ impl LibraryData {
             .write(true)
             .create(true)
             .truncate(true)
-            .open(&read_later_path)
+            .open(&temp_path)
             .map_err(|e| {
                 eyre!(
                     "Couldn't open read later file {}: {}",
-                    read_later_path.display(),
+                    temp_path.display(),
                     e
                 )
             })?;
@@ -472,6 +977,14 @@ impl LibraryData {
         file.write_all(toml_str.as_bytes()).map_err(|e| {
             eyre!(
                 "Failed to write read later file {}: {}",
+                temp_path.display(),
+                e
+            )
+        })?;
+
+        fs::rename(&temp_path, &read_later_path).map_err(|e| {
+            eyre!(
+                "Failed to replace read later file {}: {}",
                 read_later_path.display(),
                 e
             )
@@ -491,6 +1004,64 @@ impl LibraryData {
     }
 }
 
+fn apply_entry_state(entry: &mut FeedEntry, state: &FeedState) {
+    let entry_state = state.entries.get(entry_file_name(entry));
+    entry.seen = entry_state.is_some_and(|s| s.seen);
+    entry.read_at = entry_state.and_then(|s| s.read_at);
+}
+
+/// Entry files are named after the slug of their title, cut so the whole path stays short
+fn entry_path(feedir: &Path, title: &str) -> PathBuf {
+    let base_path = feedir.to_string_lossy();
+    let max_slug_len = 250usize.saturating_sub(base_path.len() + 1);
+    let slug = slugify(title);
+    let slug_cut = &slug[..slug.len().min(max_slug_len)];
+
+    feedir.join(format!("{slug_cut}.md"))
+}
+
+fn entry_file_name(entry: &FeedEntry) -> &str {
+    entry
+        .filepath
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default()
+}
+
+pub fn validate_category_name(name: &str) -> color_eyre::Result<()> {
+    let mut components = Path::new(name).components();
+    let valid = matches!(
+        (components.next(), components.next()),
+        (Some(std::path::Component::Normal(_)), None)
+    );
+
+    if valid && name.trim() == name {
+        Ok(())
+    } else {
+        Err(eyre!("Invalid category name: {:?}", name))
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct CategoryFolders {
+    folders: Vec<String>,
+}
+
+fn load_category_folders(category_dir: &Path) -> Vec<String> {
+    let path = category_dir.join(DATA_CATEGORY_FOLDERS);
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return vec![];
+    };
+
+    match toml::from_str::<CategoryFolders>(&contents) {
+        Ok(data) => data.folders,
+        Err(e) => {
+            error!("Error parsing {path:?}: {e}");
+            vec![]
+        }
+    }
+}
+
 pub fn load_or_create(path: &Path) {
     let datapath = Path::new(path);
     std::fs::create_dir_all(datapath).expect("Error: Failed to create datapath directory");
@@ -560,4 +1131,248 @@ This is the content."#;
         let result = ld.parse_feed_entry(content, path);
         assert!(result.is_err());
     }
+
+    fn create_feed_with_entry(ld: &LibraryData, category: &str, slug: &str) -> FeedEntry {
+        let feed = FeedItem {
+            title: slug.to_string(),
+            slug: slug.to_string(),
+            category: category.to_string(),
+            ..Default::default()
+        };
+        ld.feed_create(&feed).unwrap();
+
+        let entry = FeedEntry {
+            title: "Entry".to_string(),
+            filepath: ld
+                .path
+                .join(DATA_CATEGORIES_DIR)
+                .join(category)
+                .join(slug)
+                .join("entry.md"),
+            ..Default::default()
+        };
+        ld.write_feed_entry(&entry).unwrap();
+        entry
+    }
+
+    #[test]
+    fn test_move_feed_rewrites_read_later() {
+        let (mut ld, _temp) = LibraryData::new_for_test();
+        let entry = create_feed_with_entry(&ld, "General", "blog");
+        ld.add_to_read_later(&entry).unwrap();
+
+        ld.move_feed("blog", "General", "Tech").unwrap();
+
+        assert!(!ld.feed_exists("blog", "General"));
+        assert!(ld.feed_exists("blog", "Tech"));
+
+        let moved = ld.path.join(DATA_CATEGORIES_DIR).join("Tech/blog/entry.md");
+        assert!(ld.is_in_read_later(moved.to_str().unwrap()).unwrap());
+
+        // the references must survive a reload from disk
+        let entries = ld.get_read_later_feed_entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].filepath, moved);
+    }
+
+    #[test]
+    fn test_move_feed_to_existing_fails() {
+        let (mut ld, _temp) = LibraryData::new_for_test();
+        create_feed_with_entry(&ld, "General", "blog");
+        create_feed_with_entry(&ld, "Tech", "blog");
+
+        assert!(ld.move_feed("blog", "General", "Tech").is_err());
+        assert!(ld.feed_exists("blog", "General"));
+        assert!(ld.move_feed("blog", "General", "../outside").is_err());
+    }
+
+    #[test]
+    fn test_rename_feed_changes_title_and_slug() {
+        let (mut ld, _temp) = LibraryData::new_for_test();
+        let entry = create_feed_with_entry(&ld, "General", "blog");
+        ld.add_to_read_later(&entry).unwrap();
+
+        let feed = ld.rename_feed("blog", "General", "My Blog").unwrap();
+        assert_eq!(feed.slug, "my-blog");
+        assert!(ld.feed_exists("my-blog", "General"));
+
+        let categories = ld.generate_categories_tree().unwrap();
+        assert_eq!(categories[0].feeds[0].title, "My Blog");
+        assert_eq!(ld.get_read_later_feed_entries().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_rename_category() {
+        let (mut ld, _temp) = LibraryData::new_for_test();
+        let entry = create_feed_with_entry(&ld, "Tech", "blog");
+        ld.add_to_read_later(&entry).unwrap();
+
+        ld.rename_category("Tech", "Programming").unwrap();
+
+        assert!(!ld.category_exists("Tech"));
+        assert!(ld.feed_exists("blog", "Programming"));
+        let entries = ld.get_read_later_feed_entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert!(
+            entries[0]
+                .filepath
+                .starts_with(ld.path.join(DATA_CATEGORIES_DIR).join("Programming"))
+        );
+    }
+
+    #[test]
+    fn test_delete_category_drops_read_later() {
+        let (mut ld, _temp) = LibraryData::new_for_test();
+        let kept = create_feed_with_entry(&ld, "General", "blog");
+        let dropped = create_feed_with_entry(&ld, "Tech", "news");
+        ld.add_to_read_later(&kept).unwrap();
+        ld.add_to_read_later(&dropped).unwrap();
+
+        ld.delete_category("Tech").unwrap();
+
+        assert!(!ld.category_exists("Tech"));
+        assert_eq!(ld.read_later.read_later, vec!["General/blog/entry.md"]);
+    }
+
+    fn save_dated_entry(ld: &LibraryData, feedir: &Path, name: &str, days: i64, seen: bool) {
+        let entry = FeedEntry {
+            title: name.to_string(),
+            date: Utc::now() - Duration::days(days),
+            filepath: feedir.join(format!("{name}.md")),
+            ..Default::default()
+        };
+        ld.write_feed_entry(&entry).unwrap();
+        ld.set_entries_seen(&[entry], seen);
+    }
+
+    #[test]
+    fn test_prune_feed_entries() {
+        let (mut ld, _temp) = LibraryData::new_for_test();
+        let later = create_feed_with_entry(&ld, "General", "blog");
+        ld.add_to_read_later(&later).unwrap();
+
+        let feed = ld.load_feed("blog", "General").unwrap();
+        let feedir = ld
+            .path
+            .join(DATA_CATEGORIES_DIR)
+            .join("General")
+            .join("blog");
+        save_dated_entry(&ld, &feedir, "new", 1, true);
+        save_dated_entry(&ld, &feedir, "old-read", 40, true);
+        save_dated_entry(&ld, &feedir, "old-unread", 50, false);
+
+        let policy = RetentionPolicy {
+            max_age_days: Some(30),
+            ..Default::default()
+        };
+
+        let dry = ld.prune_feed_entries(&feed, &policy, true).unwrap();
+        assert_eq!(dry.len(), 1);
+        assert!(feedir.join("old-read.md").exists());
+
+        let pruned = ld.prune_feed_entries(&feed, &policy, false).unwrap();
+        assert_eq!(pruned.len(), 1);
+        assert!(!feedir.join("old-read.md").exists());
+        assert!(feedir.join("old-unread.md").exists());
+        assert!(feedir.join("new.md").exists());
+        assert!(later.filepath.exists());
+
+        let policy = RetentionPolicy {
+            max_entries: Some(1),
+            keep_unread: Some(false),
+            ..Default::default()
+        };
+
+        ld.prune_feed_entries(&feed, &policy, false).unwrap();
+        assert!(!feedir.join("old-unread.md").exists());
+        assert!(feedir.join("new.md").exists());
+        assert!(later.filepath.exists());
+    }
+
+    #[test]
+    fn test_pruned_entries_are_not_downloaded_again() {
+        let (ld, _temp) = LibraryData::new_for_test();
+        create_feed_with_entry(&ld, "General", "blog");
+        let feed = ld.load_feed("blog", "General").unwrap();
+        let feedir = ld
+            .path
+            .join(DATA_CATEGORIES_DIR)
+            .join("General")
+            .join("blog");
+
+        let rss = r#"<rss version="2.0"><channel><title>Blog</title>
+            <item><title>First</title><pubDate>Mon, 01 Jan 2024 00:00:00 GMT</pubDate></item>
+            <item><title>Second</title><pubDate>Tue, 02 Jan 2024 00:00:00 GMT</pubDate></item>
+            </channel></rss>"#;
+
+        ld.update_feed_entries("General", &feed, Some(rss.to_string()))
+            .unwrap();
+        assert!(feedir.join("first.md").exists());
+
+        let policy = RetentionPolicy {
+            max_entries: Some(1),
+            keep_unread: Some(false),
+            ..Default::default()
+        };
+        ld.prune_feed_entries(&feed, &policy, false).unwrap();
+        assert!(!feedir.join("first.md").exists());
+
+        ld.update_feed_entries("General", &feed, Some(rss.to_string()))
+            .unwrap();
+        assert!(!feedir.join("first.md").exists());
+        assert!(feedir.join("second.md").exists());
+    }
+
+    #[test]
+    fn test_seen_state_is_migrated_and_kept_out_of_entries() {
+        let (ld, _temp) = LibraryData::new_for_test();
+        create_feed_with_entry(&ld, "General", "blog");
+        let feedir = ld
+            .path
+            .join(DATA_CATEGORIES_DIR)
+            .join("General")
+            .join("blog");
+
+        let legacy = feedir.join("legacy.md");
+        let legacy_contents = indoc::indoc! {r#"
+            +++
+            title = "Legacy"
+            description = ""
+            date = "2024-01-01T00:00:00Z"
+            url = ""
+            author = ""
+            text = ""
+            lastupdated = "2024-01-01T00:00:00Z"
+            seen = true
+            +++
+
+            Body
+        "#};
+        fs::write(&legacy, legacy_contents).unwrap();
+
+        let entries = ld.load_entries_in(&feedir).unwrap();
+        let legacy_entry = entries.iter().find(|e| e.title == "Legacy").unwrap();
+        assert!(legacy_entry.seen);
+        assert!(!entries.iter().find(|e| e.title == "Entry").unwrap().seen);
+        assert_eq!(ld.get_unread_feed("General", "blog").unwrap(), 1);
+        assert!(!FeedState::path(&feedir).exists());
+
+        let ld = LibraryData::new(&ld.path);
+        assert!(FeedState::path(&feedir).exists());
+        assert_eq!(fs::read_to_string(&legacy).unwrap(), legacy_contents);
+
+        ld.toggle_entry_seen(legacy_entry);
+        assert_eq!(fs::read_to_string(&legacy).unwrap(), legacy_contents);
+
+        let entries = ld.load_entries_in(&feedir).unwrap();
+        assert!(!entries.iter().find(|e| e.title == "Legacy").unwrap().seen);
+
+        let entry = entries.iter().find(|e| e.title == "Entry").unwrap();
+        ld.set_entry_seen(entry);
+        let entries = ld.load_entries_in(&feedir).unwrap();
+        let entry = entries.iter().find(|e| e.title == "Entry").unwrap();
+        assert!(entry.seen);
+        assert!(entry.read_at.is_some());
+        assert_eq!(ld.get_unread_feed("General", "blog").unwrap(), 1);
+    }
 }
@@ -1,2 +1,5 @@
+pub mod feedstate;
+pub mod gitrepo;
 pub mod librarydata;
+pub mod newsboat;
 pub mod opml;
@@ -0,0 +1,227 @@
+use std::{collections::HashMap, path::Path};
+
+use chrono::{DateTime, Utc};
+use color_eyre::{Result, eyre::eyre};
+use rusqlite::{Connection, OpenFlags};
+
+use crate::core::library::data::opml::OpmlFeed;
+
+/// An article from newsboat's `cache.db`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CachedItem {
+    pub feed_url: String,
+    pub title: String,
+    pub author: String,
+    pub url: String,
+    pub date: DateTime<Utc>,
+    pub content: String,
+    pub unread: bool,
+    pub flagged: bool,
+}
+
+pub fn get_urls_feeds(filename: &str) -> Result<Vec<OpmlFeed>> {
+    let contents = std::fs::read_to_string(filename)?;
+    Ok(parse_urls(&contents))
+}
+
+/// Parses newsboat's `urls` file: one feed per line, followed by its tags. The first tag is the
+/// category and a `"~Title"` tag overrides the title. Query feeds and `exec:`/`filter:` sources
+/// are skipped, since they aren't plain feeds.
+pub fn parse_urls(contents: &str) -> Vec<OpmlFeed> {
+    let mut feeds = vec![];
+
+    for line in contents.lines() {
+        let fields = split_fields(line);
+        let Some((url, tags)) = fields.split_first() else {
+            continue;
+        };
+
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            continue;
+        }
+
+        let mut feed = OpmlFeed {
+            url: url.clone(),
+            ..Default::default()
+        };
+
+        for tag in tags {
+            if let Some(title) = tag.strip_prefix('~') {
+                feed.title = Some(title.to_string());
+            } else if !tag.starts_with('!') && feed.category.is_none() {
+                feed.category = Some(tag.replace('/', "-"));
+            }
+        }
+
+        feeds.push(feed);
+    }
+
+    feeds
+}
+
+/// Splits a line of the `urls` file on whitespace, keeping quoted fields together and stopping
+/// at comments
+fn split_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![];
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut chars = line.trim().chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => quoted = !quoted,
+            '\\' if quoted => {
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            '#' if !quoted && current.is_empty() => break,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    fields.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+
+    if !current.is_empty() {
+        fields.push(current);
+    }
+
+    fields
+}
+
+/// Fills in what the `urls` file doesn't have (titles without an override, site URLs) from the
+/// feeds newsboat has cached
+pub fn complete_from_cache(feeds: &mut [OpmlFeed], cache: &Path) -> Result<()> {
+    let db = open_cache(cache)?;
+    let mut statement = db.prepare("SELECT rssurl, url, title FROM rss_feed")?;
+
+    let cached: HashMap<String, (String, String)> = statement
+        .query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    for feed in feeds.iter_mut() {
+        if let Some((url, title)) = cached.get(&feed.url) {
+            if feed.title.is_none() && !title.is_empty() {
+                feed.title = Some(title.clone());
+            }
+            if feed.html_url.is_none() && !url.is_empty() {
+                feed.html_url = Some(url.clone());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads the articles newsboat has cached, leaving out the ones deleted there
+pub fn read_cache(cache: &Path) -> Result<Vec<CachedItem>> {
+    let db = open_cache(cache)?;
+    let mut statement = db.prepare(
+        "SELECT feedurl, title, author, url, pubDate, content, unread, flags
+         FROM rss_item WHERE deleted = 0 ORDER BY pubDate DESC",
+    )?;
+
+    let items = statement
+        .query_map([], |row| {
+            let flags: Option<String> = row.get(7)?;
+            Ok(CachedItem {
+                feed_url: row.get(0)?,
+                title: row.get(1)?,
+                author: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+                url: row.get(3)?,
+                date: DateTime::from_timestamp(row.get(4)?, 0).unwrap_or_default(),
+                content: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
+                unread: row.get::<_, i64>(6)? != 0,
+                flagged: flags.is_some_and(|f| !f.is_empty()),
+            })
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+
+    Ok(items)
+}
+
+fn open_cache(cache: &Path) -> Result<Connection> {
+    Connection::open_with_flags(cache, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| eyre!("Couldn't open newsboat cache {}: {}", cache.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_urls() {
+        let feeds = parse_urls(indoc! {r#"
+            # my feeds
+            https://blog.rust-lang.org/feed.xml rust "~Rust Blog" programming
+            https://example.com/rss "!hidden" "web/dev"
+            "query:Unread:unread = \"yes\""
+            exec:~/bin/feed.sh
+            http://plain.example/atom
+        "#});
+
+        assert_eq!(
+            feeds,
+            vec![
+                OpmlFeed {
+                    url: String::from("https://blog.rust-lang.org/feed.xml"),
+                    title: Some(String::from("Rust Blog")),
+                    category: Some(String::from("rust")),
+                    ..Default::default()
+                },
+                OpmlFeed {
+                    url: String::from("https://example.com/rss"),
+                    category: Some(String::from("web-dev")),
+                    ..Default::default()
+                },
+                OpmlFeed {
+                    url: String::from("http://plain.example/atom"),
+                    ..Default::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_cache() {
+        let dir = TempDir::new().unwrap();
+        let cache = dir.path().join("cache.db");
+
+        let db = Connection::open(&cache).unwrap();
+        db.execute_batch(indoc! {"
+            CREATE TABLE rss_feed (rssurl VARCHAR(1024) PRIMARY KEY NOT NULL, url VARCHAR(1024) NOT NULL,
+                title VARCHAR(1024) NOT NULL, lastmodified INTEGER(11) NOT NULL DEFAULT 0,
+                is_rtl INTEGER(1) NOT NULL DEFAULT 0, etag VARCHAR(128) NOT NULL DEFAULT '');
+            CREATE TABLE rss_item (id INTEGER PRIMARY KEY AUTOINCREMENT, guid VARCHAR(64) NOT NULL,
+                title VARCHAR(1024) NOT NULL, author VARCHAR(1024) NOT NULL, url VARCHAR(1024) NOT NULL,
+                feedurl VARCHAR(1024) NOT NULL, pubDate INTEGER NOT NULL, content VARCHAR(65535) NOT NULL,
+                unread INTEGER(1) NOT NULL, enclosure_url VARCHAR(1024), enclosure_type VARCHAR(1024),
+                enqueued INTEGER(1) NOT NULL DEFAULT 0, flags VARCHAR(52), deleted INTEGER(1) NOT NULL DEFAULT 0,
+                base VARCHAR(128) NOT NULL DEFAULT '');
+            INSERT INTO rss_feed (rssurl, url, title) VALUES ('https://example.com/rss', 'https://example.com', 'Example');
+            INSERT INTO rss_item (guid, title, author, url, feedurl, pubDate, content, unread, flags, deleted) VALUES
+                ('1', 'Old', 'Jane', 'https://example.com/1', 'https://example.com/rss', 1600000000, '<p>old</p>', 0, 's', 0),
+                ('2', 'New', '', 'https://example.com/2', 'https://example.com/rss', 1700000000, 'new', 1, NULL, 0),
+                ('3', 'Gone', '', 'https://example.com/3', 'https://example.com/rss', 1700000001, '', 1, NULL, 1);
+        "})
+        .unwrap();
+
+        let items = read_cache(&cache).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].title, "New");
+        assert!(items[0].unread && !items[0].flagged);
+        assert_eq!(items[1].author, "Jane");
+        assert!(!items[1].unread && items[1].flagged);
+
+        let mut feeds = parse_urls("https://example.com/rss\nhttps://other.example/rss \"~Mine\"");
+        complete_from_cache(&mut feeds, &cache).unwrap();
+        assert_eq!(feeds[0].title.as_deref(), Some("Example"));
+        assert_eq!(feeds[0].html_url.as_deref(), Some("https://example.com"));
+        assert_eq!(feeds[1].title.as_deref(), Some("Mine"));
+    }
+}
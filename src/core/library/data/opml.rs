@@ -1,88 +1,167 @@
 use color_eyre::{Result, eyre};
 use roxmltree::Node;
 
-use crate::core::library::feedcategory::FeedCategory;
+use crate::core::library::{feedcategory::FeedCategory, feeditem::FeedItem};
 
+/// Categories can't be nested, so nested folders are kept as a single category named after the
+/// whole path. The path itself is recorded with the category, to export the folders again.
+const CATEGORY_SEPARATOR: &str = " - ";
+
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct OpmlFeed {
     pub url: String,
+    pub title: Option<String>,
+    pub html_url: Option<String>,
+    pub description: Option<String>,
     pub category: Option<String>,
+    /// The folders the feed was in, outermost first
+    pub folders: Vec<String>,
 }
 
 pub fn get_opml_feeds(filename: &str) -> Result<Vec<OpmlFeed>> {
     let doc = std::fs::read_to_string(filename)?;
-    let doc = roxmltree::Document::parse(&doc)?;
+    parse_opml(&doc).map_err(|e| eyre::eyre!("{:?}: {}", filename, e))
+}
 
-    let body = doc.descendants().find(|n| n.has_tag_name("body"));
-    if body.is_none() {
-        return Err(eyre::eyre!("No body found in {:?}", filename));
-    }
+pub fn parse_opml(doc: &str) -> Result<Vec<OpmlFeed>> {
+    let doc = roxmltree::Document::parse(doc)?;
 
-    let outlines: Vec<Node> = body
-        .unwrap()
-        .children()
-        .filter(|n| n.is_element() && n.has_tag_name("outline"))
-        .collect();
+    let Some(body) = doc.descendants().find(|n| n.has_tag_name("body")) else {
+        return Err(eyre::eyre!("No body found"));
+    };
 
     let mut opml_feeds = Vec::<OpmlFeed>::new();
+    collect_outlines(&body, &[], &mut opml_feeds);
 
-    for o in outlines.iter() {
-        if o.has_attribute("xmlUrl") {
-            if let Ok(feed) = get_opml_feed(o, None) {
-                opml_feeds.push(feed);
-            }
+    Ok(opml_feeds)
+}
+
+fn collect_outlines(parent: &Node, folders: &[String], feeds: &mut Vec<OpmlFeed>) {
+    for o in parent
+        .children()
+        .filter(|n| n.is_element() && n.has_tag_name("outline"))
+    {
+        if let Some(feed) = get_opml_feed(&o, folders) {
+            feeds.push(feed);
         } else {
-            let title = o
-                .attribute("title")
-                .or_else(|| o.attribute("text"))
-                .map(|s| s.to_string());
-
-            let feeds: Vec<OpmlFeed> = o
-                .children()
-                .map(|c| get_opml_feed(&c, title.clone()))
-                .filter_map(Result::ok)
-                .collect();
-
-            opml_feeds.extend(feeds);
+            let mut folders = folders.to_vec();
+            if let Some(name) = outline_title(&o) {
+                folders.push(name.replace('/', "-"));
+            }
+
+            collect_outlines(&o, &folders, feeds);
         }
     }
+}
 
-    Ok(opml_feeds)
+fn get_opml_feed(node: &Node, folders: &[String]) -> Option<OpmlFeed> {
+    // OPML 1.0 exports sometimes only have `url` on `type="rss"` outlines
+    let is_rss = attribute(node, "type").is_some_and(|t| t.eq_ignore_ascii_case("rss"));
+    let url = attribute(node, "xmlUrl").or(attribute(node, "url").filter(|_| is_rss))?;
+
+    Some(OpmlFeed {
+        url: url.trim().to_string(),
+        title: outline_title(node),
+        html_url: attribute(node, "htmlUrl").map(String::from),
+        description: attribute(node, "description").map(String::from),
+        category: if folders.is_empty() {
+            None
+        } else {
+            Some(folders.join(CATEGORY_SEPARATOR))
+        },
+        folders: folders.to_vec(),
+    })
 }
 
-fn get_opml_feed(node: &Node, category: Option<String>) -> Result<OpmlFeed> {
-    if let Some(xml_url) = node.attribute("xmlUrl") {
-        Ok(OpmlFeed {
-            url: xml_url.to_string(),
-            category,
-        })
-    } else {
-        Err(eyre::eyre!("No xml attribute found in element"))
-    }
+/// `text` is the only required attribute, but plenty of exporters only set `title`, or neither
+fn outline_title(node: &Node) -> Option<String> {
+    attribute(node, "title")
+        .or_else(|| attribute(node, "text"))
+        .map(|s| s.trim().to_string())
+}
+
+/// Looks up an attribute ignoring its case (`xmlurl`, `htmlURL`...), skipping empty values
+fn attribute<'a>(node: &Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attributes()
+        .find(|a| a.name().eq_ignore_ascii_case(name))
+        .map(|a| a.value())
+        .filter(|v| !v.trim().is_empty())
 }
 
 pub fn save_opml(categories: &[FeedCategory], filename: &str) -> Result<()> {
-    let mut text_categories = String::new();
-    for category in categories.iter() {
-        let mut text_feeds = String::new();
-        for feed in category.feeds.iter() {
+    std::fs::write(filename, opml_string(categories))?;
+    Ok(())
+}
+
+/// A folder of the export, with the categories whose path goes through it
+#[derive(Default)]
+struct Folder<'a> {
+    title: &'a str,
+    feeds: Vec<&'a FeedItem>,
+    folders: Vec<Folder<'a>>,
+}
+
+impl<'a> Folder<'a> {
+    fn insert(&mut self, path: &[&'a str], feeds: &'a [FeedItem]) {
+        let Some((title, rest)) = path.split_first() else {
+            self.feeds.extend(feeds);
+            return;
+        };
+
+        let index = match self.folders.iter().position(|f| f.title == *title) {
+            Some(index) => index,
+            None => {
+                self.folders.push(Folder {
+                    title,
+                    ..Default::default()
+                });
+                self.folders.len() - 1
+            }
+        };
+        self.folders[index].insert(rest, feeds);
+    }
+
+    fn write(&self, depth: usize, text: &mut String) {
+        let indent = " ".repeat(4 * depth);
+
+        for feed in self.feeds.iter() {
             let title = html_escape::encode_double_quoted_attribute(&feed.title);
             let description = html_escape::encode_double_quoted_attribute(&feed.description);
             let feed_url = html_escape::encode_double_quoted_attribute(&feed.feed_url);
+            let html_url = html_escape::encode_double_quoted_attribute(&feed.url);
 
-            text_feeds.push_str(&format!("\n            <outline text=\"{}\" title=\"{}\" description=\"{}\" xmlUrl=\"{}\" type=\"rss\" />", title, title, description, feed_url));
+            text.push_str(&format!("\n{indent}<outline type=\"rss\" text=\"{}\" title=\"{}\" description=\"{}\" xmlUrl=\"{}\" htmlUrl=\"{}\" />", title, title, description, feed_url, html_url));
         }
 
-        let title = html_escape::encode_double_quoted_attribute(&category.title);
+        for folder in self.folders.iter() {
+            let title = html_escape::encode_double_quoted_attribute(folder.title);
+            text.push_str(&format!(
+                "\n{indent}<outline text=\"{}\" title=\"{}\">",
+                title, title
+            ));
+            folder.write(depth + 1, text);
+            text.push_str(&format!("\n{indent}</outline>"));
+        }
+    }
+}
 
-        text_categories.push_str(&format!(
-            "\n        <outline text=\"{}\" title=\"{}\">{}\n        </outline>",
-            title, title, text_feeds
-        ));
+fn opml_string(categories: &[FeedCategory]) -> String {
+    let mut root = Folder::default();
+    for category in categories.iter() {
+        let path: Vec<&str> = if category.folders.is_empty() {
+            vec![category.title.as_str()]
+        } else {
+            category.folders.iter().map(String::as_str).collect()
+        };
+        root.insert(&path, &category.feeds);
     }
 
-    let opml = format!(
+    let mut text_categories = String::new();
+    root.write(2, &mut text_categories);
+
+    format!(
         r#"<?xml version='1.0' encoding='UTF-8' ?>
-<opml version="1.0">
+<opml version="2.0">
     <head>
         <title>Generated from bulletty</title>
         <url>https://github.com/crocidb/bulletty</url>
@@ -91,9 +170,204 @@ pub fn save_opml(categories: &[FeedCategory], filename: &str) -> Result<()> {
     </body>
 </opml>
 "#
-    );
+    )
+}
 
-    std::fs::write(filename, opml)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
 
-    Ok(())
+    #[test]
+    fn test_parse_nested_outlines_and_quirks() {
+        let feeds = parse_opml(indoc! {r#"
+            <?xml version="1.0"?>
+            <opml version="1.0">
+              <head><title>Subscriptions</title></head>
+              <body>
+                <outline xmlUrl="https://top.example/feed" text="Top" htmlUrl="https://top.example" />
+                <outline text="Tech">
+                  <outline title="Rust Blog" text="ignored" xmlurl="https://blog.rust-lang.org/feed.xml"
+                    description="Rust news" />
+                  <outline text="Web/Frontend">
+                    <outline type="rss" url="https://css.example/rss" />
+                  </outline>
+                  <outline type="link" url="https://not-a-feed.example" text="Link" />
+                </outline>
+              </body>
+            </opml>
+        "#})
+        .unwrap();
+
+        assert_eq!(
+            feeds,
+            vec![
+                OpmlFeed {
+                    url: String::from("https://top.example/feed"),
+                    title: Some(String::from("Top")),
+                    html_url: Some(String::from("https://top.example")),
+                    ..Default::default()
+                },
+                OpmlFeed {
+                    url: String::from("https://blog.rust-lang.org/feed.xml"),
+                    title: Some(String::from("Rust Blog")),
+                    description: Some(String::from("Rust news")),
+                    category: Some(String::from("Tech")),
+                    folders: vec![String::from("Tech")],
+                    ..Default::default()
+                },
+                OpmlFeed {
+                    url: String::from("https://css.example/rss"),
+                    category: Some(String::from("Tech - Web-Frontend")),
+                    folders: vec![String::from("Tech"), String::from("Web-Frontend")],
+                    ..Default::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let categories = vec![FeedCategory {
+            title: String::from("News & Stuff"),
+            feeds: vec![FeedItem {
+                title: String::from("A \"quoted\" feed"),
+                description: String::from("Things"),
+                url: String::from("https://example.com"),
+                feed_url: String::from("https://example.com/feed?a=1&b=2"),
+                ..Default::default()
+            }],
+            folders: vec![],
+        }];
+
+        let feeds = parse_opml(&opml_string(&categories)).unwrap();
+        assert_eq!(
+            feeds,
+            vec![OpmlFeed {
+                url: String::from("https://example.com/feed?a=1&b=2"),
+                title: Some(String::from("A \"quoted\" feed")),
+                html_url: Some(String::from("https://example.com")),
+                description: Some(String::from("Things")),
+                category: Some(String::from("News & Stuff")),
+                folders: vec![String::from("News & Stuff")],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_nested_folders_round_trip() {
+        let feed = |title: &str| FeedItem {
+            title: title.to_string(),
+            feed_url: format!("https://{title}.example/feed"),
+            ..Default::default()
+        };
+        let opml = indoc! {r#"
+            <opml version="2.0">
+              <body>
+                <outline text="Tech">
+                  <outline type="rss" text="lwn" xmlUrl="https://lwn.example/feed" />
+                  <outline text="Languages">
+                    <outline text="Rust">
+                      <outline type="rss" text="rust" xmlUrl="https://rust.example/feed" />
+                    </outline>
+                    <outline type="rss" text="go" xmlUrl="https://go.example/feed" />
+                  </outline>
+                </outline>
+              </body>
+            </opml>
+        "#};
+
+        let categories: Vec<Option<String>> = parse_opml(opml)
+            .unwrap()
+            .into_iter()
+            .map(|f| f.category)
+            .collect();
+        assert_eq!(
+            categories,
+            vec![
+                Some(String::from("Tech")),
+                Some(String::from("Tech - Languages - Rust")),
+                Some(String::from("Tech - Languages")),
+            ]
+        );
+
+        let folders = |path: &[&str]| path.iter().map(|f| f.to_string()).collect();
+        let library = vec![
+            FeedCategory {
+                title: String::from("News - World"),
+                feeds: vec![feed("world")],
+                folders: vec![],
+            },
+            FeedCategory {
+                title: String::from("Tech"),
+                feeds: vec![feed("lwn")],
+                folders: vec![],
+            },
+            FeedCategory {
+                title: String::from("Tech - Languages"),
+                feeds: vec![feed("go")],
+                folders: folders(&["Tech", "Languages"]),
+            },
+            FeedCategory {
+                title: String::from("Tech - Languages - Rust"),
+                feeds: vec![feed("rust")],
+                folders: folders(&["Tech", "Languages", "Rust"]),
+            },
+        ];
+        let exported = opml_string(&library);
+
+        let doc = roxmltree::Document::parse(&exported).unwrap();
+        let tech: Vec<_> = doc
+            .descendants()
+            .filter(|n| n.attribute("text") == Some("Tech"))
+            .collect();
+        assert_eq!(tech.len(), 1);
+        let rust = doc
+            .descendants()
+            .find(|n| n.attribute("text") == Some("rust"))
+            .unwrap();
+        let folders: Vec<_> = rust
+            .ancestors()
+            .filter_map(|n| n.attribute("text"))
+            .collect();
+        assert_eq!(folders, vec!["rust", "Rust", "Languages", "Tech"]);
+
+        // A category named with the separator by the user stays a single folder
+        assert!(
+            doc.descendants()
+                .any(|n| n.attribute("text") == Some("News - World"))
+        );
+        assert!(
+            !doc.descendants()
+                .any(|n| n.attribute("text") == Some("News"))
+        );
+
+        let mut reimported: Vec<(String, Option<String>)> = parse_opml(&exported)
+            .unwrap()
+            .into_iter()
+            .map(|f| (f.url, f.category))
+            .collect();
+        reimported.sort();
+        assert_eq!(
+            reimported,
+            vec![
+                (
+                    String::from("https://go.example/feed"),
+                    Some(String::from("Tech - Languages"))
+                ),
+                (
+                    String::from("https://lwn.example/feed"),
+                    Some(String::from("Tech"))
+                ),
+                (
+                    String::from("https://rust.example/feed"),
+                    Some(String::from("Tech - Languages - Rust"))
+                ),
+                (
+                    String::from("https://world.example/feed"),
+                    Some(String::from("News - World"))
+                ),
+            ]
+        );
+    }
 }
@@ -4,4 +4,7 @@ use crate::core::library::feeditem::FeedItem;
 pub struct FeedCategory {
     pub title: String,
     pub feeds: Vec<FeedItem>,
+    /// The nested OPML folders the category was imported from, to export it the same way. Empty
+    /// for the others, which are a folder of their own.
+    pub folders: Vec<String>,
 }
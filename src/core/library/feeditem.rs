@@ -1,6 +1,8 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::core::library::settings::retention::RetentionPolicy;
+
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct FeedItem {
     pub title: String,
@@ -12,6 +14,21 @@ pub struct FeedItem {
 
     pub lastupdated: DateTime<Utc>,
 
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention: Option<RetentionPolicy>,
+
+    /// A shell command the fetched feed goes through (on stdin) before being parsed, whose
+    /// output is used as the feed instead
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+
     #[serde(skip_serializing, skip_deserializing)]
     pub category: String,
 }
+
+impl FeedItem {
+    /// Whether the feed was never fetched, like feeds imported without fetching them
+    pub fn never_updated(&self) -> bool {
+        self.lastupdated == DateTime::<Utc>::default()
+    }
+}
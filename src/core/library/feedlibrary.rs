@@ -1,7 +1,13 @@
-use std::path::Path;
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread::JoinHandle,
+};
 
 use color_eyre::eyre::eyre;
 use fuzzt::algorithms::normalized_levenshtein;
+use slug::slugify;
 use tracing::error;
 
 use crate::{
@@ -9,9 +15,18 @@ use crate::{
     core::{
         defs,
         feed::{self, feedentry::FeedEntry},
+        hooks::AppHooks,
         library::{
-            data::librarydata::LibraryData, feedcategory::FeedCategory, feeditem::FeedItem,
-            settings::usersettings::UserSettings, updater::Updater,
+            data::{
+                gitrepo::LibraryRepo,
+                librarydata::{self, LibraryData},
+                newsboat::CachedItem,
+                opml::OpmlFeed,
+            },
+            feedcategory::FeedCategory,
+            feeditem::FeedItem,
+            settings::usersettings::UserSettings,
+            updater::Updater,
         },
     },
 };
@@ -19,12 +34,24 @@ use crate::{
 #[cfg(test)]
 use tempfile::TempDir;
 
+/// What happened to each feed of an import
+#[derive(Debug)]
+pub enum ImportResult {
+    Added(FeedItem),
+    AlreadySubscribed(String),
+    Failed(String, String),
+}
+
 pub struct FeedLibrary {
     pub feedcategories: Vec<FeedCategory>,
     pub data: LibraryData,
     pub updater: Option<Updater>,
     pub settings: UserSettings,
     pub generation: u64,
+    pub repo: Option<LibraryRepo>,
+    pub hooks: Arc<AppHooks>,
+    hook_threads: Vec<JoinHandle<()>>,
+    changes: Vec<String>,
     last_updater_completed: u16,
 }
 
@@ -46,6 +73,10 @@ impl FeedLibrary {
             updater: None,
             settings: UserSettings::new(data_dir).unwrap(),
             generation: 0,
+            repo: LibraryRepo::open(data_dir),
+            hooks: Arc::default(),
+            hook_threads: vec![],
+            changes: vec![],
             last_updater_completed: 0,
         }
     }
@@ -61,6 +92,10 @@ impl FeedLibrary {
                 updater: None,
                 settings: UserSettings::new(temp_dir.path()).unwrap(),
                 generation: 0,
+                repo: None,
+                hooks: Arc::default(),
+                hook_threads: vec![],
+                changes: vec![],
                 last_updater_completed: 0,
             },
             temp_dir,
@@ -71,14 +106,15 @@ impl FeedLibrary {
         &mut self,
         url: &str,
         category: &Option<String>,
+        filter: Option<&str>,
     ) -> color_eyre::Result<FeedItem> {
-        let (mut feed, text) = feed::feedparser::get_feed_with_data(url)?;
+        let (mut feed, text) = feed::feedparser::get_filtered_feed_with_data(url, filter)?;
 
         feed.category = category
             .clone()
             .unwrap_or_else(|| String::from(defs::DATA_CATEGORY_DEFAULT));
 
-        self.add_feed(feed, Some(text))
+        self.add_feed(feed, text)
     }
 
     pub fn add_feed(
@@ -86,6 +122,8 @@ impl FeedLibrary {
         feed: FeedItem,
         text: Option<String>,
     ) -> color_eyre::Result<FeedItem> {
+        librarydata::validate_category_name(&feed.category)?;
+
         // check if feed already in library
         if self.data.feed_exists(&feed.slug, &feed.category) {
             return Err(eyre!("Feed {:?} already exists", feed.title));
@@ -96,16 +134,291 @@ impl FeedLibrary {
 
         // then update
         // but let's only update the text is present. because of tests. maybve not the best
-        // approach, but...
-        if text.is_some() {
+        // approach, but... directories don't have a text, their files are read instead
+        if text.is_some()
+            || feed::feedparser::local_path(&feed.feed_url).is_some_and(|p| p.is_dir())
+        {
             self.data.update_feed_entries(&feed.category, &feed, text)?;
         }
 
+        self.record(format!("Add feed {:?} to {}", feed.title, feed.category));
         Ok(feed)
     }
 
-    pub fn delete_feed(&self, slug: &str, category: &str) -> color_eyre::Result<()> {
-        self.data.delete_feed(slug, category)
+    /// Adds a list of feeds, skipping the ones already in the library. Unless `fetch` is set,
+    /// feeds are only registered and get their entries on the next update, so a big import
+    /// doesn't stall on dead URLs. With `dry_run` nothing is written.
+    pub fn import_feeds(
+        &mut self,
+        feeds: &[OpmlFeed],
+        fetch: bool,
+        dry_run: bool,
+    ) -> Vec<ImportResult> {
+        let mut subscribed: HashSet<String> = self
+            .feedcategories
+            .iter()
+            .flat_map(|c| c.feeds.iter())
+            .map(|f| normalize_feed_url(&f.feed_url))
+            .collect();
+        let mut slugs: HashSet<(String, String)> = HashSet::new();
+
+        let mut results = vec![];
+        for imported in feeds.iter() {
+            if !feed::feedparser::is_web_url(&imported.url) {
+                results.push(ImportResult::Failed(
+                    imported.url.clone(),
+                    String::from("Only http and https feeds can be imported"),
+                ));
+                continue;
+            }
+
+            if !subscribed.insert(normalize_feed_url(&imported.url)) {
+                results.push(ImportResult::AlreadySubscribed(imported.url.clone()));
+                continue;
+            }
+
+            let result =
+                self.import_feed(imported, fetch && !dry_run)
+                    .and_then(|(mut feed, text)| {
+                        // titles aren't unique, slugs in a category have to be
+                        let base_slug = feed.slug.clone();
+                        let mut n = 1;
+                        while self.data.feed_exists(&feed.slug, &feed.category)
+                            || slugs.contains(&(feed.category.clone(), feed.slug.clone()))
+                        {
+                            n += 1;
+                            feed.slug = format!("{base_slug}-{n}");
+                        }
+                        slugs.insert((feed.category.clone(), feed.slug.clone()));
+
+                        if dry_run {
+                            librarydata::validate_category_name(&feed.category)?;
+                            return Ok(feed);
+                        }
+
+                        let feed = self.add_feed(feed, text)?;
+                        if imported.folders.len() > 1 {
+                            self.data
+                                .set_category_folders(&feed.category, &imported.folders)?;
+                        }
+                        Ok(feed)
+                    });
+
+            results.push(match result {
+                Ok(feed) => ImportResult::Added(feed),
+                Err(e) => ImportResult::Failed(imported.url.clone(), e.to_string()),
+            });
+        }
+
+        if !dry_run && let Err(e) = self.reload_categories() {
+            error!("{e}");
+        }
+
+        results
+    }
+
+    fn import_feed(
+        &self,
+        imported: &OpmlFeed,
+        fetch: bool,
+    ) -> color_eyre::Result<(FeedItem, Option<String>)> {
+        let (mut feed, text) = if fetch {
+            feed::feedparser::get_feed_with_data(&imported.url)?
+        } else {
+            let feed = FeedItem {
+                feed_url: imported.url.clone(),
+                ..Default::default()
+            };
+            (feed, None)
+        };
+
+        // the names in the import are the ones the user picked, so they win over the feed's
+        if let Some(title) = imported.title.as_ref() {
+            feed.title = title.clone();
+        }
+        if let Some(url) = imported.html_url.as_ref() {
+            feed.url = url.clone();
+        }
+        if let Some(description) = imported.description.as_ref() {
+            feed.description = description.clone();
+        }
+
+        // Without a title from either, the site's host reads better than the whole URL
+        if feed.title.trim().is_empty() {
+            feed.title = imported
+                .html_url
+                .iter()
+                .chain([&imported.url])
+                .find_map(|url| url::Url::parse(url).ok()?.host_str().map(String::from))
+                .map(|host| host.trim_start_matches("www.").to_string())
+                .unwrap_or_else(|| imported.url.clone());
+        }
+
+        feed.slug = slugify(&feed.title);
+        feed.category = imported
+            .category
+            .clone()
+            .unwrap_or_else(|| String::from(defs::DATA_CATEGORY_DEFAULT));
+
+        Ok((feed, text))
+    }
+
+    /// Writes articles from another reader into the feeds they belong to, keeping whether they
+    /// were read and flagging them for Read Later. Articles of feeds that aren't in the library
+    /// are skipped. Returns how many entries were added.
+    pub fn import_entries(&mut self, items: &[CachedItem]) -> color_eyre::Result<usize> {
+        let feeds: HashMap<String, PathBuf> = self
+            .feedcategories
+            .iter()
+            .flat_map(|c| c.feeds.iter())
+            .map(|f| {
+                let feedir = self
+                    .data
+                    .path
+                    .join(defs::DATA_CATEGORIES_DIR)
+                    .join(&f.category)
+                    .join(&f.slug);
+                (normalize_feed_url(&f.feed_url), feedir)
+            })
+            .collect();
+
+        let mut imported = 0;
+        let mut read = vec![];
+        let mut later = vec![];
+
+        for item in items.iter() {
+            let Some(feedir) = feeds.get(&normalize_feed_url(&item.feed_url)) else {
+                continue;
+            };
+
+            let (description, text) =
+                feed::feedparser::description_and_content(Some(&item.content), None);
+            let entry = FeedEntry {
+                title: feed::feedutils::normalize_and_truncate(&item.title, 256),
+                description,
+                date: item.date,
+                url: item.url.clone(),
+                author: item.author.clone(),
+                text,
+                lastupdated: chrono::Utc::now(),
+                ..Default::default()
+            };
+
+            let Some((filepath, new)) = self.data.store_entry(feedir, entry)? else {
+                continue;
+            };
+
+            if new {
+                imported += 1;
+            }
+
+            let entry = FeedEntry {
+                filepath,
+                ..Default::default()
+            };
+            if !item.unread {
+                read.push(entry.clone());
+            }
+            if item.flagged {
+                later.push(entry);
+            }
+        }
+
+        self.data.set_entries_seen(&read, true);
+        for entry in later.iter() {
+            self.data.add_to_read_later(entry)?;
+        }
+
+        self.record(format!("Import {imported} entries"));
+        self.generation += 1;
+
+        Ok(imported)
+    }
+
+    pub fn delete_feed(&mut self, slug: &str, category: &str) -> color_eyre::Result<()> {
+        self.data.delete_feed(slug, category)?;
+        self.record(format!("Delete feed {category}/{slug}"));
+        Ok(())
+    }
+
+    pub fn move_feed(
+        &mut self,
+        slug: &str,
+        category: &str,
+        new_category: &str,
+    ) -> color_eyre::Result<()> {
+        self.data.move_feed(slug, category, new_category)?;
+        self.record(format!("Move feed {category}/{slug} to {new_category}"));
+        self.reload_categories()
+    }
+
+    pub fn rename_feed(
+        &mut self,
+        slug: &str,
+        category: &str,
+        new_title: &str,
+    ) -> color_eyre::Result<FeedItem> {
+        let feed = self.data.rename_feed(slug, category, new_title)?;
+        self.record(format!("Rename feed {category}/{slug} to {:?}", feed.title));
+        self.reload_categories()?;
+        Ok(feed)
+    }
+
+    /// Applies the changes from the edit dialog, returning the feed as it ends up in the library
+    pub fn edit_feed(
+        &mut self,
+        feed: &FeedItem,
+        title: &str,
+        feed_url: &str,
+        category: &str,
+    ) -> color_eyre::Result<FeedItem> {
+        let feed_url = feed_url.trim();
+        if feed_url.is_empty() {
+            return Err(eyre!("The feed URL can't be empty"));
+        }
+
+        // The feed is moved once, straight to its final place, so a clash with the new slug or
+        // category can't leave it renamed but not moved
+        let edited = self
+            .data
+            .edit_feed(&feed.slug, &feed.category, title, feed_url, category)?;
+
+        self.record(format!("Edit feed {:?}", title.trim()));
+        self.reload_categories()?;
+
+        self.get_feed(&edited.slug, &edited.category)
+            .cloned()
+            .ok_or_else(|| eyre!("Feed {:?} not found after editing", title))
+    }
+
+    pub fn get_feed(&self, slug: &str, category: &str) -> Option<&FeedItem> {
+        self.feedcategories
+            .iter()
+            .filter(|c| c.title == category)
+            .flat_map(|c| c.feeds.iter())
+            .find(|f| f.slug == slug)
+    }
+
+    pub fn rename_category(
+        &mut self,
+        category: &str,
+        new_category: &str,
+    ) -> color_eyre::Result<()> {
+        self.data.rename_category(category, new_category)?;
+        self.record(format!("Rename category {category} to {new_category}"));
+        self.reload_categories()
+    }
+
+    pub fn delete_category(&mut self, category: &str) -> color_eyre::Result<()> {
+        self.data.delete_category(category)?;
+        self.record(format!("Delete category {category}"));
+        self.reload_categories()
+    }
+
+    pub fn reload_categories(&mut self) -> color_eyre::Result<()> {
+        self.feedcategories = self.data.generate_categories_tree()?;
+        self.generation += 1;
+        Ok(())
     }
 
     pub fn get_feed_entries_by_category(
@@ -122,7 +435,7 @@ impl FeedLibrary {
             }
         }
 
-        entries.sort_by(|a, b| b.date.cmp(&a.date));
+        entries.sort_by_key(|e| std::cmp::Reverse(e.date));
         Ok(entries)
     }
 
@@ -132,7 +445,7 @@ impl FeedLibrary {
                 if feed.slug == slug {
                     let mut entries = self.data.load_feed_entries(category, feed)?;
 
-                    entries.sort_by(|a, b| b.date.cmp(&a.date));
+                    entries.sort_by_key(|e| std::cmp::Reverse(e.date));
                     return Ok(entries);
                 }
             }
@@ -141,8 +454,35 @@ impl FeedLibrary {
         Ok(vec![])
     }
 
+    /// Applies the retention policy to every feed in the library
+    pub fn prune(&mut self, dry_run: bool) -> color_eyre::Result<Vec<FeedEntry>> {
+        let mut pruned = vec![];
+
+        for category in self.feedcategories.iter() {
+            for feed in category.feeds.iter() {
+                pruned.extend(self.prune_feed(feed, dry_run)?);
+            }
+        }
+
+        if !dry_run && !pruned.is_empty() {
+            self.record(format!("Prune {} entries", pruned.len()));
+            self.generation += 1;
+        }
+
+        Ok(pruned)
+    }
+
+    pub fn prune_feed(&self, feed: &FeedItem, dry_run: bool) -> color_eyre::Result<Vec<FeedEntry>> {
+        let policy = self.settings.retention.merged(feed.retention.as_ref());
+        self.data.prune_feed_entries(feed, &policy, dry_run)
+    }
+
     pub fn start_updater(&mut self) {
-        self.updater = Some(Updater::new(self.feedcategories.clone(), &self.data.path));
+        self.updater = Some(Updater::new(
+            self.feedcategories.clone(),
+            &self.data.path,
+            self.hooks.clone(),
+        ));
     }
 
     pub fn bump_generation(&mut self) {
@@ -150,15 +490,112 @@ impl FeedLibrary {
     }
 
     pub fn set_entry_seen(&mut self, entry: &FeedEntry) {
+        if !entry.seen {
+            self.record(format!("Mark {:?} as read", entry.title));
+            self.run_entry_read_hooks(std::slice::from_ref(entry));
+        }
         self.data.set_entry_seen(entry);
         self.generation += 1;
     }
 
     pub fn toggle_entry_seen(&mut self, entry: &FeedEntry) {
+        let state = if entry.seen { "unread" } else { "read" };
+        self.record(format!("Mark {:?} as {state}", entry.title));
+        if !entry.seen {
+            self.run_entry_read_hooks(std::slice::from_ref(entry));
+        }
         self.data.toggle_entry_seen(entry);
         self.generation += 1;
     }
 
+    pub fn set_entries_seen(&mut self, entries: &[FeedEntry]) {
+        let unseen: Vec<FeedEntry> = entries.iter().filter(|e| !e.seen).cloned().collect();
+        if unseen.is_empty() {
+            return;
+        }
+
+        self.record(format!("Mark {} entries as read", unseen.len()));
+        self.run_entry_read_hooks(&unseen);
+        self.data.set_entries_seen(&unseen, true);
+        self.generation += 1;
+    }
+
+    /// The category and feed an entry belongs to, found from where its file is
+    pub fn entry_feed(&self, entry: &FeedEntry) -> Option<(String, &FeedItem)> {
+        let feedir = entry.filepath.parent()?;
+        let slug = feedir.file_name()?.to_string_lossy();
+        let category = feedir.parent()?.file_name()?.to_string_lossy();
+
+        let feed = self
+            .feedcategories
+            .iter()
+            .filter(|c| c.title == category)
+            .flat_map(|c| c.feeds.iter())
+            .find(|f| f.slug == slug)?;
+
+        Some((category.to_string(), feed))
+    }
+
+    /// Runs `on_entry_read` in the background, so marking entries never waits on the commands
+    fn run_entry_read_hooks(&mut self, entries: &[FeedEntry]) {
+        if !self.hooks.has_entry_read() {
+            return;
+        }
+
+        let read: Vec<(String, FeedItem, FeedEntry)> = entries
+            .iter()
+            .filter_map(|entry| {
+                let (category, feed) = self.entry_feed(entry)?;
+                Some((category, feed.clone(), entry.clone()))
+            })
+            .collect();
+
+        let hooks = self.hooks.clone();
+        self.hook_threads.retain(|t| !t.is_finished());
+        self.hook_threads.push(std::thread::spawn(move || {
+            for (category, feed, entry) in read.iter() {
+                hooks.run_entry_read(category, feed, entry);
+            }
+        }));
+    }
+
+    /// Waits for the hooks still running in the background, before bulletty exits
+    pub fn wait_for_hooks(&mut self) {
+        for thread in self.hook_threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+
+    pub fn set_entries_unseen(&mut self, entries: &[FeedEntry]) {
+        let seen: Vec<FeedEntry> = entries.iter().filter(|e| e.seen).cloned().collect();
+        if seen.is_empty() {
+            return;
+        }
+
+        self.record(format!("Mark {} entries as unread", seen.len()));
+        self.data.set_entries_seen(&seen, false);
+        self.generation += 1;
+    }
+
+    /// Remembers a change to describe it in the next commit, when the library is versioned
+    pub fn record(&mut self, change: String) {
+        if self.repo.is_some() {
+            self.changes.push(change);
+        }
+    }
+
+    /// Commits the changes made since the last commit, when the library is versioned
+    pub fn commit_changes(&mut self) {
+        let Some(repo) = self.repo.as_ref() else {
+            return;
+        };
+
+        if let Err(e) = repo.commit(&self.changes) {
+            error!("Couldn't commit library changes: {:?}", e);
+        }
+        self.changes.clear();
+    }
+
     pub fn update(&mut self) {
         if let Some(updater) = self.updater.as_ref() {
             let completed = updater
@@ -173,6 +610,9 @@ impl FeedLibrary {
                 self.updater = None;
                 self.last_updater_completed = 0;
                 self.generation += 1;
+
+                self.record(String::from("Update feeds"));
+                self.commit_changes();
             }
         }
     }
@@ -225,12 +665,14 @@ impl FeedLibrary {
 
     pub fn add_to_read_later(&mut self, entry: &FeedEntry) -> color_eyre::Result<()> {
         self.data.add_to_read_later(entry)?;
+        self.record(format!("Add {:?} to Read Later", entry.title));
         self.generation += 1;
         Ok(())
     }
 
     pub fn remove_from_read_later(&mut self, file_path: &str) -> color_eyre::Result<()> {
         self.data.remove_from_read_later(file_path)?;
+        self.record(String::from("Remove an entry from Read Later"));
         self.generation += 1;
         Ok(())
     }
@@ -257,6 +699,10 @@ impl FeedLibrary {
     }
 }
 
+fn normalize_feed_url(url: &str) -> String {
+    url.trim().trim_end_matches('/').to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use crate::core::library::feedlibrary::FeedLibrary;
@@ -287,6 +733,252 @@ mod tests {
         assert!(!library.data.feed_exists("my-test-feed", "testing"));
     }
 
+    #[test]
+    fn test_edit_feed() {
+        let (mut library, _temp_dir) = FeedLibrary::new_for_test();
+
+        let feed = crate::core::library::feeditem::FeedItem {
+            title: "My Test Feed".to_string(),
+            slug: "my-test-feed".to_string(),
+            feed_url: "https://mytestfeed/rss".to_string(),
+            category: "testing".to_string(),
+            ..Default::default()
+        };
+
+        assert!(library.add_feed(feed.clone(), None).is_ok());
+        library.reload_categories().unwrap();
+
+        assert!(library.edit_feed(&feed, "Renamed", " ", "testing").is_err());
+
+        let edited = library
+            .edit_feed(&feed, "Renamed Feed", "https://mytestfeed/atom", "other")
+            .unwrap();
+
+        assert_eq!(edited.title, "Renamed Feed");
+        assert_eq!(edited.slug, "renamed-feed");
+        assert_eq!(edited.feed_url, "https://mytestfeed/atom");
+        assert_eq!(edited.category, "other");
+        assert!(!library.data.feed_exists("my-test-feed", "testing"));
+        assert!(library.data.feed_exists("renamed-feed", "other"));
+
+        let clash = crate::core::library::feeditem::FeedItem {
+            title: "Clash".to_string(),
+            slug: "clash".to_string(),
+            feed_url: "https://clash/rss".to_string(),
+            category: "testing".to_string(),
+            ..Default::default()
+        };
+        assert!(library.add_feed(clash.clone(), None).is_ok());
+        library.reload_categories().unwrap();
+
+        // The rename is free in "testing", but the move clashes with the edited feed in "other"
+        assert!(
+            library
+                .edit_feed(&clash, "Renamed Feed", &clash.feed_url, "other")
+                .is_err()
+        );
+        assert!(library.data.feed_exists("clash", "testing"));
+        assert!(!library.data.feed_exists("renamed-feed", "testing"));
+        library.reload_categories().unwrap();
+        assert_eq!(library.get_feed("clash", "testing").unwrap().title, "Clash");
+    }
+
+    #[test]
+    fn test_import_feeds_dedupes_and_registers_without_fetching() {
+        use crate::core::library::data::opml::OpmlFeed;
+        use crate::core::library::feedlibrary::ImportResult;
+
+        let (mut library, _temp_dir) = FeedLibrary::new_for_test();
+
+        let existing = crate::core::library::feeditem::FeedItem {
+            title: "Existing".to_string(),
+            slug: "existing".to_string(),
+            feed_url: "https://existing.example/feed".to_string(),
+            category: "General".to_string(),
+            ..Default::default()
+        };
+        library.add_feed(existing, None).unwrap();
+        library.reload_categories().unwrap();
+
+        let feed = |url: &str, title: &str| OpmlFeed {
+            url: url.to_string(),
+            title: Some(title.to_string()),
+            category: Some("Blogs".to_string()),
+            ..Default::default()
+        };
+        let feeds = vec![
+            feed("https://existing.example/feed/", "Existing"),
+            feed("https://a.example/feed", "Same Name"),
+            feed("https://b.example/feed", "Same Name"),
+            feed("https://a.example/feed", "Duplicate"),
+        ];
+
+        let results = library.import_feeds(&feeds, false, true);
+        assert!(matches!(results[0], ImportResult::AlreadySubscribed(_)));
+        assert!(matches!(results[3], ImportResult::AlreadySubscribed(_)));
+        assert!(!library.data.feed_exists("same-name", "Blogs"));
+
+        let results = library.import_feeds(&feeds, false, false);
+        let added: Vec<String> = results
+            .iter()
+            .filter_map(|r| match r {
+                ImportResult::Added(feed) => Some(feed.slug.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(added, vec!["same-name", "same-name-2"]);
+
+        let feed = library.get_feed("same-name-2", "Blogs").unwrap();
+        assert_eq!(feed.title, "Same Name");
+        assert_eq!(feed.feed_url, "https://b.example/feed");
+
+        let results = library.import_feeds(&feeds, false, false);
+        assert!(
+            results
+                .iter()
+                .all(|r| matches!(r, ImportResult::AlreadySubscribed(_)))
+        );
+    }
+
+    #[test]
+    fn test_import_feeds_keeps_nested_folders() {
+        use crate::core::library::data::opml::{get_opml_feeds, parse_opml, save_opml};
+
+        let (mut library, temp_dir) = FeedLibrary::new_for_test();
+        let feeds = parse_opml(
+            r#"<opml version="2.0"><body>
+                <outline text="Tech"><outline text="Rust">
+                    <outline xmlUrl="https://www.rust.example/feed"/>
+                    <outline xmlUrl="https://lang.example/feed" htmlUrl="https://blog.lang.example/"/>
+                </outline></outline>
+                <outline text="News - World">
+                    <outline text="World" xmlUrl="https://world.example/feed"/>
+                </outline>
+            </body></opml>"#,
+        )
+        .unwrap();
+        library.import_feeds(&feeds, false, false);
+
+        let rust = library.get_feed("rust-example", "Tech - Rust").unwrap();
+        assert_eq!(rust.title, "rust.example");
+        assert!(
+            library
+                .get_feed("blog-lang-example", "Tech - Rust")
+                .is_some()
+        );
+
+        let opml = temp_dir.path().join("export.opml");
+        save_opml(&library.feedcategories, opml.to_str().unwrap()).unwrap();
+        let mut exported: Vec<(String, Vec<String>)> = get_opml_feeds(opml.to_str().unwrap())
+            .unwrap()
+            .into_iter()
+            .map(|f| (f.url, f.folders))
+            .collect();
+        exported.sort();
+        assert_eq!(
+            exported,
+            vec![
+                (
+                    String::from("https://lang.example/feed"),
+                    vec![String::from("Tech"), String::from("Rust")]
+                ),
+                (
+                    String::from("https://world.example/feed"),
+                    vec![String::from("News - World")]
+                ),
+                (
+                    String::from("https://www.rust.example/feed"),
+                    vec![String::from("Tech"), String::from("Rust")]
+                ),
+            ]
+        );
+
+        // Once renamed, the category is the user's own and exports as a single folder
+        library
+            .rename_category("Tech - Rust", "Rust - Lang")
+            .unwrap();
+        assert!(library.feedcategories.iter().all(|c| c.folders.is_empty()));
+    }
+
+    #[test]
+    fn test_import_feeds_rejects_local_sources() {
+        use crate::core::library::data::opml::parse_opml;
+        use crate::core::library::feedlibrary::ImportResult;
+
+        let (mut library, temp_dir) = FeedLibrary::new_for_test();
+        let marker = temp_dir.path().join("ran");
+        let opml = format!(
+            r#"<opml version="2.0"><body>
+                <outline text="Evil" xmlUrl="exec:touch {}"/>
+                <outline text="Passwords" xmlUrl="file:///etc/passwd"/>
+                <outline text="Home" xmlUrl="~/feed.xml"/>
+            </body></opml>"#,
+            marker.display()
+        );
+        let feeds = parse_opml(&opml).unwrap();
+
+        for fetch in [true, false] {
+            let results = library.import_feeds(&feeds, fetch, false);
+            assert_eq!(results.len(), 3);
+            assert!(
+                results
+                    .iter()
+                    .all(|r| matches!(r, ImportResult::Failed(_, _)))
+            );
+        }
+
+        assert!(!marker.exists());
+        assert!(library.feedcategories.iter().all(|c| c.feeds.is_empty()));
+    }
+
+    #[test]
+    fn test_import_entries_keeps_read_and_flagged_state() {
+        use crate::core::library::data::newsboat::CachedItem;
+
+        let (mut library, _temp_dir) = FeedLibrary::new_for_test();
+
+        let feed = crate::core::library::feeditem::FeedItem {
+            title: "Blog".to_string(),
+            slug: "blog".to_string(),
+            feed_url: "https://blog.example/rss".to_string(),
+            category: "General".to_string(),
+            ..Default::default()
+        };
+        library.add_feed(feed, None).unwrap();
+        library.reload_categories().unwrap();
+
+        let item = |title: &str, unread: bool, flagged: bool| CachedItem {
+            feed_url: "https://blog.example/rss".to_string(),
+            title: title.to_string(),
+            content: "<p>Some <em>text</em></p>".to_string(),
+            unread,
+            flagged,
+            ..Default::default()
+        };
+        let items = vec![
+            item("Read", false, false),
+            item("Unread", true, true),
+            CachedItem {
+                feed_url: "https://unknown.example/rss".to_string(),
+                ..item("Elsewhere", true, false)
+            },
+        ];
+
+        assert_eq!(library.import_entries(&items).unwrap(), 2);
+        assert_eq!(library.import_entries(&items).unwrap(), 0);
+
+        let mut entries = library.get_feed_entries_by_item_slug("blog").unwrap();
+        entries.sort_by_key(|e| e.title.clone());
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].seen);
+        assert!(!entries[1].seen);
+        assert_eq!(entries[1].text, "Some *text*");
+
+        let later = library.get_read_later_feed_entries().unwrap();
+        assert_eq!(later.len(), 1);
+        assert_eq!(later[0].title, "Unread");
+    }
+
     fn setup_test_library_for_matches() -> FeedLibrary {
         let (mut library, _temp_dir) = FeedLibrary::new_for_test();
 
@@ -0,0 +1,198 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        mpsc::{self, Receiver, Sender},
+    },
+    thread,
+};
+
+use color_eyre::eyre::eyre;
+use image::DynamicImage;
+use reqwest::blocking::Client;
+use tracing::{error, info};
+
+use crate::core::feed::feedparser;
+
+/// Where downloaded images are kept, inside the library directory
+pub const IMAGE_CACHE_DIR: &str = ".cache/images";
+
+/// Bigger images are most likely not figures, and would take long to decode
+const MAX_IMAGE_BYTES: u64 = 20 * 1024 * 1024;
+
+pub enum CachedImage {
+    Loading,
+    Ready(Arc<DynamicImage>),
+    Failed,
+}
+
+/// Article images, downloaded once into the library's `.cache/images` and decoded in the
+/// background so the reader never waits for them
+pub struct ImageCache {
+    dir: PathBuf,
+    images: HashMap<String, CachedImage>,
+    sender: Sender<(String, Option<DynamicImage>)>,
+    receiver: Receiver<(String, Option<DynamicImage>)>,
+}
+
+impl ImageCache {
+    pub fn new(datapath: &Path) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        Self {
+            dir: datapath.join(IMAGE_CACHE_DIR),
+            images: HashMap::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    /// The image at `url`, starting to load it the first time it's asked for
+    pub fn get(&mut self, url: &str) -> &CachedImage {
+        while let Ok((url, image)) = self.receiver.try_recv() {
+            let image = match image {
+                Some(image) => CachedImage::Ready(Arc::new(image)),
+                None => CachedImage::Failed,
+            };
+            self.images.insert(url, image);
+        }
+
+        if !self.images.contains_key(url) {
+            self.load(url);
+        }
+
+        &self.images[url]
+    }
+
+    fn load(&mut self, url: &str) {
+        self.images.insert(url.to_string(), CachedImage::Loading);
+
+        let url = url.to_string();
+        let path = self.dir.join(cache_name(&url));
+        let sender = self.sender.clone();
+
+        thread::spawn(move || {
+            let image = match load_image(&url, &path) {
+                Ok(image) => Some(image),
+                Err(e) => {
+                    error!("Couldn't load image {url}: {e}");
+                    None
+                }
+            };
+            // the reader may be gone by now, and then nobody wants the image anymore
+            let _ = sender.send((url, image));
+        });
+    }
+}
+
+/// File name of a cached image: a hash of its URL, as URLs can be longer than a file name. It's
+/// FNV-1a, so the name stays the same across Rust versions and the cache survives upgrades.
+fn cache_name(url: &str) -> String {
+    let hash = url.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    });
+    format!("{hash:016x}")
+}
+
+fn load_image(url: &str, path: &Path) -> color_eyre::Result<DynamicImage> {
+    let bytes = if path.exists() {
+        fs::read(path)?
+    } else {
+        let bytes = download(url)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, &bytes)?;
+        info!("Cached image {url}");
+        bytes
+    };
+
+    Ok(image::load_from_memory(&bytes)?)
+}
+
+/// Downloads an image, reading no more than `MAX_IMAGE_BYTES` of it. Images come from the
+/// articles, so anything but http(s) is refused: a `file://` URL would read local files.
+fn download(url: &str) -> color_eyre::Result<Vec<u8>> {
+    if !feedparser::is_web_url(url) {
+        return Err(eyre!("Only http and https images are loaded"));
+    }
+
+    let client = Client::builder()
+        .user_agent(format!("bulletty/{}", env!("CARGO_PKG_VERSION")))
+        .build()?;
+
+    let response = client.get(url).send()?;
+
+    if !response.status().is_success() {
+        return Err(eyre!(
+            "Request to \"{}\" returned status code {:?}",
+            url,
+            response.status()
+        ));
+    }
+
+    if let Some(length) = response.content_length()
+        && length > MAX_IMAGE_BYTES
+    {
+        return Err(eyre!("Image is too big ({} bytes)", length));
+    }
+
+    // the length can be missing or wrong, so the body is capped as well
+    let mut bytes = vec![];
+    response.take(MAX_IMAGE_BYTES + 1).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > MAX_IMAGE_BYTES {
+        return Err(eyre!("Image is too big (over {} bytes)", MAX_IMAGE_BYTES));
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use image::{ImageFormat, RgbImage};
+
+    use super::*;
+
+    fn wait(cache: &mut ImageCache, url: &str) {
+        let started = Instant::now();
+        while matches!(cache.get(url), CachedImage::Loading) {
+            assert!(started.elapsed() < Duration::from_secs(10));
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_loads_cached_images_and_refuses_local_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let url = "https://example.com/figure.png";
+        let cached = temp.path().join(IMAGE_CACHE_DIR).join(cache_name(url));
+        fs::create_dir_all(cached.parent().unwrap()).unwrap();
+        RgbImage::new(4, 2)
+            .save_with_format(&cached, ImageFormat::Png)
+            .unwrap();
+
+        let mut cache = ImageCache::new(temp.path());
+        assert!(matches!(cache.get(url), CachedImage::Loading));
+        wait(&mut cache, url);
+        match cache.get(url) {
+            CachedImage::Ready(image) => assert_eq!((image.width(), image.height()), (4, 2)),
+            _ => panic!("image should have loaded"),
+        }
+
+        // a local file is never read, even when it's a valid image
+        let local = format!("file://{}", cached.display());
+        wait(&mut cache, &local);
+        assert!(matches!(cache.get(&local), CachedImage::Failed));
+    }
+
+    #[test]
+    fn test_cache_name_is_stable() {
+        assert_eq!(cache_name(""), "cbf29ce484222325");
+        assert_eq!(cache_name("a"), "af63dc4c8601ec8c");
+    }
+}
@@ -2,5 +2,7 @@ pub mod data;
 pub mod feedcategory;
 pub mod feeditem;
 pub mod feedlibrary;
+pub mod imagecache;
+pub mod readersync;
 pub mod settings;
 pub mod updater;
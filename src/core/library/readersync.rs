@@ -0,0 +1,471 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::eyre;
+use serde::{Deserialize, Serialize};
+use slug::slugify;
+use tracing::{error, info};
+
+use crate::core::{
+    defs::{DATA_CATEGORIES_DIR, DATA_CATEGORY_DEFAULT, DATA_GREADER},
+    feed::{
+        feedentry::FeedEntry,
+        feedparser,
+        greader::{GReaderClient, ReaderItem, TAG_READ, TAG_STARRED},
+    },
+    library::{feeditem::FeedItem, feedlibrary::FeedLibrary},
+};
+
+/// The state of an item as of the last sync, so changes can be told apart on each side
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SyncedItem {
+    path: String,
+    read: bool,
+    starred: bool,
+}
+
+/// Kept in the library's `.greader.toml`, keyed by the item ids of the server
+#[derive(Default, Debug, Serialize, Deserialize)]
+struct ReaderSyncState {
+    #[serde(default)]
+    items: BTreeMap<String, SyncedItem>,
+}
+
+impl ReaderSyncState {
+    fn load(datapath: &Path) -> color_eyre::Result<Self> {
+        let path = datapath.join(DATA_GREADER);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        toml::from_str(&fs::read_to_string(&path)?)
+            .map_err(|e| eyre!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    fn save(&self, datapath: &Path) -> color_eyre::Result<()> {
+        let path = datapath.join(DATA_GREADER);
+        let temp_path = datapath.join(format!("{DATA_GREADER}.tmp"));
+        let toml_str =
+            toml::to_string(self).map_err(|e| eyre!("Failed to serialize sync state: {}", e))?;
+
+        fs::write(&temp_path, toml_str)
+            .map_err(|e| eyre!("Failed to write file {}: {}", temp_path.display(), e))?;
+        fs::rename(&temp_path, &path)
+            .map_err(|e| eyre!("Failed to replace file {}: {}", path.display(), e))
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct ReaderSyncStats {
+    pub new_feeds: usize,
+    pub new_entries: usize,
+    /// Read and starred changes taken from the server
+    pub pulled: usize,
+    /// Read and Read Later changes sent to the server
+    pub pushed: usize,
+}
+
+/// Tag changes to send back to the server, batched per tag
+#[derive(Default)]
+struct TagChanges {
+    read: Vec<String>,
+    unread: Vec<String>,
+    starred: Vec<String>,
+    unstarred: Vec<String>,
+}
+
+impl TagChanges {
+    fn len(&self) -> usize {
+        self.read.len() + self.unread.len() + self.starred.len() + self.unstarred.len()
+    }
+
+    fn push(&self, client: &mut GReaderClient) -> color_eyre::Result<()> {
+        client.edit_tag(&self.read, TAG_READ, true)?;
+        client.edit_tag(&self.unread, TAG_READ, false)?;
+        client.edit_tag(&self.starred, TAG_STARRED, true)?;
+        client.edit_tag(&self.unstarred, TAG_STARRED, false)
+    }
+}
+
+/// Pulls the subscriptions and latest entries of a Google Reader API server into the library
+/// and reconciles the read and Read Later (starred) state in both directions. Whichever side
+/// changed since the last sync wins; items seen for the first time are read or starred if
+/// either side says so.
+pub fn sync_reader(
+    library: &mut FeedLibrary,
+    client: &mut GReaderClient,
+    max_entries: usize,
+) -> color_eyre::Result<ReaderSyncStats> {
+    let mut stats = ReaderSyncStats::default();
+    let datapath = library.data.path.clone();
+
+    let mut feeds: HashMap<String, PathBuf> = HashMap::new();
+    for sub in client.subscriptions()? {
+        let feed = match find_feed(library, &sub.feed_url) {
+            Some(feed) => feed,
+            // the server could otherwise plant `exec:` or local sources in the library
+            None if !feedparser::is_web_url(&sub.feed_url) => {
+                error!(
+                    "Skipping subscription {:?}: {:?} isn't an http(s) feed",
+                    sub.title, sub.feed_url
+                );
+                continue;
+            }
+            None => {
+                let feed = FeedItem {
+                    slug: slugify(&sub.title),
+                    title: sub.title.clone(),
+                    url: sub.url.clone(),
+                    feed_url: sub.feed_url.clone(),
+                    category: sub
+                        .category
+                        .clone()
+                        .unwrap_or_else(|| String::from(DATA_CATEGORY_DEFAULT)),
+                    ..Default::default()
+                };
+
+                match library.add_feed(feed, None) {
+                    Ok(feed) => {
+                        stats.new_feeds += 1;
+                        feed
+                    }
+                    Err(e) => {
+                        error!("Couldn't add subscription {:?}: {}", sub.title, e);
+                        continue;
+                    }
+                }
+            }
+        };
+
+        let feedir = datapath
+            .join(DATA_CATEGORIES_DIR)
+            .join(&feed.category)
+            .join(&feed.slug);
+        feeds.insert(sub.id, feedir);
+    }
+
+    let mut state = ReaderSyncState::load(&datapath)?;
+    let mut changes = TagChanges::default();
+    let mut to_read: Vec<FeedEntry> = vec![];
+    let mut to_unread: Vec<FeedEntry> = vec![];
+    let mut to_later: Vec<FeedEntry> = vec![];
+    let mut from_later: Vec<String> = vec![];
+
+    for item in client.items(max_entries)? {
+        let Some(feedir) = feeds.get(&item.feed_id) else {
+            continue;
+        };
+
+        let path = match state.items.get(&item.id) {
+            Some(synced) => datapath.join(DATA_CATEGORIES_DIR).join(&synced.path),
+            None => match library.data.store_entry(feedir, item_entry(&item))? {
+                Some((path, new)) => {
+                    if new {
+                        stats.new_entries += 1;
+                    }
+                    path
+                }
+                None => continue,
+            },
+        };
+
+        let entry = match library.data.load_entry(&path) {
+            Ok(entry) => entry,
+            Err(_) => {
+                // the entry is gone locally, most likely pruned
+                state.items.remove(&item.id);
+                continue;
+            }
+        };
+
+        let path_str = entry.filepath.to_string_lossy().to_string();
+        let later = library.is_in_read_later(&path_str);
+        let synced = state.items.get(&item.id);
+
+        let read = reconcile(synced.map(|s| s.read), entry.seen, item.read);
+        let starred = reconcile(synced.map(|s| s.starred), later, item.starred);
+
+        match (read == item.read, read) {
+            (true, _) => {}
+            (false, true) => changes.read.push(item.id.clone()),
+            (false, false) => changes.unread.push(item.id.clone()),
+        }
+        match (starred == item.starred, starred) {
+            (true, _) => {}
+            (false, true) => changes.starred.push(item.id.clone()),
+            (false, false) => changes.unstarred.push(item.id.clone()),
+        }
+
+        if read != entry.seen {
+            if read {
+                to_read.push(entry.clone());
+            } else {
+                to_unread.push(entry.clone());
+            }
+        }
+        if starred != later {
+            if starred {
+                to_later.push(entry.clone());
+            } else {
+                from_later.push(path_str);
+            }
+        }
+
+        let rel_path = entry
+            .filepath
+            .strip_prefix(datapath.join(DATA_CATEGORIES_DIR))
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        state.items.insert(
+            item.id.clone(),
+            SyncedItem {
+                path: rel_path,
+                read,
+                starred,
+            },
+        );
+    }
+
+    changes.push(client)?;
+    stats.pushed = changes.len();
+    stats.pulled = to_read.len() + to_unread.len() + to_later.len() + from_later.len();
+
+    library.data.set_entries_seen(&to_read, true);
+    library.data.set_entries_seen(&to_unread, false);
+    for entry in to_later.iter() {
+        library.data.add_to_read_later(entry)?;
+    }
+    for path in from_later.iter() {
+        library.data.remove_from_read_later(path)?;
+    }
+
+    // forget items whose entries don't exist anymore
+    state
+        .items
+        .retain(|_, s| datapath.join(DATA_CATEGORIES_DIR).join(&s.path).exists());
+    state.save(&datapath)?;
+
+    library.reload_categories()?;
+    library.record(format!(
+        "Sync with reader: {} new entries, {} changes pulled, {} pushed",
+        stats.new_entries, stats.pulled, stats.pushed
+    ));
+
+    info!("Reader sync finished: {:?}", stats);
+    Ok(stats)
+}
+
+fn find_feed(library: &FeedLibrary, feed_url: &str) -> Option<FeedItem> {
+    let feed_url = feed_url.trim_end_matches('/');
+
+    library
+        .feedcategories
+        .iter()
+        .flat_map(|c| c.feeds.iter())
+        .find(|f| f.feed_url.trim_end_matches('/') == feed_url)
+        .cloned()
+}
+
+/// The state to settle on for a flag, given its value at the last sync and on each side now
+fn reconcile(synced: Option<bool>, local: bool, remote: bool) -> bool {
+    match synced {
+        Some(synced) if local != synced => local,
+        Some(_) => remote,
+        None => local || remote,
+    }
+}
+
+fn item_entry(item: &ReaderItem) -> FeedEntry {
+    FeedEntry {
+        title: item.title.clone(),
+        description: item.description.clone(),
+        date: item.date,
+        url: item.url.clone(),
+        author: item.author.clone(),
+        text: item.text.clone(),
+        lastupdated: chrono::Utc::now(),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    use super::*;
+    use crate::core::feed::greader::GReaderConfig;
+
+    const SUBSCRIPTIONS: &str = r#"{"subscriptions": [{
+        "id": "feed/1",
+        "title": "Example Blog",
+        "url": "http://example.com/feed.xml",
+        "htmlUrl": "http://example.com",
+        "categories": [{"id": "user/-/label/Tech", "label": "Tech"}]
+    }, {
+        "id": "feed/2",
+        "title": "Planted",
+        "url": "exec:false",
+        "htmlUrl": "http://example.com"
+    }]}"#;
+
+    const ITEMS: &str = r#"{"items": [{
+        "id": "item/a",
+        "title": "First post",
+        "published": 1700000000,
+        "author": "Jane",
+        "canonical": [{"href": "http://example.com/a"}],
+        "content": {"content": "<p>Hello</p>"},
+        "categories": ["user/-/state/com.google/read"],
+        "origin": {"streamId": "feed/1"}
+    }, {
+        "id": "item/b",
+        "title": "Second post",
+        "published": 1700100000,
+        "alternate": [{"href": "http://example.com/b"}],
+        "summary": {"content": "World"},
+        "categories": ["user/-/state/com.google/starred"],
+        "origin": {"streamId": "feed/1"}
+    }]}"#;
+
+    /// Serves canned responses like a Google Reader API server would, recording each request
+    fn mock_server() -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+
+        let log = Arc::clone(&requests);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+
+                let mut length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some(value) = header.to_lowercase().strip_prefix("content-length:") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+
+                let target = request_line
+                    .split(' ')
+                    .nth(1)
+                    .unwrap_or_default()
+                    .to_string();
+                let response = match target.split('?').next().unwrap_or_default() {
+                    "/accounts/ClientLogin" => "SID=x\nAuth=secret\n",
+                    "/reader/api/0/subscription/list" => SUBSCRIPTIONS,
+                    p if p.starts_with("/reader/api/0/stream/contents/") => ITEMS,
+                    "/reader/api/0/token" => "token123",
+                    "/reader/api/0/edit-tag" => "OK",
+                    _ => "",
+                };
+
+                log.lock()
+                    .unwrap()
+                    .push(format!("{target} {}", String::from_utf8_lossy(&body)));
+
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    response.len(),
+                    response
+                )
+                .unwrap();
+            }
+        });
+
+        (url, requests)
+    }
+
+    #[test]
+    fn test_sync_reader_pulls_and_pushes_state() {
+        let (url, requests) = mock_server();
+        let (mut library, _dir) = FeedLibrary::new_for_test();
+
+        let config = GReaderConfig {
+            url,
+            username: String::from("jane"),
+            password: Some(String::from("hunter2")),
+            max_entries: 10,
+            ..Default::default()
+        };
+        let mut client = GReaderClient::login(&config).unwrap();
+
+        let stats = sync_reader(&mut library, &mut client, config.max_entries).unwrap();
+        assert_eq!(
+            stats,
+            ReaderSyncStats {
+                new_feeds: 1,
+                new_entries: 2,
+                pulled: 2,
+                pushed: 0,
+            }
+        );
+
+        let feed = library.get_feed("example-blog", "Tech").unwrap().clone();
+        assert_eq!(feed.feed_url, "http://example.com/feed.xml");
+        assert!(!library.data.feed_exists("planted", DATA_CATEGORY_DEFAULT));
+
+        let mut entries = library
+            .get_feed_entries_by_item_slug("example-blog")
+            .unwrap();
+        entries.sort_by_key(|e| e.date);
+        assert_eq!(entries[0].title, "First post");
+        assert_eq!(entries[0].url, "http://example.com/a");
+        assert!(entries[0].seen);
+        assert!(!entries[1].seen);
+        assert!(library.is_in_read_later(entries[1].filepath.to_str().unwrap()));
+        assert!(!library.is_in_read_later(entries[0].filepath.to_str().unwrap()));
+
+        // reading the second post locally gets pushed, while the server's state is kept
+        library.set_entry_seen(&entries[1]);
+        let stats = sync_reader(&mut library, &mut client, config.max_entries).unwrap();
+        assert_eq!(
+            stats,
+            ReaderSyncStats {
+                pushed: 1,
+                ..Default::default()
+            }
+        );
+
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].contains("Email=jane&Passwd=hunter2"));
+        assert!(
+            requests
+                .iter()
+                .any(|r| r.starts_with("/reader/api/0/edit-tag")
+                    && r.contains("i=item%2Fb")
+                    && r.contains("a=user%2F-%2Fstate%2Fcom.google%2Fread")
+                    && r.contains("T=token123"))
+        );
+    }
+
+    #[test]
+    fn test_reconcile() {
+        assert!(reconcile(None, false, true));
+        assert!(reconcile(None, true, false));
+        assert!(!reconcile(Some(true), false, true));
+        assert!(reconcile(Some(false), false, true));
+        assert!(!reconcile(Some(true), true, false));
+    }
+}
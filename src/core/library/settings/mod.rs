@@ -1,4 +1,6 @@
 pub mod appearance;
+pub mod retention;
+pub mod session;
 pub mod theme;
 pub mod themedata;
 pub mod usersettings;
@@ -0,0 +1,69 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+use tracing::error;
+
+const RETENTION_PATH: &str = ".retention.toml";
+
+/// How long entries are kept around. The global policy lives in the library's `.retention.toml`
+/// and each feed can override any of its fields under `[retention]` in its `.feed.toml`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct RetentionPolicy {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_days: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_entries: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_unread: Option<bool>,
+}
+
+impl RetentionPolicy {
+    pub fn new(datapath: &Path) -> color_eyre::Result<Self> {
+        let path = datapath.join(RETENTION_PATH);
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let data = fs::read_to_string(&path)?;
+        match toml::from_str(&data) {
+            Ok(policy) => Ok(policy),
+            Err(e) => {
+                error!("Error parsing {path:?}: {e:?}");
+                Ok(Self::default())
+            }
+        }
+    }
+
+    /// The policy for a single feed: the feed's own fields take precedence over the global ones
+    pub fn merged(&self, feed: Option<&RetentionPolicy>) -> RetentionPolicy {
+        let Some(feed) = feed else {
+            return self.clone();
+        };
+
+        RetentionPolicy {
+            max_age_days: feed.max_age_days.or(self.max_age_days),
+            max_entries: feed.max_entries.or(self.max_entries),
+            keep_unread: feed.keep_unread.or(self.keep_unread),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.max_age_days.is_some() || self.max_entries.is_some()
+    }
+
+    /// Unread entries are kept unless the policy explicitly says otherwise
+    pub fn keep_unread(&self) -> bool {
+        self.keep_unread.unwrap_or(true)
+    }
+
+    /// Whether an entry at position `index` (newest first) published at `date` should go
+    pub fn expires(&self, index: usize, date: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        let too_many = self.max_entries.is_some_and(|max| index >= max);
+        let too_old = self
+            .max_age_days
+            .is_some_and(|days| now.signed_duration_since(date) > Duration::days(days.into()));
+
+        too_many || too_old
+    }
+}
@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use tracing::error;
+
+const SESSION_PATH: &str = ".session.toml";
+
+/// Entries read further back than this many are forgotten, so the file doesn't grow forever
+const MAX_POSITIONS: usize = 200;
+
+/// The node of the feed tree that was selected
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SessionNode {
+    Category { title: String },
+    Feed { category: String, slug: String },
+    ReadLater,
+}
+
+/// Where the user was when bulletty closed, restored on the next launch. It's kept per device, in
+/// the library's `.session.toml`.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Session {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<SessionNode>,
+    /// The selected entry, by its path in the library
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry: Option<String>,
+    /// How far the entries left partially read were scrolled, by their path in the library
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    positions: BTreeMap<String, Position>,
+
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(skip)]
+    datapath: PathBuf,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+struct Position {
+    /// The part of the entry scrolled past, from 0 to 1. Rows depend on the width the entry is
+    /// wrapped to, so they'd point somewhere else on another terminal or reader width.
+    #[serde(default)]
+    progress: f64,
+    /// When it was left, to forget the oldest first
+    order: u64,
+}
+
+impl Session {
+    pub fn new(datapath: &Path) -> color_eyre::Result<Self> {
+        let path = datapath.join(SESSION_PATH);
+
+        let mut session = if path.exists() {
+            let data = fs::read_to_string(&path)?;
+            match toml::from_str(&data) {
+                Ok(session) => session,
+                Err(e) => {
+                    error!("Error parsing {path:?}: {e:?}");
+                    Self::default()
+                }
+            }
+        } else {
+            Self::default()
+        };
+
+        session.path = path;
+        session.datapath = datapath.to_path_buf();
+        Ok(session)
+    }
+
+    /// The key of an entry file, relative to the library so it survives moving the library
+    pub fn entry_key(&self, filepath: &Path) -> String {
+        filepath
+            .strip_prefix(&self.datapath)
+            .unwrap_or(filepath)
+            .to_string_lossy()
+            .to_string()
+    }
+
+    pub fn position(&self, filepath: &Path) -> Option<f64> {
+        self.positions
+            .get(&self.entry_key(filepath))
+            .map(|p| p.progress)
+    }
+
+    /// Remembers how far an entry was read, as the part of it scrolled past. Entries at the top,
+    /// or read to the end, are forgotten: there's nothing to come back to.
+    pub fn set_position(&mut self, filepath: &Path, progress: f64) {
+        let key = self.entry_key(filepath);
+        if progress <= 0.0 || progress >= 1.0 {
+            self.positions.remove(&key);
+            return;
+        }
+
+        let order = self
+            .positions
+            .values()
+            .map(|p| p.order + 1)
+            .max()
+            .unwrap_or(0);
+        self.positions.insert(key, Position { progress, order });
+
+        while self.positions.len() > MAX_POSITIONS {
+            let oldest = self
+                .positions
+                .iter()
+                .min_by_key(|(_, p)| p.order)
+                .map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                self.positions.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn save(&self) -> color_eyre::Result<()> {
+        let toml_string = toml::to_string_pretty(self)?;
+        fs::write(&self.path, toml_string)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let entry = dir.path().join("categories/Tech/blog/post.md");
+
+        let mut session = Session::new(dir.path()).unwrap();
+        assert_eq!(session.node, None);
+
+        session.node = Some(SessionNode::Feed {
+            category: String::from("Tech"),
+            slug: String::from("blog"),
+        });
+        session.entry = Some(session.entry_key(&entry));
+        session.set_position(&entry, 0.42);
+        session.save().unwrap();
+
+        let session = Session::new(dir.path()).unwrap();
+        assert_eq!(
+            session.node,
+            Some(SessionNode::Feed {
+                category: String::from("Tech"),
+                slug: String::from("blog"),
+            })
+        );
+        assert_eq!(
+            session.entry.as_deref(),
+            Some("categories/Tech/blog/post.md")
+        );
+        assert_eq!(session.position(&entry), Some(0.42));
+    }
+
+    #[test]
+    fn test_positions() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut session = Session::new(dir.path()).unwrap();
+        let entry = dir.path().join("post.md");
+
+        session.set_position(&entry, 0.1);
+        assert_eq!(session.position(&entry), Some(0.1));
+
+        session.set_position(&entry, 1.0);
+        assert_eq!(session.position(&entry), None);
+
+        session.set_position(&entry, 0.3);
+        session.set_position(&entry, 0.0);
+        assert_eq!(session.position(&entry), None);
+
+        for i in 0..=MAX_POSITIONS {
+            session.set_position(&dir.path().join(format!("{i}.md")), 0.5);
+        }
+        assert_eq!(session.positions.len(), MAX_POSITIONS);
+        assert_eq!(session.position(&dir.path().join("0.md")), None);
+        assert_eq!(
+            session.position(&dir.path().join(format!("{MAX_POSITIONS}.md"))),
+            Some(0.5)
+        );
+    }
+}
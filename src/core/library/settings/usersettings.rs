@@ -1,9 +1,13 @@
 use std::{collections::HashMap, path::Path};
 
-use crate::core::library::settings::{appearance::Appearance, theme::Theme, themedata};
+use crate::core::library::settings::{
+    appearance::Appearance, retention::RetentionPolicy, session::Session, theme::Theme, themedata,
+};
 
 pub struct UserSettings {
     pub appearance: Appearance,
+    pub retention: RetentionPolicy,
+    pub session: Session,
     themes: HashMap<String, Theme>,
 }
 
@@ -11,6 +15,8 @@ impl UserSettings {
     pub fn new(datapath: &Path) -> color_eyre::Result<Self> {
         Ok(Self {
             appearance: Appearance::new(datapath)?,
+            retention: RetentionPolicy::new(datapath)?,
+            session: Session::new(datapath)?,
             themes: themedata::get_themes(),
         })
     }
@@ -9,7 +9,10 @@ use std::{
 
 use tracing::{error, info};
 
-use crate::core::library::{feedcategory::FeedCategory, feedlibrary::FeedLibrary};
+use crate::core::{
+    hooks::{AppHooks, FeedUpdate},
+    library::{feedcategory::FeedCategory, feedlibrary::FeedLibrary},
+};
 
 pub struct Updater {
     pub last_completed: Arc<Mutex<String>>,
@@ -20,7 +23,7 @@ pub struct Updater {
 }
 
 impl Updater {
-    pub fn new(feedcategories: Vec<FeedCategory>, data_dir: &Path) -> Self {
+    pub fn new(feedcategories: Vec<FeedCategory>, data_dir: &Path, hooks: Arc<AppHooks>) -> Self {
         let completed = Arc::new(Mutex::new(String::from("Working...")));
         let finished = Arc::new(AtomicBool::new(false));
         let total_completed = Arc::new(AtomicU16::new(0));
@@ -33,24 +36,53 @@ impl Updater {
         let handle = Some(thread::spawn(move || {
             info!("Starting updater");
             let library = FeedLibrary::new(&data_dir);
+            let mut updates = vec![];
 
             for category in feedcategories.iter() {
                 for feed in category.feeds.iter() {
-                    if let Err(e) = library
+                    let result = library
                         .data
-                        .update_feed_entries(&category.title, feed, None)
-                    {
-                        error!("Something happened when updating {}: {:?}", &feed.title, e);
-                        continue;
-                    }
+                        .update_feed_entries(&category.title, feed, None);
+
+                    let entries = match result {
+                        Ok(entries) => entries,
+                        Err(e) => {
+                            error!("Something happened when updating {}: {:?}", &feed.title, e);
+                            hooks.run_feed_error(&category.title, feed, &e.to_string());
+                            updates.push(FeedUpdate {
+                                category: category.title.clone(),
+                                feed: feed.clone(),
+                                new_entries: 0,
+                                failed: true,
+                            });
+                            continue;
+                        }
+                    };
+
+                    hooks.run_new_entries(&category.title, feed, &entries);
+                    updates.push(FeedUpdate {
+                        category: category.title.clone(),
+                        feed: feed.clone(),
+                        new_entries: entries.len(),
+                        failed: false,
+                    });
 
                     info!("Updated {}", &feed.title);
 
+                    match library.prune_feed(feed, false) {
+                        Ok(pruned) if !pruned.is_empty() => {
+                            info!("Pruned {} entries from {}", pruned.len(), &feed.title)
+                        }
+                        Ok(_) => {}
+                        Err(e) => error!("Couldn't prune {}: {:?}", &feed.title, e),
+                    }
+
                     total_completed_clone.fetch_add(1, Relaxed);
                     *completed_clone.lock().unwrap() = feed.title.clone();
                 }
             }
 
+            hooks.run_update_finished(&updates);
             finished_clone.store(true, Relaxed);
         }));
 
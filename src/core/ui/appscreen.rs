@@ -8,6 +8,7 @@ use super::instructiondetails::ScreenInstructions;
 use super::notification::AppNotification;
 
 use super::dialog::Dialog;
+use super::external::ExternalView;
 
 pub enum AppScreenEvent {
     None,
@@ -20,6 +21,9 @@ pub enum AppScreenEvent {
 
     Notify(AppNotification),
 
+    /// Leaves the terminal to another program until it exits
+    OpenExternal(ExternalView),
+
     ExitApp,
 }
 
@@ -31,6 +35,9 @@ pub trait AppScreen {
     fn unpause(&mut self);
 
     fn render(&mut self, frame: &mut Frame, area: Rect);
+    /// Called once the frame is on the terminal, for what has to be written straight to it.
+    /// `covered` is set when a dialog was drawn over the screen.
+    fn after_draw(&mut self, _covered: bool) {}
     fn handle_event(&mut self, event: Event) -> Result<AppScreenEvent>;
     fn handle_keypress(&mut self, key: KeyEvent) -> Result<AppScreenEvent>;
 
@@ -0,0 +1,146 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use color_eyre::eyre::{Result, bail, eyre};
+
+use crate::core::feed::feedentry::FeedEntry;
+
+/// Used when neither the configuration nor the environment name an editor or pager
+#[cfg(not(windows))]
+const FALLBACK_EDITOR: &str = "vi";
+#[cfg(not(windows))]
+const FALLBACK_PAGER: &str = "less";
+#[cfg(windows)]
+const FALLBACK_EDITOR: &str = "notepad";
+#[cfg(windows)]
+const FALLBACK_PAGER: &str = "more";
+
+/// An entry handed to a program outside bulletty, which takes over the terminal until it exits
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExternalView {
+    /// The entry's Markdown file, to edit or annotate
+    Editor(PathBuf),
+    /// The entry's text, piped to the pager
+    Pager(String),
+}
+
+impl ExternalView {
+    pub fn editor(entry: &FeedEntry) -> Self {
+        ExternalView::Editor(entry.filepath.clone())
+    }
+
+    pub fn pager(entry: &FeedEntry) -> Self {
+        ExternalView::Pager(format!("# {}\n\n{}\n", entry.title, entry.text))
+    }
+
+    /// The command line to run: the configured command, or else the one from the environment,
+    /// or else `vi` and `less` (`notepad` and `more` on Windows). The editor gets the file as its
+    /// last argument.
+    pub fn command(
+        &self,
+        configured: Option<&str>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Vec<String>> {
+        let (vars, fallback): (&[&str], &str) = match self {
+            ExternalView::Editor(_) => (&["VISUAL", "EDITOR"], FALLBACK_EDITOR),
+            ExternalView::Pager(_) => (&["PAGER"], FALLBACK_PAGER),
+        };
+
+        let template = configured
+            .map(String::from)
+            .or_else(|| {
+                vars.iter()
+                    .find_map(|var| env(var).filter(|value| !value.trim().is_empty()))
+            })
+            .unwrap_or_else(|| fallback.to_string());
+
+        let mut words = shell_words::split(&template)
+            .map_err(|e| eyre!("Invalid command {:?}: {}", template, e))?;
+        if words.is_empty() {
+            bail!("The command is empty");
+        }
+
+        if let ExternalView::Editor(path) = self {
+            words.push(path.to_string_lossy().to_string());
+        }
+
+        Ok(words)
+    }
+
+    /// Runs the command on the terminal, waiting for it to exit
+    pub fn run(&self, command: &[String]) -> Result<()> {
+        let Some((program, args)) = command.split_first() else {
+            bail!("The command is empty");
+        };
+
+        let mut process = Command::new(program);
+        process.args(args);
+
+        let status = match self {
+            ExternalView::Editor(_) => process.status(),
+            ExternalView::Pager(text) => {
+                process.stdin(Stdio::piped()).spawn().and_then(|mut child| {
+                    if let Some(mut stdin) = child.stdin.take() {
+                        // The pager can quit before reading it all, which is fine
+                        let _ = stdin.write_all(text.as_bytes());
+                    }
+                    child.wait()
+                })
+            }
+        }
+        .map_err(|e| eyre!("Couldn't run {}: {}", program, e))?;
+
+        if !status.success() {
+            bail!("{} exited with {}", program, status);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command() {
+        let editor = ExternalView::Editor(PathBuf::from("/data/my post.md"));
+        let pager = ExternalView::Pager(String::from("# Title"));
+        let env = |var: &str| match var {
+            "EDITOR" => Some(String::from("nvim -c 'set wrap'")),
+            "PAGER" => Some(String::from(" ")),
+            _ => None,
+        };
+
+        assert_eq!(
+            editor.command(None, env).unwrap(),
+            vec!["nvim", "-c", "set wrap", "/data/my post.md"]
+        );
+        assert_eq!(
+            editor.command(Some("hx"), env).unwrap(),
+            vec!["hx", "/data/my post.md"]
+        );
+        assert_eq!(pager.command(None, env).unwrap(), vec![FALLBACK_PAGER]);
+        assert_eq!(
+            pager.command(Some("glow -p -"), env).unwrap(),
+            vec!["glow", "-p", "-"]
+        );
+        assert!(pager.command(Some("glow 'unclosed"), env).is_err());
+        assert!(pager.command(Some(""), env).is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run() {
+        let pager = ExternalView::Pager(String::from("# Title\n\nText\n"));
+        let read_all = ["sh", "-c", "cat > /dev/null"].map(String::from);
+        assert!(pager.run(&read_all).is_ok());
+        assert!(pager.run(&[String::from("false")]).is_err());
+        assert!(
+            pager
+                .run(&[String::from("bulletty-no-such-program")])
+                .is_err()
+        );
+    }
+}
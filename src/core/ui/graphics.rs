@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+/// How article images are drawn in the reader. `auto` picks the protocol from the terminal;
+/// `off` keeps the `[Image: ...]` placeholders only.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageProtocol {
+    #[default]
+    Auto,
+    Kitty,
+    Iterm2,
+    Sixel,
+    Off,
+}
+
+impl ImageProtocol {
+    /// The protocol to draw with, looking at the terminal's environment when set to `auto`.
+    /// Returns `None` when images can't be drawn.
+    pub fn resolve(self, env: impl Fn(&str) -> Option<String>) -> Option<ImageProtocol> {
+        match self {
+            ImageProtocol::Auto => Self::detect(env),
+            ImageProtocol::Off => None,
+            protocol => Some(protocol),
+        }
+    }
+
+    fn detect(env: impl Fn(&str) -> Option<String>) -> Option<ImageProtocol> {
+        // Multiplexers need their own passthrough, which is rarely set up
+        if env("TMUX").is_some() || env("STY").is_some() || env("ZELLIJ").is_some() {
+            return None;
+        }
+
+        let term = env("TERM").unwrap_or_default().to_lowercase();
+        let program = env("TERM_PROGRAM").unwrap_or_default().to_lowercase();
+
+        if env("KITTY_WINDOW_ID").is_some()
+            || term.contains("kitty")
+            || term.contains("ghostty")
+            || program == "ghostty"
+        {
+            return Some(ImageProtocol::Kitty);
+        }
+
+        if program == "iterm.app"
+            || program == "wezterm"
+            || env("LC_TERMINAL").is_some_and(|t| t == "iTerm2")
+        {
+            return Some(ImageProtocol::Iterm2);
+        }
+
+        if env("KONSOLE_VERSION").is_some()
+            || term.contains("foot")
+            || term.contains("mlterm")
+            || term.contains("sixel")
+            || program == "contour"
+        {
+            return Some(ImageProtocol::Sixel);
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |key| {
+            vars.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.to_string())
+        }
+    }
+
+    #[test]
+    fn test_detect() {
+        let auto = ImageProtocol::Auto;
+        assert_eq!(
+            auto.resolve(env(&[("TERM", "xterm-kitty")])),
+            Some(ImageProtocol::Kitty)
+        );
+        assert_eq!(
+            auto.resolve(env(&[("TERM_PROGRAM", "iTerm.app")])),
+            Some(ImageProtocol::Iterm2)
+        );
+        assert_eq!(
+            auto.resolve(env(&[("TERM", "foot")])),
+            Some(ImageProtocol::Sixel)
+        );
+        assert_eq!(auto.resolve(env(&[("TERM", "xterm-256color")])), None);
+        assert_eq!(
+            auto.resolve(env(&[("TERM", "xterm-kitty"), ("TMUX", "/tmp/tmux")])),
+            None
+        );
+
+        // Explicit settings don't look at the terminal
+        assert_eq!(
+            ImageProtocol::Sixel.resolve(env(&[])),
+            Some(ImageProtocol::Sixel)
+        );
+        assert_eq!(
+            ImageProtocol::Off.resolve(env(&[("TERM", "xterm-kitty")])),
+            None
+        );
+    }
+}
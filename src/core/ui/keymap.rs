@@ -0,0 +1,1031 @@
+use std::collections::{BTreeMap, HashMap};
+
+use color_eyre::eyre::{Result, bail};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+
+use crate::core::ui::instructiondetails::{
+    InstructionCategory, InstructionDetail, ScreenInstructions,
+};
+
+/// Where a key is pressed; the same key can do different things in each of them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyContext {
+    /// The feed tree of the main screen
+    Tree,
+    /// The entry list of the main screen
+    Entries,
+    Reader,
+    /// The reader while a search is active, before the reader keys
+    Search,
+    /// Dialogs. The ones with a text input leave the keys that type a character to it.
+    Dialog,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    Back,
+    MoveDown,
+    MoveUp,
+    First,
+    Last,
+    HalfPageDown,
+    HalfPageUp,
+    NextCategory,
+    PreviousCategory,
+    NextEntry,
+    PreviousEntry,
+    FocusEntries,
+    ReadEntry,
+    OpenLink,
+    FollowLink,
+    LinkList,
+    CopyLink,
+    Search,
+    NextMatch,
+    PreviousMatch,
+    ClearSearch,
+    ToggleRead,
+    MarkAllRead,
+    ToggleReadLater,
+    OpenInEditor,
+    OpenInPager,
+    AddFeed,
+    EditFeed,
+    MoveFeed,
+    DeleteFeed,
+    WidenPane,
+    NarrowPane,
+    ThemePicker,
+    Help,
+    Confirm,
+    Close,
+}
+
+/// The `[keys]` table of the configuration: key sequences per action, per context
+pub type KeyConfig = BTreeMap<KeyContext, BTreeMap<Action, Vec<String>>>;
+
+struct DefaultBinding {
+    action: Action,
+    keys: &'static [&'static str],
+    category: &'static str,
+    description: &'static str,
+}
+
+const fn bind(
+    action: Action,
+    keys: &'static [&'static str],
+    category: &'static str,
+    description: &'static str,
+) -> DefaultBinding {
+    DefaultBinding {
+        action,
+        keys,
+        category,
+        description,
+    }
+}
+
+const TREE_BINDINGS: &[DefaultBinding] = &[
+    bind(Action::MoveDown, &["j", "Down"], "Navigation", "move down"),
+    bind(Action::MoveUp, &["k", "Up"], "Navigation", "move up"),
+    bind(Action::NextCategory, &["n"], "Navigation", "next category"),
+    bind(
+        Action::PreviousCategory,
+        &["p"],
+        "Navigation",
+        "previous category",
+    ),
+    bind(
+        Action::First,
+        &["g", "Home"],
+        "Navigation",
+        "beginning of list",
+    ),
+    bind(Action::Last, &["G", "End"], "Navigation", "end of list"),
+    bind(
+        Action::FocusEntries,
+        &["Enter", "Right", "Tab", "l"],
+        "Actions",
+        "select category or feed",
+    ),
+    bind(
+        Action::MarkAllRead,
+        &["R"],
+        "Actions",
+        "mark all items as read",
+    ),
+    bind(Action::AddFeed, &["a"], "Organize", "add a new feed"),
+    bind(
+        Action::EditFeed,
+        &["e"],
+        "Organize",
+        "edit selected feed or rename category",
+    ),
+    bind(
+        Action::MoveFeed,
+        &["m"],
+        "Organize",
+        "move selected feed to another category",
+    ),
+    bind(
+        Action::DeleteFeed,
+        &["D"],
+        "Organize",
+        "delete selected feed or category",
+    ),
+    bind(Action::NarrowPane, &["<"], "App", "narrow the feed column"),
+    bind(Action::WidenPane, &[">"], "App", "widen the feed column"),
+    bind(Action::ThemePicker, &["t"], "App", "open theme picker"),
+    bind(Action::Help, &["?"], "App", "show this help"),
+    bind(Action::Quit, &["Esc", "q", "Ctrl+c"], "App", "quit"),
+];
+
+const ENTRIES_BINDINGS: &[DefaultBinding] = &[
+    bind(Action::MoveDown, &["j", "Down"], "Navigation", "move down"),
+    bind(Action::MoveUp, &["k", "Up"], "Navigation", "move up"),
+    bind(
+        Action::First,
+        &["g", "Home"],
+        "Navigation",
+        "beginning of list",
+    ),
+    bind(Action::Last, &["G", "End"], "Navigation", "end of list"),
+    bind(Action::ReadEntry, &["Enter"], "Actions", "read entry"),
+    bind(Action::OpenLink, &["o"], "Actions", "open link externally"),
+    bind(
+        Action::ToggleReadLater,
+        &["L"],
+        "Actions",
+        "add/remove read later",
+    ),
+    bind(
+        Action::ToggleRead,
+        &["r"],
+        "Actions",
+        "toggle item read state",
+    ),
+    bind(
+        Action::OpenInPager,
+        &["v"],
+        "Actions",
+        "read entry in the pager",
+    ),
+    bind(
+        Action::OpenInEditor,
+        &["e"],
+        "Actions",
+        "open entry in the editor",
+    ),
+    bind(
+        Action::MarkAllRead,
+        &["R"],
+        "Actions",
+        "mark all items as read",
+    ),
+    bind(Action::NarrowPane, &["<"], "App", "narrow the feed column"),
+    bind(Action::WidenPane, &[">"], "App", "widen the feed column"),
+    bind(Action::ThemePicker, &["t"], "App", "open theme picker"),
+    bind(Action::Help, &["?"], "App", "show this help"),
+    bind(
+        Action::Back,
+        &["Esc", "h", "Left"],
+        "App",
+        "back to the feed list",
+    ),
+    bind(Action::Quit, &["q", "Ctrl+c"], "App", "quit"),
+];
+
+const READER_BINDINGS: &[DefaultBinding] = &[
+    bind(
+        Action::MoveDown,
+        &["j", "Down"],
+        "Navigation",
+        "scroll down",
+    ),
+    bind(Action::MoveUp, &["k", "Up"], "Navigation", "scroll up"),
+    bind(
+        Action::HalfPageDown,
+        &["Ctrl+d"],
+        "Navigation",
+        "scroll half page down",
+    ),
+    bind(
+        Action::HalfPageUp,
+        &["Ctrl+u"],
+        "Navigation",
+        "scroll half page up",
+    ),
+    bind(
+        Action::First,
+        &["g", "Home"],
+        "Navigation",
+        "beginning of entry",
+    ),
+    bind(Action::Last, &["G", "End"], "Navigation", "end of entry"),
+    bind(Action::NextEntry, &["n"], "Navigation", "next entry"),
+    bind(
+        Action::PreviousEntry,
+        &["p"],
+        "Navigation",
+        "previous entry",
+    ),
+    bind(Action::OpenLink, &["o"], "Actions", "open link externally"),
+    bind(
+        Action::OpenInPager,
+        &["v"],
+        "Actions",
+        "read entry in the pager",
+    ),
+    bind(
+        Action::OpenInEditor,
+        &["e"],
+        "Actions",
+        "open entry in the editor",
+    ),
+    bind(
+        Action::FollowLink,
+        &["f"],
+        "Links",
+        "type a link number to open it",
+    ),
+    bind(
+        Action::LinkList,
+        &["L"],
+        "Links",
+        "list the links of the entry",
+    ),
+    bind(Action::CopyLink, &["y"], "Links", "copy the entry link"),
+    bind(Action::Search, &["/"], "Search", "search in the entry"),
+    bind(Action::NarrowPane, &["<"], "App", "narrow the reader"),
+    bind(Action::WidenPane, &[">"], "App", "widen the reader"),
+    bind(Action::ThemePicker, &["t"], "App", "open theme picker"),
+    bind(Action::Help, &["?"], "App", "show this help"),
+    bind(Action::Back, &["Esc", "q", "Ctrl+c"], "App", "leave"),
+];
+
+const SEARCH_BINDINGS: &[DefaultBinding] = &[
+    bind(Action::NextMatch, &["n"], "Search", "next match"),
+    bind(Action::PreviousMatch, &["N"], "Search", "previous match"),
+    bind(Action::ClearSearch, &["Esc"], "Search", "clear the search"),
+];
+
+const DIALOG_BINDINGS: &[DefaultBinding] = &[
+    bind(Action::MoveDown, &["j", "Down"], "Navigation", "move down"),
+    bind(Action::MoveUp, &["k", "Up"], "Navigation", "move up"),
+    bind(
+        Action::First,
+        &["g", "Home"],
+        "Navigation",
+        "beginning of list",
+    ),
+    bind(Action::Last, &["G", "End"], "Navigation", "end of list"),
+    bind(Action::Confirm, &["Enter"], "Actions", "confirm"),
+    bind(Action::OpenLink, &["o"], "Actions", "open link externally"),
+    bind(Action::CopyLink, &["y"], "Actions", "copy the link"),
+    bind(Action::Close, &["Esc", "q", "Ctrl+c"], "App", "close"),
+];
+
+fn default_bindings(context: KeyContext) -> &'static [DefaultBinding] {
+    match context {
+        KeyContext::Tree => TREE_BINDINGS,
+        KeyContext::Entries => ENTRIES_BINDINGS,
+        KeyContext::Reader => READER_BINDINGS,
+        KeyContext::Search => SEARCH_BINDINGS,
+        KeyContext::Dialog => DIALOG_BINDINGS,
+    }
+}
+
+/// A single key press, with its modifiers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyChord {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+const NAMED_KEYS: &[(&str, KeyCode)] = &[
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("enter", KeyCode::Enter),
+    ("return", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("escape", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pgup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("pgdn", KeyCode::PageDown),
+    ("space", KeyCode::Char(' ')),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("del", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+];
+
+const MODIFIER_PREFIXES: &[(&str, KeyModifiers)] = &[
+    ("ctrl+", KeyModifiers::CONTROL),
+    ("control+", KeyModifiers::CONTROL),
+    ("c-", KeyModifiers::CONTROL),
+    ("alt+", KeyModifiers::ALT),
+    ("meta+", KeyModifiers::ALT),
+    ("m-", KeyModifiers::ALT),
+    ("shift+", KeyModifiers::SHIFT),
+];
+
+impl KeyChord {
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        // Letters already carry their case, and terminals disagree on whether they also report
+        // Shift, so it's only kept for the other keys. Ctrl and Alt combinations ignore case.
+        let (code, modifiers) = match code {
+            KeyCode::Char(c) => {
+                let modifiers = modifiers - KeyModifiers::SHIFT;
+                if modifiers.is_empty() {
+                    (code, modifiers)
+                } else {
+                    (KeyCode::Char(c.to_ascii_lowercase()), modifiers)
+                }
+            }
+            KeyCode::BackTab => (code, modifiers - KeyModifiers::SHIFT),
+            _ => (code, modifiers),
+        };
+
+        Self {
+            code,
+            modifiers: modifiers
+                & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT),
+        }
+    }
+
+    pub fn from_event(key: KeyEvent) -> Self {
+        Self::new(key.code, key.modifiers)
+    }
+
+    fn named_key(name: &str) -> Option<KeyCode> {
+        let lower = name.to_ascii_lowercase();
+
+        if let Some(number) = lower.strip_prefix('f')
+            && let Ok(number) = number.parse::<u8>()
+            && (1..=12).contains(&number)
+        {
+            return Some(KeyCode::F(number));
+        }
+
+        NAMED_KEYS
+            .iter()
+            .find(|(key, _)| *key == lower)
+            .map(|(_, code)| *code)
+    }
+
+    /// Parses one space-separated part of a key sequence. Several plain characters in a row,
+    /// like `gg`, are a sequence of their own.
+    fn parse_word(word: &str) -> Result<Vec<KeyChord>> {
+        let mut rest = word;
+        let mut modifiers = KeyModifiers::NONE;
+
+        'prefixes: loop {
+            for (prefix, modifier) in MODIFIER_PREFIXES {
+                if rest.len() > prefix.len()
+                    && rest.is_char_boundary(prefix.len())
+                    && rest[..prefix.len()].eq_ignore_ascii_case(prefix)
+                {
+                    modifiers |= *modifier;
+                    rest = &rest[prefix.len()..];
+                    continue 'prefixes;
+                }
+            }
+            break;
+        }
+
+        if let Some(code) = Self::named_key(rest) {
+            return Ok(vec![Self::new(code, modifiers)]);
+        }
+
+        let chars: Vec<char> = rest.chars().collect();
+        match chars.as_slice() {
+            [c] => Ok(vec![Self::new(KeyCode::Char(*c), modifiers)]),
+            [_, ..] if modifiers.is_empty() => Ok(chars
+                .iter()
+                .map(|c| Self::new(KeyCode::Char(*c), modifiers))
+                .collect()),
+            _ => bail!("Unknown key {word:?}"),
+        }
+    }
+
+    /// Parses a key sequence such as `j`, `Down`, `Ctrl+d`, `gg` or `C-x C-c`
+    pub fn parse_sequence(text: &str) -> Result<Vec<KeyChord>> {
+        let mut sequence = vec![];
+        for word in text.split_whitespace() {
+            sequence.extend(Self::parse_word(word)?);
+        }
+
+        if sequence.is_empty() {
+            bail!("Empty key sequence");
+        }
+
+        Ok(sequence)
+    }
+
+    /// Whether the key types a character into a text input, space included
+    fn types_char(&self) -> bool {
+        matches!(self.code, KeyCode::Char(_)) && self.modifiers.is_empty()
+    }
+
+    fn is_plain_char(&self) -> bool {
+        matches!(self.code, KeyCode::Char(c) if c != ' ') && self.modifiers.is_empty()
+    }
+
+    fn display(&self) -> String {
+        let mut text = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            text.push_str("Ctrl+");
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            text.push_str("Alt+");
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            text.push_str("Shift+");
+        }
+
+        match self.code {
+            KeyCode::Up => text.push('↑'),
+            KeyCode::Down => text.push('↓'),
+            KeyCode::Left => text.push('←'),
+            KeyCode::Right => text.push('→'),
+            KeyCode::Char(' ') => text.push_str("Space"),
+            KeyCode::Char(c) => text.push(c),
+            KeyCode::BackTab => text.push_str("Shift+Tab"),
+            KeyCode::PageUp => text.push_str("PgUp"),
+            KeyCode::PageDown => text.push_str("PgDn"),
+            code => text.push_str(&code.to_string()),
+        }
+
+        text
+    }
+}
+
+fn display_sequence(sequence: &[KeyChord]) -> String {
+    if sequence.iter().all(KeyChord::is_plain_char) {
+        sequence.iter().map(KeyChord::display).collect()
+    } else {
+        sequence
+            .iter()
+            .map(KeyChord::display)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// One line of the generated help, collecting the keys of an action across contexts
+struct HelpEntry {
+    action: Action,
+    description: &'static str,
+    keys: Vec<String>,
+}
+
+struct Binding {
+    action: Action,
+    sequences: Vec<Vec<KeyChord>>,
+    category: &'static str,
+    description: &'static str,
+}
+
+/// Key sequences typed so far that are the beginning of a longer binding
+#[derive(Default)]
+pub struct PendingKeys {
+    chords: Vec<KeyChord>,
+}
+
+/// The active key bindings of every context: the defaults, with the user's `[keys]` on top
+pub struct Keymap {
+    contexts: HashMap<KeyContext, Vec<Binding>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(None).expect("default key bindings should be valid")
+    }
+}
+
+impl Keymap {
+    pub fn new(config: Option<&KeyConfig>) -> Result<Self> {
+        let mut contexts = HashMap::new();
+
+        for context in [
+            KeyContext::Tree,
+            KeyContext::Entries,
+            KeyContext::Reader,
+            KeyContext::Search,
+            KeyContext::Dialog,
+        ] {
+            let mut bindings = default_bindings(context)
+                .iter()
+                .map(|binding| {
+                    Ok(Binding {
+                        action: binding.action,
+                        sequences: binding
+                            .keys
+                            .iter()
+                            .map(|keys| KeyChord::parse_sequence(keys))
+                            .collect::<Result<_>>()?,
+                        category: binding.category,
+                        description: binding.description,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            if let Some(overrides) = config.and_then(|config| config.get(&context)) {
+                Self::apply_overrides(context, &mut bindings, overrides)?;
+            }
+
+            contexts.insert(context, bindings);
+        }
+
+        Ok(Self { contexts })
+    }
+
+    /// Replaces the keys of every overridden action, taking them away from the other actions
+    /// of the context that had them by default
+    fn apply_overrides(
+        context: KeyContext,
+        bindings: &mut [Binding],
+        overrides: &BTreeMap<Action, Vec<String>>,
+    ) -> Result<()> {
+        let mut taken: Vec<(Vec<KeyChord>, Action)> = vec![];
+
+        for (action, keys) in overrides {
+            let Some(binding) = bindings.iter_mut().find(|b| b.action == *action) else {
+                bail!("Action {action:?} can't be bound in the {context:?} keys");
+            };
+
+            binding.sequences.clear();
+            for keys in keys {
+                let sequence = KeyChord::parse_sequence(keys)?;
+                if let Some((_, other)) = taken.iter().find(|(s, _)| *s == sequence) {
+                    bail!(
+                        "{keys:?} is bound to both {other:?} and {action:?} in the {context:?} keys"
+                    );
+                }
+                taken.push((sequence.clone(), *action));
+                binding.sequences.push(sequence);
+            }
+        }
+
+        for binding in bindings.iter_mut() {
+            if !overrides.contains_key(&binding.action) {
+                binding
+                    .sequences
+                    .retain(|sequence| !taken.iter().any(|(s, _)| s == sequence));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn bindings(&self, context: KeyContext) -> &[Binding] {
+        self.contexts
+            .get(&context)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    fn exact_action(&self, context: KeyContext, chords: &[KeyChord]) -> Option<Action> {
+        self.bindings(context)
+            .iter()
+            .find(|b| b.sequences.iter().any(|s| s.as_slice() == chords))
+            .map(|b| b.action)
+    }
+
+    fn starts_longer_sequence(&self, context: KeyContext, chords: &[KeyChord]) -> bool {
+        self.bindings(context).iter().any(|b| {
+            b.sequences
+                .iter()
+                .any(|s| s.len() > chords.len() && s.starts_with(chords))
+        })
+    }
+
+    /// Feeds a key press, returning the actions it completes. A key that begins a longer
+    /// sequence waits for the next one; if that doesn't continue the sequence, the shorter
+    /// binding runs and the new key is handled on its own.
+    pub fn actions(
+        &self,
+        context: KeyContext,
+        pending: &mut PendingKeys,
+        key: KeyEvent,
+    ) -> Vec<Action> {
+        if matches!(key.code, KeyCode::Modifier(_)) {
+            return vec![];
+        }
+
+        pending.chords.push(KeyChord::from_event(key));
+
+        if self.starts_longer_sequence(context, &pending.chords) {
+            return vec![];
+        }
+
+        if let Some(action) = self.exact_action(context, &pending.chords) {
+            pending.chords.clear();
+            return vec![action];
+        }
+
+        let previous = pending.chords.len() - 1;
+        if previous == 0 {
+            pending.chords.clear();
+            return vec![];
+        }
+
+        let mut actions: Vec<Action> = self
+            .exact_action(context, &pending.chords[..previous])
+            .into_iter()
+            .collect();
+        pending.chords.clear();
+        actions.extend(self.actions(context, pending, key));
+        actions
+    }
+
+    /// Like `actions`, for dialogs with a text input: keys that type a character are left to
+    /// the input, even when they're bound
+    pub fn input_actions(
+        &self,
+        context: KeyContext,
+        pending: &mut PendingKeys,
+        key: KeyEvent,
+    ) -> Vec<Action> {
+        if KeyChord::from_event(key).types_char() {
+            pending.chords.clear();
+            return vec![];
+        }
+
+        self.actions(context, pending, key)
+    }
+
+    /// All the keys bound to an action, like `j/↓`
+    pub fn keys(&self, context: KeyContext, action: Action) -> String {
+        self.bindings(context)
+            .iter()
+            .filter(|b| b.action == action)
+            .flat_map(|b| b.sequences.iter().map(|s| display_sequence(s)))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// A short status line, showing the first key of each of the given actions
+    pub fn status_line(&self, context: KeyContext, items: &[(&[Action], &str)]) -> String {
+        items
+            .iter()
+            .filter_map(|(actions, label)| {
+                let keys: Vec<String> = actions
+                    .iter()
+                    .filter_map(|action| {
+                        self.bindings(context)
+                            .iter()
+                            .find(|b| b.action == *action)
+                            .and_then(|b| b.sequences.first())
+                            .map(|s| display_sequence(s))
+                    })
+                    .collect();
+
+                (!keys.is_empty()).then(|| format!("{}: {label}", keys.join("/")))
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+
+    /// The help for the given contexts, grouped by category. Actions shared between the
+    /// contexts are listed once, with all their keys.
+    pub fn instructions(&self, contexts: &[KeyContext]) -> ScreenInstructions {
+        let mut categories: Vec<(&str, Vec<HelpEntry>)> = vec![];
+
+        for context in contexts {
+            for binding in self.bindings(*context) {
+                let category = match categories.iter().position(|(c, _)| *c == binding.category) {
+                    Some(index) => &mut categories[index].1,
+                    None => {
+                        categories.push((binding.category, vec![]));
+                        &mut categories.last_mut().expect("category was just added").1
+                    }
+                };
+
+                let index = match category.iter().position(|e| e.action == binding.action) {
+                    Some(index) => index,
+                    None => {
+                        category.push(HelpEntry {
+                            action: binding.action,
+                            description: binding.description,
+                            keys: vec![],
+                        });
+                        category.len() - 1
+                    }
+                };
+
+                for sequence in &binding.sequences {
+                    let keys = display_sequence(sequence);
+                    if !category[index].keys.contains(&keys) {
+                        category[index].keys.push(keys);
+                    }
+                }
+            }
+        }
+
+        ScreenInstructions::new(
+            categories
+                .into_iter()
+                .map(|(name, details)| {
+                    InstructionCategory::new(
+                        name,
+                        details
+                            .into_iter()
+                            .filter(|entry| !entry.keys.is_empty())
+                            .map(|entry| {
+                                InstructionDetail::new(entry.keys.join("/"), entry.description)
+                            })
+                            .collect(),
+                    )
+                })
+                .filter(|category| !category.details.is_empty())
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    fn char_key(c: char) -> KeyEvent {
+        press(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    fn config(context: KeyContext, bindings: &[(Action, &[&str])]) -> KeyConfig {
+        let mut config = KeyConfig::new();
+        config.insert(
+            context,
+            bindings
+                .iter()
+                .map(|(action, keys)| (*action, keys.iter().map(|k| k.to_string()).collect()))
+                .collect(),
+        );
+        config
+    }
+
+    #[test]
+    fn test_parse_sequences() {
+        let ctrl_d = KeyChord::new(KeyCode::Char('d'), KeyModifiers::CONTROL);
+        assert_eq!(KeyChord::parse_sequence("Ctrl+d").unwrap(), vec![ctrl_d]);
+        assert_eq!(KeyChord::parse_sequence("C-D").unwrap(), vec![ctrl_d]);
+        assert_eq!(
+            KeyChord::parse_sequence("Down").unwrap(),
+            vec![KeyChord::new(KeyCode::Down, KeyModifiers::NONE)]
+        );
+        assert_eq!(KeyChord::parse_sequence("gg").unwrap().len(), 2);
+        assert_eq!(KeyChord::parse_sequence("C-x C-c").unwrap().len(), 2);
+        assert_eq!(KeyChord::parse_sequence("<").unwrap().len(), 1);
+        assert_eq!(
+            KeyChord::parse_sequence("F5").unwrap(),
+            vec![KeyChord::new(KeyCode::F(5), KeyModifiers::NONE)]
+        );
+        assert!(KeyChord::parse_sequence("Ctrl+nope").is_err());
+        assert!(KeyChord::parse_sequence(" ").is_err());
+
+        assert_eq!(
+            display_sequence(&KeyChord::parse_sequence("g g").unwrap()),
+            "gg"
+        );
+        assert_eq!(
+            display_sequence(&KeyChord::parse_sequence("C-x C-c").unwrap()),
+            "Ctrl+x Ctrl+c"
+        );
+
+        // Shift is implied by the character itself
+        assert_eq!(
+            KeyChord::from_event(press(KeyCode::Char('G'), KeyModifiers::SHIFT)),
+            KeyChord::parse_sequence("G").unwrap()[0]
+        );
+    }
+
+    #[test]
+    fn test_default_keys() {
+        let keymap = Keymap::default();
+        let mut pending = PendingKeys::default();
+
+        assert_eq!(
+            keymap.actions(KeyContext::Reader, &mut pending, char_key('j')),
+            vec![Action::MoveDown]
+        );
+        assert_eq!(
+            keymap.actions(
+                KeyContext::Reader,
+                &mut pending,
+                press(
+                    KeyCode::Char('D'),
+                    KeyModifiers::CONTROL | KeyModifiers::SHIFT
+                )
+            ),
+            vec![Action::HalfPageDown]
+        );
+        assert_eq!(
+            keymap.actions(
+                KeyContext::Tree,
+                &mut pending,
+                press(KeyCode::Esc, KeyModifiers::NONE)
+            ),
+            vec![Action::Quit]
+        );
+        assert_eq!(
+            keymap.actions(
+                KeyContext::Entries,
+                &mut pending,
+                press(KeyCode::Esc, KeyModifiers::NONE)
+            ),
+            vec![Action::Back]
+        );
+        assert!(
+            keymap
+                .actions(KeyContext::Tree, &mut pending, char_key('z'))
+                .is_empty()
+        );
+
+        assert_eq!(
+            keymap.status_line(
+                KeyContext::Reader,
+                &[(&[Action::MoveDown, Action::MoveUp], "scroll")]
+            ),
+            "j/k: scroll"
+        );
+        assert_eq!(keymap.keys(KeyContext::Tree, Action::Quit), "Esc/q/Ctrl+c");
+    }
+
+    #[test]
+    fn test_dialog_keys() {
+        let keymap = Keymap::default();
+        let mut pending = PendingKeys::default();
+
+        assert_eq!(
+            keymap.actions(KeyContext::Dialog, &mut pending, char_key('q')),
+            vec![Action::Close]
+        );
+        assert_eq!(
+            keymap.actions(KeyContext::Dialog, &mut pending, char_key('G')),
+            vec![Action::Last]
+        );
+
+        // Text inputs keep the characters, but not the other keys
+        assert!(
+            keymap
+                .input_actions(KeyContext::Dialog, &mut pending, char_key('q'))
+                .is_empty()
+        );
+        assert!(
+            keymap
+                .input_actions(KeyContext::Dialog, &mut pending, char_key('j'))
+                .is_empty()
+        );
+        assert_eq!(
+            keymap.input_actions(
+                KeyContext::Dialog,
+                &mut pending,
+                press(KeyCode::Esc, KeyModifiers::NONE)
+            ),
+            vec![Action::Close]
+        );
+        assert_eq!(
+            keymap.input_actions(
+                KeyContext::Dialog,
+                &mut pending,
+                press(KeyCode::Char('c'), KeyModifiers::CONTROL)
+            ),
+            vec![Action::Close]
+        );
+        assert_eq!(
+            keymap.input_actions(
+                KeyContext::Dialog,
+                &mut pending,
+                press(KeyCode::Down, KeyModifiers::NONE)
+            ),
+            vec![Action::MoveDown]
+        );
+
+        assert_eq!(
+            keymap.status_line(
+                KeyContext::Dialog,
+                &[(&[Action::Confirm], "save"), (&[Action::Close], "cancel")]
+            ),
+            "Enter: save | Esc: cancel"
+        );
+    }
+
+    #[test]
+    fn test_overrides_and_sequences() {
+        let config = config(
+            KeyContext::Reader,
+            &[
+                (Action::First, &["gg"]),
+                (Action::NextEntry, &["Ctrl+n"]),
+                (Action::MoveDown, &["n", "Down"]),
+                (Action::Help, &[]),
+            ],
+        );
+        let keymap = Keymap::new(Some(&config)).unwrap();
+        let mut pending = PendingKeys::default();
+
+        assert_eq!(
+            keymap.actions(KeyContext::Reader, &mut pending, char_key('n')),
+            vec![Action::MoveDown]
+        );
+        assert!(
+            keymap
+                .actions(KeyContext::Reader, &mut pending, char_key('j'))
+                .is_empty()
+        );
+        assert!(
+            keymap
+                .actions(KeyContext::Reader, &mut pending, char_key('?'))
+                .is_empty()
+        );
+        assert_eq!(keymap.keys(KeyContext::Reader, Action::Help), "");
+
+        // "gg" waits for its second key, anything else drops the sequence
+        assert!(
+            keymap
+                .actions(KeyContext::Reader, &mut pending, char_key('g'))
+                .is_empty()
+        );
+        assert_eq!(
+            keymap.actions(KeyContext::Reader, &mut pending, char_key('g')),
+            vec![Action::First]
+        );
+        assert!(
+            keymap
+                .actions(KeyContext::Reader, &mut pending, char_key('g'))
+                .is_empty()
+        );
+        assert_eq!(
+            keymap.actions(KeyContext::Reader, &mut pending, char_key('G')),
+            vec![Action::Last]
+        );
+
+        // Other contexts keep their defaults
+        assert_eq!(
+            keymap.actions(KeyContext::Tree, &mut pending, char_key('g')),
+            vec![Action::First]
+        );
+    }
+
+    #[test]
+    fn test_prefix_of_longer_sequence() {
+        let config = config(
+            KeyContext::Tree,
+            &[(Action::Quit, &["q"]), (Action::DeleteFeed, &["q d"])],
+        );
+        let keymap = Keymap::new(Some(&config)).unwrap();
+        let mut pending = PendingKeys::default();
+
+        assert!(
+            keymap
+                .actions(KeyContext::Tree, &mut pending, char_key('q'))
+                .is_empty()
+        );
+        assert_eq!(
+            keymap.actions(KeyContext::Tree, &mut pending, char_key('d')),
+            vec![Action::DeleteFeed]
+        );
+
+        // The shorter binding runs once the sequence is broken, followed by the new key
+        assert!(
+            keymap
+                .actions(KeyContext::Tree, &mut pending, char_key('q'))
+                .is_empty()
+        );
+        assert_eq!(
+            keymap.actions(KeyContext::Tree, &mut pending, char_key('j')),
+            vec![Action::Quit, Action::MoveDown]
+        );
+    }
+
+    #[test]
+    fn test_invalid_overrides() {
+        let duplicated = config(
+            KeyContext::Tree,
+            &[(Action::MoveDown, &["x"]), (Action::MoveUp, &["x"])],
+        );
+        assert!(Keymap::new(Some(&duplicated)).is_err());
+
+        let wrong_context = config(KeyContext::Tree, &[(Action::HalfPageDown, &["x"])]);
+        assert!(Keymap::new(Some(&wrong_context)).is_err());
+
+        let parsed: KeyConfig = toml::from_str("[reader]\nmove_down = [\"n\"]\n").unwrap();
+        assert_eq!(parsed[&KeyContext::Reader][&Action::MoveDown], vec!["n"]);
+        assert!(toml::from_str::<KeyConfig>("[reader]\nfly = [\"n\"]\n").is_err());
+    }
+}
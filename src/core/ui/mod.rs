@@ -1,4 +1,7 @@
 pub mod appscreen;
 pub mod dialog;
+pub mod external;
+pub mod graphics;
 pub mod instructiondetails;
+pub mod keymap;
 pub mod notification;
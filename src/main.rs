@@ -6,15 +6,20 @@ pub mod logging;
 pub mod mainui;
 pub mod ui;
 
+use std::process::ExitCode;
+
 use clap::Parser;
 use color_eyre::eyre::Context;
 
 use crate::{
-    core::config::{Config, ConfigStore},
+    core::{
+        config::{Config, ConfigStore},
+        ui::graphics::ImageProtocol,
+    },
     dirs::Directories,
 };
 
-pub fn run() -> color_eyre::Result<()> {
+pub fn run() -> color_eyre::Result<ExitCode> {
     color_eyre::install()?;
 
     let dirs = Directories::new().wrap_err("Failed to construct base directories")?;
@@ -25,6 +30,12 @@ pub fn run() -> color_eyre::Result<()> {
     let mut config = config_store.get_or_create(|| Config {
         datapath: dirs.default_data().into(),
         hooks: None,
+        greader: None,
+        keys: None,
+        mouse: true,
+        images: ImageProtocol::Auto,
+        editor: None,
+        pager: None,
     })?;
 
     let cli = cli::Cli::parse();
@@ -34,12 +45,13 @@ pub fn run() -> color_eyre::Result<()> {
     }
 
     if cli.command.is_none() {
-        mainui::run_main_ui(&config)
+        mainui::run_main_ui(&config)?;
+        Ok(ExitCode::SUCCESS)
     } else {
         cli::run_main_cli(cli, &dirs, &mut config, &config_store)
     }
 }
 
-fn main() -> color_eyre::Result<()> {
+fn main() -> color_eyre::Result<ExitCode> {
     run()
 }
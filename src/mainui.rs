@@ -1,19 +1,29 @@
-use tracing::info;
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use crossterm::terminal::{EnterAlternateScreen, enable_raw_mode};
+use ratatui::DefaultTerminal;
+use tracing::{error, info};
 
-use crate::{app, core::config::Config};
+use crate::{
+    app,
+    core::{config::Config, ui::keymap::Keymap},
+};
 
 pub fn run_main_ui(config: &Config) -> color_eyre::Result<()> {
     info!("Initializing UI");
 
+    let keymap = Keymap::new(config.keys.as_ref())?;
+
     if let Some(hooks) = &config.hooks {
         hooks.run_before_tui();
     }
 
     let terminal = ratatui::init();
-    let mut app = app::App::new(config);
+    set_mouse_capture(config.mouse, true);
+    restore_mouse_on_panic(config.mouse);
+    let mut app = app::App::new(config, keymap);
     app.initmain();
     let result = app.run(terminal);
-    ratatui::restore();
+    suspend_tui(config.mouse);
 
     if let Some(hooks) = &config.hooks {
         hooks.run_after_tui();
@@ -21,3 +31,48 @@ pub fn run_main_ui(config: &Config) -> color_eyre::Result<()> {
 
     result
 }
+
+/// Gives the terminal back to the shell
+pub fn suspend_tui(mouse: bool) {
+    set_mouse_capture(mouse, false);
+    ratatui::restore();
+}
+
+/// Takes the terminal back after `suspend_tui`, redrawing all of it on the next frame
+pub fn resume_tui(terminal: &mut DefaultTerminal, mouse: bool) -> color_eyre::Result<()> {
+    enable_raw_mode()?;
+    crossterm::execute!(std::io::stdout(), EnterAlternateScreen)?;
+    set_mouse_capture(mouse, true);
+    terminal.clear()?;
+    Ok(())
+}
+
+/// ratatui's panic hook restores the terminal but knows nothing of the mouse, so capture is
+/// turned off before it runs. Otherwise the shell would be left printing mouse escapes.
+fn restore_mouse_on_panic(mouse: bool) {
+    if !mouse {
+        return;
+    }
+
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = crossterm::execute!(std::io::stdout(), DisableMouseCapture);
+        hook(info);
+    }));
+}
+
+fn set_mouse_capture(mouse: bool, enable: bool) {
+    if !mouse {
+        return;
+    }
+
+    let result = if enable {
+        crossterm::execute!(std::io::stdout(), EnableMouseCapture)
+    } else {
+        crossterm::execute!(std::io::stdout(), DisableMouseCapture)
+    };
+
+    if let Err(e) = result {
+        error!("Couldn't change mouse capture: {e}");
+    }
+}
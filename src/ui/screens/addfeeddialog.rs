@@ -0,0 +1,393 @@
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+
+use color_eyre::eyre::Result;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Alignment, Constraint, Layout, Margin, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Padding, Paragraph, Wrap};
+
+use crate::app::AppWorkStatus;
+use crate::core::feed::{feedentry::FeedEntry, feedparser};
+use crate::core::library::feeditem::FeedItem;
+use crate::core::library::feedlibrary::FeedLibrary;
+use crate::core::library::settings::theme::Theme;
+use crate::core::ui::appscreen::{AppScreen, AppScreenEvent};
+use crate::core::ui::dialog::Dialog;
+use crate::core::ui::instructiondetails::ScreenInstructions;
+use crate::core::ui::keymap::{Action, KeyContext, Keymap, PendingKeys};
+use crate::ui::states::categorypickerstate::CategoryPickerState;
+use crate::ui::states::textinputstate::TextInputState;
+use crate::ui::tools::widgets;
+
+const PREVIEW_ENTRIES: usize = 5;
+
+/// A feed fetched by the dialog, kept around so saving doesn't need another request
+struct FetchedFeed {
+    feed: FeedItem,
+    text: Option<String>,
+    entries: Vec<FeedEntry>,
+}
+
+enum AddStep {
+    Url,
+    Fetching(Receiver<color_eyre::Result<FetchedFeed>>),
+    Preview(Box<FetchedFeed>),
+}
+
+/// Adds a new feed: takes an URL, fetches it in the background and shows a preview of the
+/// discovered feed before saving it into the chosen category.
+pub struct AddFeedDialog {
+    library: Rc<RefCell<FeedLibrary>>,
+    keymap: Rc<Keymap>,
+    pending_keys: PendingKeys,
+    url: TextInputState,
+    picker: CategoryPickerState,
+    step: AddStep,
+    error: Option<String>,
+}
+
+impl AddFeedDialog {
+    pub fn new(library: Rc<RefCell<FeedLibrary>>, keymap: Rc<Keymap>, category: String) -> Self {
+        let picker = CategoryPickerState::new(&library.borrow(), &category);
+
+        Self {
+            library,
+            keymap,
+            pending_keys: PendingKeys::default(),
+            url: TextInputState::new(""),
+            picker,
+            step: AddStep::Url,
+            error: None,
+        }
+    }
+
+    fn fetch(&mut self) {
+        let url = self.url.value.trim().to_string();
+        if url.is_empty() {
+            return;
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let result = feedparser::get_feed_with_data(&url).and_then(|(feed, text)| {
+                let mut entries = match text.as_ref() {
+                    Some(text) => feedparser::get_feed_entries_doc(text, &feed.author)?,
+                    None => feedparser::get_feed_entries(&feed)?,
+                };
+                entries.sort_by_key(|e| std::cmp::Reverse(e.date));
+                entries.truncate(PREVIEW_ENTRIES);
+
+                Ok(FetchedFeed {
+                    feed,
+                    text,
+                    entries,
+                })
+            });
+
+            // the dialog may have been closed in the meantime, so the result is just dropped
+            let _ = sender.send(result);
+        });
+
+        self.error = None;
+        self.step = AddStep::Fetching(receiver);
+    }
+
+    fn check_fetch(&mut self) {
+        let AddStep::Fetching(receiver) = &self.step else {
+            return;
+        };
+
+        match receiver.try_recv() {
+            Ok(Ok(fetched)) => self.step = AddStep::Preview(Box::new(fetched)),
+            Ok(Err(e)) => {
+                self.error = Some(e.to_string());
+                self.step = AddStep::Url;
+            }
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Disconnected) => {
+                self.error = Some(String::from("Couldn't fetch the feed"));
+                self.step = AddStep::Url;
+            }
+        }
+    }
+
+    fn apply(&mut self) -> Result<AppScreenEvent> {
+        let AddStep::Preview(fetched) = &self.step else {
+            return Ok(AppScreenEvent::None);
+        };
+
+        let mut feed = fetched.feed.clone();
+        feed.category = self.picker.value().to_string();
+
+        let result = {
+            let mut library = self.library.borrow_mut();
+            library
+                .add_feed(feed, fetched.text.clone())
+                .and_then(|_| library.reload_categories())
+        };
+
+        match result {
+            Ok(()) => Ok(AppScreenEvent::CloseDialog),
+            Err(e) => {
+                self.error = Some(e.to_string());
+                Ok(AppScreenEvent::None)
+            }
+        }
+    }
+
+    fn render_preview(
+        &self,
+        frame: &mut ratatui::Frame,
+        area: Rect,
+        fetched: &FetchedFeed,
+        theme: &Theme,
+    ) {
+        let layout = Layout::vertical([
+            Constraint::Length(4),
+            Constraint::Length(1),
+            Constraint::Length(PREVIEW_ENTRIES as u16 + 1),
+            Constraint::Length(1),
+            Constraint::Length(3),
+            Constraint::Fill(1),
+        ])
+        .split(area);
+
+        let label_style = Style::new().fg(Color::from_u32(theme.base[0x4]));
+
+        let info = Paragraph::new(vec![
+            Line::from(Span::styled(
+                format!("\u{f09e}  {}", fetched.feed.title),
+                Style::new().fg(Color::from_u32(theme.base[0x5])),
+            )),
+            Line::from(Span::styled(fetched.feed.description.as_str(), label_style)),
+        ])
+        .wrap(Wrap { trim: true })
+        .block(Block::new().padding(Padding::top(1)));
+
+        let entries: Vec<Line> = if fetched.entries.is_empty() {
+            vec![Line::from(Span::styled("No entries yet", label_style))]
+        } else {
+            fetched
+                .entries
+                .iter()
+                .map(|entry| {
+                    Line::from(vec![
+                        Span::styled(
+                            format!(
+                                "{} ",
+                                entry.date.with_timezone(&chrono::Local).format("%Y-%m-%d")
+                            ),
+                            label_style,
+                        ),
+                        Span::styled(
+                            entry.title.as_str(),
+                            Style::new().fg(Color::from_u32(theme.base[0x5])),
+                        ),
+                    ])
+                })
+                .collect()
+        };
+
+        frame.render_widget(info, layout[0]);
+        frame.render_widget(
+            Paragraph::new("Latest entries").style(label_style),
+            layout[1],
+        );
+        frame.render_widget(
+            Paragraph::new(entries).block(Block::new().padding(Padding::left(1))),
+            layout[2],
+        );
+        frame.render_widget(Paragraph::new("Category").style(label_style), layout[3]);
+        widgets::text_input(frame, layout[4], &self.picker.input, theme, true);
+        widgets::category_list(frame, layout[5], &self.picker, theme);
+    }
+}
+
+impl Dialog for AddFeedDialog {
+    fn get_size(&self) -> Rect {
+        Rect::new(80, 36, 0, 0)
+    }
+
+    fn as_screen(&self) -> &dyn AppScreen {
+        self
+    }
+
+    fn as_screen_mut(&mut self) -> &mut dyn AppScreen {
+        self
+    }
+}
+
+impl AppScreen for AddFeedDialog {
+    fn start(&mut self) {}
+
+    fn quit(&mut self) {}
+
+    fn pause(&mut self) {}
+
+    fn unpause(&mut self) {}
+
+    fn render(&mut self, frame: &mut ratatui::Frame, area: Rect) {
+        self.check_fetch();
+
+        let theme = {
+            let library = self.library.borrow();
+            library.settings.get_theme().unwrap().clone()
+        };
+
+        let layout = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Length(1),
+            Constraint::Length(3),
+            Constraint::Fill(1),
+            Constraint::Length(2),
+        ])
+        .split(area.inner(Margin::new(2, 1)));
+
+        let title = Paragraph::new(self.get_title())
+            .style(Style::new().fg(Color::from_u32(theme.base[0x8])))
+            .alignment(Alignment::Center);
+
+        let label_style = Style::new().fg(Color::from_u32(theme.base[0x4]));
+
+        frame.render_widget(title, layout[0]);
+        frame.render_widget(Paragraph::new("Feed URL").style(label_style), layout[1]);
+        widgets::text_input(
+            frame,
+            layout[2],
+            &self.url,
+            &theme,
+            matches!(self.step, AddStep::Url),
+        );
+
+        match &self.step {
+            AddStep::Url => {}
+            AddStep::Fetching(_) => {
+                let fetching = Paragraph::new("Fetching feed...")
+                    .style(label_style)
+                    .alignment(Alignment::Center)
+                    .block(Block::new().padding(Padding::top(1)));
+                frame.render_widget(fetching, layout[3]);
+            }
+            AddStep::Preview(fetched) => {
+                self.render_preview(frame, layout[3], fetched, &theme);
+            }
+        }
+
+        if let Some(error) = &self.error {
+            let error = Paragraph::new(error.as_str())
+                .style(Style::new().fg(Color::from_u32(theme.base[0x8])))
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true })
+                .block(Block::new().padding(Padding::top(1)));
+            frame.render_widget(error, layout[4]);
+        }
+    }
+
+    fn handle_event(&mut self, event: Event) -> Result<AppScreenEvent> {
+        match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => self.handle_keypress(key),
+            Event::Mouse(_) => Ok(AppScreenEvent::None),
+            Event::Resize(_, _) => Ok(AppScreenEvent::None),
+            _ => Ok(AppScreenEvent::None),
+        }
+    }
+
+    fn handle_keypress(&mut self, key: KeyEvent) -> Result<AppScreenEvent> {
+        let actions = self
+            .keymap
+            .input_actions(KeyContext::Dialog, &mut self.pending_keys, key);
+        let action = actions.first().copied();
+
+        match &self.step {
+            AddStep::Url => match action {
+                Some(Action::Close) => Ok(AppScreenEvent::CloseDialog),
+                Some(Action::Confirm) => {
+                    self.fetch();
+                    Ok(AppScreenEvent::None)
+                }
+                _ => {
+                    if self.url.handle_key(key) {
+                        self.error = None;
+                    }
+                    Ok(AppScreenEvent::None)
+                }
+            },
+            AddStep::Fetching(_) => {
+                if action == Some(Action::Close) {
+                    // dropping the receiver discards whatever the fetch ends up returning
+                    self.step = AddStep::Url;
+                }
+                Ok(AppScreenEvent::None)
+            }
+            AddStep::Preview(_) => match action {
+                _ if matches!(key.code, KeyCode::Tab | KeyCode::BackTab) => {
+                    self.error = None;
+                    self.step = AddStep::Url;
+                    Ok(AppScreenEvent::None)
+                }
+                Some(Action::Close) => Ok(AppScreenEvent::CloseDialog),
+                Some(Action::Confirm) => self.apply(),
+                Some(Action::MoveDown) => {
+                    self.picker.select_next();
+                    self.error = None;
+                    Ok(AppScreenEvent::None)
+                }
+                Some(Action::MoveUp) => {
+                    self.picker.select_previous();
+                    self.error = None;
+                    Ok(AppScreenEvent::None)
+                }
+                _ => {
+                    if self.picker.handle_key(key) {
+                        self.error = None;
+                    }
+                    Ok(AppScreenEvent::None)
+                }
+            },
+        }
+    }
+
+    fn get_work_status(&self) -> AppWorkStatus {
+        AppWorkStatus::None
+    }
+
+    fn get_title(&self) -> String {
+        String::from("Add feed")
+    }
+
+    fn get_instructions(&self) -> String {
+        match self.step {
+            AddStep::Url => self.keymap.status_line(
+                KeyContext::Dialog,
+                &[
+                    (&[Action::Confirm], "fetch feed"),
+                    (&[Action::Close], "cancel"),
+                ],
+            ),
+            AddStep::Fetching(_) => self
+                .keymap
+                .status_line(KeyContext::Dialog, &[(&[Action::Close], "stop fetching")]),
+            AddStep::Preview(_) => {
+                let keys = self.keymap.status_line(
+                    KeyContext::Dialog,
+                    &[
+                        (&[Action::MoveDown, Action::MoveUp], "pick category"),
+                        (&[Action::Confirm], "add feed"),
+                        (&[Action::Close], "cancel"),
+                    ],
+                );
+                format!("Tab: change URL | {keys}")
+            }
+        }
+    }
+
+    fn get_full_instructions(&self) -> ScreenInstructions {
+        ScreenInstructions::empty()
+    }
+}
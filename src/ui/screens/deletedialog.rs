@@ -0,0 +1,164 @@
+use std::{cell::RefCell, rc::Rc};
+
+use color_eyre::eyre::Result;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Alignment, Constraint, Layout, Margin, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::widgets::{Paragraph, Wrap};
+
+use crate::app::AppWorkStatus;
+use crate::core::library::feedlibrary::FeedLibrary;
+use crate::core::ui::appscreen::{AppScreen, AppScreenEvent};
+use crate::core::ui::dialog::Dialog;
+use crate::core::ui::instructiondetails::ScreenInstructions;
+
+pub enum DeleteTarget {
+    Category(String),
+    /// title, category and slug of the feed
+    Feed(String, String, String),
+}
+
+pub struct DeleteDialog {
+    library: Rc<RefCell<FeedLibrary>>,
+    target: DeleteTarget,
+    error: Option<String>,
+}
+
+impl DeleteDialog {
+    pub fn new(library: Rc<RefCell<FeedLibrary>>, target: DeleteTarget) -> Self {
+        Self {
+            library,
+            target,
+            error: None,
+        }
+    }
+
+    fn target_title(&self) -> String {
+        match &self.target {
+            DeleteTarget::Category(title) => title.clone(),
+            DeleteTarget::Feed(title, category, _) => format!("{category}/{title}"),
+        }
+    }
+
+    fn apply(&mut self) -> Result<AppScreenEvent> {
+        let result = match &self.target {
+            DeleteTarget::Category(title) => self.library.borrow_mut().delete_category(title),
+            DeleteTarget::Feed(_, category, slug) => {
+                let mut library = self.library.borrow_mut();
+                library
+                    .delete_feed(slug, category)
+                    .and_then(|_| library.reload_categories())
+            }
+        };
+
+        match result {
+            Ok(()) => Ok(AppScreenEvent::CloseDialog),
+            Err(e) => {
+                self.error = Some(e.to_string());
+                Ok(AppScreenEvent::None)
+            }
+        }
+    }
+}
+
+impl Dialog for DeleteDialog {
+    fn get_size(&self) -> Rect {
+        Rect::new(60, 12, 0, 0)
+    }
+
+    fn as_screen(&self) -> &dyn AppScreen {
+        self
+    }
+
+    fn as_screen_mut(&mut self) -> &mut dyn AppScreen {
+        self
+    }
+}
+
+impl AppScreen for DeleteDialog {
+    fn start(&mut self) {}
+
+    fn quit(&mut self) {}
+
+    fn pause(&mut self) {}
+
+    fn unpause(&mut self) {}
+
+    fn render(&mut self, frame: &mut ratatui::Frame, area: Rect) {
+        let theme = {
+            let library = self.library.borrow();
+            library.settings.get_theme().unwrap().clone()
+        };
+
+        let layout = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Fill(1),
+            Constraint::Length(2),
+        ])
+        .split(area.inner(Margin::new(2, 1)));
+
+        let title = Paragraph::new(self.get_title())
+            .style(Style::new().fg(Color::from_u32(theme.base[0x8])))
+            .alignment(Alignment::Center);
+
+        let question = Paragraph::new(format!(
+            "Are you sure you want to delete '{}'? That can't be reverted. [y/N]",
+            self.target_title()
+        ))
+        .style(Style::new().fg(Color::from_u32(theme.base[0x5])))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+
+        frame.render_widget(title, layout[0]);
+        frame.render_widget(question, layout[1]);
+
+        if let Some(error) = &self.error {
+            let error = Paragraph::new(error.as_str())
+                .style(Style::new().fg(Color::from_u32(theme.base[0x8])))
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true });
+            frame.render_widget(error, layout[2]);
+        }
+    }
+
+    fn handle_event(&mut self, event: Event) -> Result<AppScreenEvent> {
+        match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => self.handle_keypress(key),
+            Event::Mouse(_) => Ok(AppScreenEvent::None),
+            Event::Resize(_, _) => Ok(AppScreenEvent::None),
+            _ => Ok(AppScreenEvent::None),
+        }
+    }
+
+    fn handle_keypress(&mut self, key: KeyEvent) -> Result<AppScreenEvent> {
+        match (key.modifiers, key.code) {
+            (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => {
+                Ok(AppScreenEvent::CloseDialog)
+            }
+            (_, KeyCode::Char('y') | KeyCode::Char('Y')) => self.apply(),
+            (_, KeyCode::Esc | KeyCode::Enter | KeyCode::Char('n') | KeyCode::Char('q')) => {
+                Ok(AppScreenEvent::CloseDialog)
+            }
+            _ => Ok(AppScreenEvent::None),
+        }
+    }
+
+    fn get_work_status(&self) -> AppWorkStatus {
+        AppWorkStatus::None
+    }
+
+    fn get_title(&self) -> String {
+        match self.target {
+            DeleteTarget::Category(_) => String::from("Delete category"),
+            DeleteTarget::Feed(..) => String::from("Delete feed"),
+        }
+    }
+
+    fn get_instructions(&self) -> String {
+        String::from("y: delete | n/Esc: cancel")
+    }
+
+    fn get_full_instructions(&self) -> ScreenInstructions {
+        ScreenInstructions::empty()
+    }
+}
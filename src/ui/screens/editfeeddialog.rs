@@ -0,0 +1,255 @@
+use std::{cell::RefCell, rc::Rc};
+
+use color_eyre::eyre::Result;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Alignment, Constraint, Layout, Margin, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::widgets::{Block, Padding, Paragraph, Wrap};
+
+use crate::app::AppWorkStatus;
+use crate::core::library::feeditem::FeedItem;
+use crate::core::library::feedlibrary::FeedLibrary;
+use crate::core::ui::appscreen::{AppScreen, AppScreenEvent};
+use crate::core::ui::dialog::Dialog;
+use crate::core::ui::instructiondetails::ScreenInstructions;
+use crate::core::ui::keymap::{Action, KeyContext, Keymap, PendingKeys};
+use crate::ui::states::categorypickerstate::CategoryPickerState;
+use crate::ui::states::textinputstate::TextInputState;
+use crate::ui::tools::widgets;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum EditField {
+    Title,
+    Url,
+    Category,
+}
+
+impl EditField {
+    fn next(self) -> Self {
+        match self {
+            EditField::Title => EditField::Url,
+            EditField::Url => EditField::Category,
+            EditField::Category => EditField::Title,
+        }
+    }
+
+    fn previous(self) -> Self {
+        match self {
+            EditField::Title => EditField::Category,
+            EditField::Url => EditField::Title,
+            EditField::Category => EditField::Url,
+        }
+    }
+}
+
+/// Edits the title, feed URL and category of a feed
+pub struct EditFeedDialog {
+    library: Rc<RefCell<FeedLibrary>>,
+    keymap: Rc<Keymap>,
+    pending_keys: PendingKeys,
+    feed: FeedItem,
+    title: TextInputState,
+    url: TextInputState,
+    picker: CategoryPickerState,
+    focus: EditField,
+    error: Option<String>,
+}
+
+impl EditFeedDialog {
+    pub fn new(library: Rc<RefCell<FeedLibrary>>, keymap: Rc<Keymap>, feed: FeedItem) -> Self {
+        let picker = CategoryPickerState::new(&library.borrow(), &feed.category);
+
+        Self {
+            library,
+            keymap,
+            pending_keys: PendingKeys::default(),
+            title: TextInputState::new(feed.title.clone()),
+            url: TextInputState::new(feed.feed_url.clone()),
+            picker,
+            feed,
+            focus: EditField::Title,
+            error: None,
+        }
+    }
+
+    fn apply(&mut self) -> Result<AppScreenEvent> {
+        let result = self.library.borrow_mut().edit_feed(
+            &self.feed,
+            &self.title.value,
+            &self.url.value,
+            self.picker.value(),
+        );
+
+        match result {
+            Ok(_) => Ok(AppScreenEvent::CloseDialog),
+            Err(e) => {
+                self.error = Some(e.to_string());
+                Ok(AppScreenEvent::None)
+            }
+        }
+    }
+}
+
+impl Dialog for EditFeedDialog {
+    fn get_size(&self) -> Rect {
+        Rect::new(70, 30, 0, 0)
+    }
+
+    fn as_screen(&self) -> &dyn AppScreen {
+        self
+    }
+
+    fn as_screen_mut(&mut self) -> &mut dyn AppScreen {
+        self
+    }
+}
+
+impl AppScreen for EditFeedDialog {
+    fn start(&mut self) {}
+
+    fn quit(&mut self) {}
+
+    fn pause(&mut self) {}
+
+    fn unpause(&mut self) {}
+
+    fn render(&mut self, frame: &mut ratatui::Frame, area: Rect) {
+        let theme = {
+            let library = self.library.borrow();
+            library.settings.get_theme().unwrap().clone()
+        };
+
+        let layout = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Length(1),
+            Constraint::Length(3),
+            Constraint::Length(1),
+            Constraint::Length(3),
+            Constraint::Length(1),
+            Constraint::Length(3),
+            Constraint::Fill(1),
+            Constraint::Length(2),
+        ])
+        .split(area.inner(Margin::new(2, 1)));
+
+        let title = Paragraph::new(self.get_title())
+            .style(Style::new().fg(Color::from_u32(theme.base[0x8])))
+            .alignment(Alignment::Center);
+        frame.render_widget(title, layout[0]);
+
+        let label_style = Style::new().fg(Color::from_u32(theme.base[0x4]));
+        frame.render_widget(Paragraph::new("Title").style(label_style), layout[1]);
+        frame.render_widget(Paragraph::new("Feed URL").style(label_style), layout[3]);
+        frame.render_widget(Paragraph::new("Category").style(label_style), layout[5]);
+
+        widgets::text_input(
+            frame,
+            layout[2],
+            &self.title,
+            &theme,
+            self.focus == EditField::Title,
+        );
+        widgets::text_input(
+            frame,
+            layout[4],
+            &self.url,
+            &theme,
+            self.focus == EditField::Url,
+        );
+        widgets::text_input(
+            frame,
+            layout[6],
+            &self.picker.input,
+            &theme,
+            self.focus == EditField::Category,
+        );
+        widgets::category_list(frame, layout[7], &self.picker, &theme);
+
+        if let Some(error) = &self.error {
+            let error = Paragraph::new(error.as_str())
+                .style(Style::new().fg(Color::from_u32(theme.base[0x8])))
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true })
+                .block(Block::new().padding(Padding::top(1)));
+            frame.render_widget(error, layout[8]);
+        }
+    }
+
+    fn handle_event(&mut self, event: Event) -> Result<AppScreenEvent> {
+        match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => self.handle_keypress(key),
+            Event::Mouse(_) => Ok(AppScreenEvent::None),
+            Event::Resize(_, _) => Ok(AppScreenEvent::None),
+            _ => Ok(AppScreenEvent::None),
+        }
+    }
+
+    fn handle_keypress(&mut self, key: KeyEvent) -> Result<AppScreenEvent> {
+        match key.code {
+            KeyCode::Tab => {
+                self.focus = self.focus.next();
+                return Ok(AppScreenEvent::None);
+            }
+            KeyCode::BackTab => {
+                self.focus = self.focus.previous();
+                return Ok(AppScreenEvent::None);
+            }
+            _ => {}
+        }
+
+        let actions = self
+            .keymap
+            .input_actions(KeyContext::Dialog, &mut self.pending_keys, key);
+
+        match actions.first() {
+            Some(Action::Close) => Ok(AppScreenEvent::CloseDialog),
+            Some(Action::Confirm) => self.apply(),
+            Some(Action::MoveDown) if self.focus == EditField::Category => {
+                self.picker.select_next();
+                self.error = None;
+                Ok(AppScreenEvent::None)
+            }
+            Some(Action::MoveUp) if self.focus == EditField::Category => {
+                self.picker.select_previous();
+                self.error = None;
+                Ok(AppScreenEvent::None)
+            }
+            _ => {
+                let changed = match self.focus {
+                    EditField::Title => self.title.handle_key(key),
+                    EditField::Url => self.url.handle_key(key),
+                    EditField::Category => self.picker.handle_key(key),
+                };
+
+                if changed {
+                    self.error = None;
+                }
+                Ok(AppScreenEvent::None)
+            }
+        }
+    }
+
+    fn get_work_status(&self) -> AppWorkStatus {
+        AppWorkStatus::None
+    }
+
+    fn get_title(&self) -> String {
+        String::from("Edit feed")
+    }
+
+    fn get_instructions(&self) -> String {
+        let keys = self.keymap.status_line(
+            KeyContext::Dialog,
+            &[
+                (&[Action::MoveDown, Action::MoveUp], "pick category"),
+                (&[Action::Confirm], "save"),
+                (&[Action::Close], "cancel"),
+            ],
+        );
+        format!("Tab: next field | {keys}")
+    }
+
+    fn get_full_instructions(&self) -> ScreenInstructions {
+        ScreenInstructions::empty()
+    }
+}
@@ -1,7 +1,7 @@
 use std::{cell::RefCell, rc::Rc};
 
 use color_eyre::eyre::Result;
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, MouseEventKind};
 use ratatui::layout::{Alignment, Constraint, Layout, Margin, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span, Text};
@@ -12,9 +12,12 @@ use crate::core::library::feedlibrary::FeedLibrary;
 use crate::core::ui::appscreen::{AppScreen, AppScreenEvent};
 use crate::core::ui::dialog::Dialog;
 use crate::core::ui::instructiondetails::ScreenInstructions;
+use crate::core::ui::keymap::{Action, KeyContext, Keymap, PendingKeys};
 
 pub struct HelpDialog {
     library: Rc<RefCell<FeedLibrary>>,
+    keymap: Rc<Keymap>,
+    pending_keys: PendingKeys,
     instructions: ScreenInstructions,
     active_tab: usize,
     scroll: u16,
@@ -22,15 +25,35 @@ pub struct HelpDialog {
 }
 
 impl HelpDialog {
-    pub fn new(library: Rc<RefCell<FeedLibrary>>, instructions: ScreenInstructions) -> HelpDialog {
+    pub fn new(
+        library: Rc<RefCell<FeedLibrary>>,
+        keymap: Rc<Keymap>,
+        instructions: ScreenInstructions,
+    ) -> HelpDialog {
         HelpDialog {
             library,
+            keymap,
+            pending_keys: PendingKeys::default(),
             instructions,
             active_tab: 0,
             scroll: 0,
             scrollmax: 0,
         }
     }
+
+    fn run_action(&mut self, action: Action) -> Result<AppScreenEvent> {
+        match action {
+            Action::Close => return Ok(AppScreenEvent::CloseDialog),
+            _ if self.active_tab != 0 => {}
+            Action::MoveDown => self.scroll = self.scroll.saturating_add(1).min(self.scrollmax),
+            Action::MoveUp => self.scroll = self.scroll.saturating_sub(1),
+            Action::First => self.scroll = 0,
+            Action::Last => self.scroll = self.scrollmax,
+            _ => {}
+        }
+
+        Ok(AppScreenEvent::None)
+    }
 }
 
 impl Dialog for HelpDialog {
@@ -154,42 +177,45 @@ impl AppScreen for HelpDialog {
     fn handle_event(&mut self, event: Event) -> Result<AppScreenEvent> {
         match event {
             Event::Key(key) if key.kind == KeyEventKind::Press => self.handle_keypress(key),
-            Event::Mouse(_) => Ok(AppScreenEvent::None),
+            Event::Mouse(mouse) => {
+                if self.active_tab == 0 {
+                    match mouse.kind {
+                        MouseEventKind::ScrollDown => {
+                            self.scroll = self.scroll.saturating_add(1).min(self.scrollmax);
+                        }
+                        MouseEventKind::ScrollUp => {
+                            self.scroll = self.scroll.saturating_sub(1);
+                        }
+                        _ => {}
+                    }
+                }
+                Ok(AppScreenEvent::None)
+            }
             Event::Resize(_, _) => Ok(AppScreenEvent::None),
             _ => Ok(AppScreenEvent::None),
         }
     }
 
     fn handle_keypress(&mut self, key: KeyEvent) -> Result<AppScreenEvent> {
-        match (key.modifiers, key.code) {
-            (_, KeyCode::Esc | KeyCode::Char('q'))
-            | (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => {
-                Ok(AppScreenEvent::CloseDialog)
-            }
-            (_, KeyCode::Tab) => {
-                self.active_tab = (self.active_tab + 1) % 2;
-                self.scroll = 0;
-                Ok(AppScreenEvent::None)
-            }
-            (_, KeyCode::BackTab) => {
-                self.active_tab = (self.active_tab - 1) % 2;
-                self.scroll = 0;
-                Ok(AppScreenEvent::None)
-            }
-            (_, KeyCode::Down | KeyCode::Char('j')) => {
-                if self.active_tab == 0 {
-                    self.scroll = self.scroll.saturating_add(1).min(self.scrollmax);
-                }
-                Ok(AppScreenEvent::None)
-            }
-            (_, KeyCode::Up | KeyCode::Char('k')) => {
-                if self.active_tab == 0 {
-                    self.scroll = self.scroll.saturating_sub(1);
-                }
-                Ok(AppScreenEvent::None)
+        // With only two tabs, Tab and Shift+Tab both go to the other one
+        if matches!(key.code, KeyCode::Tab | KeyCode::BackTab) {
+            self.active_tab = (self.active_tab + 1) % 2;
+            self.scroll = 0;
+            return Ok(AppScreenEvent::None);
+        }
+
+        let actions = self
+            .keymap
+            .actions(KeyContext::Dialog, &mut self.pending_keys, key);
+
+        for action in actions {
+            let event = self.run_action(action)?;
+            if !matches!(event, AppScreenEvent::None) {
+                return Ok(event);
             }
-            _ => Ok(AppScreenEvent::None),
         }
+
+        Ok(AppScreenEvent::None)
     }
 
     fn get_work_status(&self) -> AppWorkStatus {
@@ -201,7 +227,14 @@ impl AppScreen for HelpDialog {
     }
 
     fn get_instructions(&self) -> String {
-        String::from("Tab: switch tabs | j/k ↑↓: scroll | Esc/q: close")
+        let keys = self.keymap.status_line(
+            KeyContext::Dialog,
+            &[
+                (&[Action::MoveDown, Action::MoveUp], "scroll"),
+                (&[Action::Close], "close"),
+            ],
+        );
+        format!("Tab: switch tabs | {keys}")
     }
 
     fn get_full_instructions(&self) -> ScreenInstructions {
@@ -0,0 +1,286 @@
+use std::{cell::RefCell, rc::Rc};
+
+use color_eyre::eyre::Result;
+use crossterm::event::{Event, KeyEvent, KeyEventKind, MouseEventKind};
+use ratatui::layout::{Alignment, Constraint, Layout, Margin, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{
+    Block, List, ListItem, ListState, Padding, Paragraph, Scrollbar, ScrollbarOrientation,
+    ScrollbarState, Wrap,
+};
+
+use crate::app::AppWorkStatus;
+use crate::core::feed::feedentry::FeedEntry;
+use crate::core::hooks::AppHooks;
+use crate::core::library::feedlibrary::FeedLibrary;
+use crate::core::ui::appscreen::{AppScreen, AppScreenEvent};
+use crate::core::ui::dialog::Dialog;
+use crate::core::ui::instructiondetails::ScreenInstructions;
+use crate::core::ui::keymap::{Action, KeyContext, Keymap, PendingKeys};
+use crate::ui::tools::links;
+use crate::ui::tools::mouse::WHEEL_STEP;
+use crate::ui::tools::tuimarkdown::MarkdownLink;
+
+/// Lists the links and images of an entry, with the same numbers as the reader shows
+pub struct LinksDialog {
+    library: Rc<RefCell<FeedLibrary>>,
+    hooks: Rc<AppHooks>,
+    keymap: Rc<Keymap>,
+    pending_keys: PendingKeys,
+    entry: FeedEntry,
+    links: Vec<MarkdownLink>,
+    liststate: ListState,
+}
+
+impl LinksDialog {
+    pub fn new(
+        library: Rc<RefCell<FeedLibrary>>,
+        hooks: Rc<AppHooks>,
+        keymap: Rc<Keymap>,
+        entry: FeedEntry,
+        links: Vec<MarkdownLink>,
+    ) -> Self {
+        Self {
+            library,
+            hooks,
+            keymap,
+            pending_keys: PendingKeys::default(),
+            entry,
+            links,
+            liststate: ListState::default().with_selected(Some(0)),
+        }
+    }
+
+    fn select_next(&mut self, step: usize) {
+        let next = self
+            .liststate
+            .selected()
+            .map(|i| (i + step).min(self.links.len().saturating_sub(1)))
+            .unwrap_or(0);
+        self.liststate.select(Some(next));
+    }
+
+    fn select_previous(&mut self, step: usize) {
+        let previous = self
+            .liststate
+            .selected()
+            .map(|i| i.saturating_sub(step))
+            .unwrap_or(0);
+        self.liststate.select(Some(previous));
+    }
+
+    fn selected(&self) -> Option<&MarkdownLink> {
+        self.liststate.selected().and_then(|i| self.links.get(i))
+    }
+
+    fn open_selected(&self) -> Result<AppScreenEvent> {
+        Ok(match self.selected() {
+            Some(link) => links::open_external_url(
+                &self.library.borrow(),
+                &self.hooks,
+                &link.url,
+                &self.entry,
+            ),
+            None => AppScreenEvent::None,
+        })
+    }
+
+    fn copy_selected(&self) -> Result<AppScreenEvent> {
+        Ok(match self.selected() {
+            Some(link) => links::copy_to_clipboard(&link.url),
+            None => AppScreenEvent::None,
+        })
+    }
+
+    fn run_action(&mut self, action: Action) -> Result<AppScreenEvent> {
+        match action {
+            Action::Close => Ok(AppScreenEvent::CloseDialog),
+            Action::MoveDown => {
+                self.select_next(1);
+                Ok(AppScreenEvent::None)
+            }
+            Action::MoveUp => {
+                self.select_previous(1);
+                Ok(AppScreenEvent::None)
+            }
+            Action::First => {
+                self.liststate.select(Some(0));
+                Ok(AppScreenEvent::None)
+            }
+            Action::Last => {
+                self.liststate
+                    .select(Some(self.links.len().saturating_sub(1)));
+                Ok(AppScreenEvent::None)
+            }
+            Action::Confirm | Action::OpenLink => self.open_selected(),
+            Action::CopyLink => self.copy_selected(),
+            _ => Ok(AppScreenEvent::None),
+        }
+    }
+}
+
+impl Dialog for LinksDialog {
+    fn get_size(&self) -> Rect {
+        Rect::new(80, 22, 0, 0)
+    }
+
+    fn as_screen(&self) -> &dyn AppScreen {
+        self
+    }
+
+    fn as_screen_mut(&mut self) -> &mut dyn AppScreen {
+        self
+    }
+}
+
+impl AppScreen for LinksDialog {
+    fn start(&mut self) {}
+
+    fn quit(&mut self) {}
+
+    fn pause(&mut self) {}
+
+    fn unpause(&mut self) {}
+
+    fn render(&mut self, frame: &mut ratatui::Frame, area: Rect) {
+        let theme = {
+            let library = self.library.borrow();
+            library.settings.get_theme().unwrap().clone()
+        };
+
+        let contentlayout = Layout::vertical([Constraint::Length(2), Constraint::Fill(1)])
+            .split(area.inner(Margin::new(2, 1)));
+
+        let title = Paragraph::new(self.get_title())
+            .style(Style::new().fg(Color::from_u32(theme.base[0x8])))
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true });
+
+        frame.render_widget(title, contentlayout[0]);
+
+        if self.links.is_empty() {
+            let empty = Paragraph::new("This entry has no links")
+                .style(Style::new().fg(Color::from_u32(theme.base[0x4])))
+                .alignment(Alignment::Center);
+            frame.render_widget(empty, contentlayout[1]);
+            return;
+        }
+
+        let chunks = Layout::horizontal([Constraint::Fill(1), Constraint::Length(1)])
+            .split(contentlayout[1]);
+
+        let items: Vec<ListItem> = self
+            .links
+            .iter()
+            .enumerate()
+            .map(|(i, link)| {
+                let icon = if link.is_image {
+                    "\u{f03e}"
+                } else {
+                    "\u{f0c1}"
+                };
+                let text = if link.text.is_empty() || link.text == link.url {
+                    String::new()
+                } else {
+                    format!("{}  ", link.text)
+                };
+
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{:>3} ", i + 1),
+                        Style::new().fg(Color::from_u32(theme.base[0xa])),
+                    ),
+                    Span::from(format!("{icon} {text}")),
+                    Span::styled(
+                        link.url.as_str(),
+                        Style::new().fg(Color::from_u32(theme.base[0xd])),
+                    ),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .style(
+                        Style::default()
+                            .fg(Color::from_u32(theme.base[0x5]))
+                            .bg(Color::from_u32(theme.base[0x1])),
+                    )
+                    .padding(Padding::new(1, 1, 1, 1)),
+            )
+            .highlight_style(
+                Style::default()
+                    .fg(Color::from_u32(theme.base[0x2]))
+                    .bg(Color::from_u32(theme.base[0x8])),
+            );
+
+        frame.render_stateful_widget(list, chunks[0], &mut self.liststate);
+
+        // Scrollbar
+        let mut scrollbarstate = ScrollbarState::new(self.links.len().saturating_sub(1))
+            .position(self.liststate.selected().unwrap_or(0));
+        let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight).style(
+            Style::new()
+                .fg(Color::from_u32(theme.base[3]))
+                .bg(Color::from_u32(theme.base[2])),
+        );
+        frame.render_stateful_widget(scrollbar, chunks[1], &mut scrollbarstate);
+    }
+
+    fn handle_event(&mut self, event: Event) -> Result<AppScreenEvent> {
+        match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => self.handle_keypress(key),
+            Event::Mouse(mouse) => {
+                match mouse.kind {
+                    MouseEventKind::ScrollDown => self.select_next(WHEEL_STEP),
+                    MouseEventKind::ScrollUp => self.select_previous(WHEEL_STEP),
+                    _ => {}
+                }
+                Ok(AppScreenEvent::None)
+            }
+            Event::Resize(_, _) => Ok(AppScreenEvent::None),
+            _ => Ok(AppScreenEvent::None),
+        }
+    }
+
+    fn handle_keypress(&mut self, key: KeyEvent) -> Result<AppScreenEvent> {
+        let actions = self
+            .keymap
+            .actions(KeyContext::Dialog, &mut self.pending_keys, key);
+
+        for action in actions {
+            let event = self.run_action(action)?;
+            if !matches!(event, AppScreenEvent::None) {
+                return Ok(event);
+            }
+        }
+
+        Ok(AppScreenEvent::None)
+    }
+
+    fn get_work_status(&self) -> AppWorkStatus {
+        AppWorkStatus::None
+    }
+
+    fn get_title(&self) -> String {
+        format!("Links ({})", self.links.len())
+    }
+
+    fn get_instructions(&self) -> String {
+        self.keymap.status_line(
+            KeyContext::Dialog,
+            &[
+                (&[Action::MoveDown, Action::MoveUp], "select"),
+                (&[Action::Confirm, Action::OpenLink], "open"),
+                (&[Action::CopyLink], "copy"),
+                (&[Action::Close], "close"),
+            ],
+        )
+    }
+
+    fn get_full_instructions(&self) -> ScreenInstructions {
+        self.keymap.instructions(&[KeyContext::Dialog])
+    }
+}
@@ -1,7 +1,7 @@
 use std::{cell::RefCell, rc::Rc};
 
 use color_eyre::Result;
-use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::event::{Event, KeyEventKind, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
     layout::{Constraint, Layout, Rect},
     style::{Color, Style, Stylize},
@@ -12,21 +12,37 @@ use tracing::error;
 use crate::{
     app::AppWorkStatus,
     core::{
+        defs,
         feed::feedentry::FeedEntry,
         hooks::AppHooks,
         library::feedlibrary::FeedLibrary,
         ui::{
             appscreen::{AppScreen, AppScreenEvent},
-            instructiondetails::{InstructionCategory, InstructionDetail, ScreenInstructions},
+            external::ExternalView,
+            graphics::ImageProtocol,
+            instructiondetails::ScreenInstructions,
+            keymap::{Action, KeyContext, Keymap, PendingKeys},
             notification::{AppNotification, NotificationPriority},
         },
     },
     ui::{
-        screens::{readerscreen::ReaderScreen, themedialog::ThemeDialog, urldialog::UrlDialog},
+        screens::{
+            addfeeddialog::AddFeedDialog,
+            deletedialog::{DeleteDialog, DeleteTarget},
+            editfeeddialog::EditFeedDialog,
+            movedialog::MoveDialog,
+            readerscreen::ReaderScreen,
+            renamedialog::RenameDialog,
+            themedialog::ThemeDialog,
+        },
         states::{
-            feedentrystate::FeedEntryState,
+            feedentrystate::{ENTRY_ITEM_HEIGHT, FeedEntryState},
             feedtreestate::{FeedItemInfo, FeedTreeState},
         },
+        tools::{
+            links,
+            mouse::{self, ClickTracker},
+        },
     },
 };
 
@@ -44,16 +60,54 @@ pub struct MainScreen {
     feedentrystate: FeedEntryState,
     inputstate: MainInputState,
     hooks: Rc<AppHooks>,
+    keymap: Rc<Keymap>,
+    images: ImageProtocol,
+    pending_keys: PendingKeys,
+    // Where the lists were last drawn, to find what's under the mouse
+    tree_area: Rect,
+    tree_list_area: Rect,
+    tree_offset: usize,
+    entries_area: Rect,
+    entries_list_area: Rect,
+    entries_offset: usize,
+    clicks: ClickTracker,
+    dragging_divider: bool,
+    // The selection of the last session is restored once the lists are first filled
+    restore_session: bool,
 }
 
 impl MainScreen {
-    pub fn new(library: Rc<RefCell<FeedLibrary>>, hooks: Rc<AppHooks>) -> Self {
+    pub fn new(
+        library: Rc<RefCell<FeedLibrary>>,
+        hooks: Rc<AppHooks>,
+        keymap: Rc<Keymap>,
+        images: ImageProtocol,
+    ) -> Self {
         Self {
             library,
             feedtreestate: FeedTreeState::new(),
             feedentrystate: FeedEntryState::new(),
             inputstate: MainInputState::Menu,
             hooks,
+            keymap,
+            images,
+            pending_keys: PendingKeys::default(),
+            tree_area: Rect::default(),
+            tree_list_area: Rect::default(),
+            tree_offset: 0,
+            entries_area: Rect::default(),
+            entries_list_area: Rect::default(),
+            entries_offset: 0,
+            clicks: ClickTracker::default(),
+            dragging_divider: false,
+            restore_session: true,
+        }
+    }
+
+    fn key_context(&self) -> KeyContext {
+        match self.inputstate {
+            MainInputState::Menu => KeyContext::Tree,
+            MainInputState::Content => KeyContext::Entries,
         }
     }
 
@@ -89,39 +143,116 @@ impl MainScreen {
             _ => vec![],
         };
 
-        let mut lib = self.library.borrow_mut();
-        for entry in entries.iter() {
-            lib.data.set_entry_seen(entry);
+        self.library.borrow_mut().set_entries_seen(&entries);
+    }
+
+    fn open_external_url(&self, url: &str, entry: &FeedEntry) -> Result<AppScreenEvent> {
+        Ok(links::open_external_url(
+            &self.library.borrow(),
+            &self.hooks,
+            url,
+            entry,
+        ))
+    }
+
+    fn open_theme_selector(&self) -> Result<AppScreenEvent> {
+        Ok(AppScreenEvent::OpenDialog(Box::new(ThemeDialog::new(
+            self.library.clone(),
+            self.keymap.clone(),
+        ))))
+    }
+
+    /// Reorganising the library while the updater is writing entries would recreate the old
+    /// directories, so these actions wait until it's done.
+    fn organize_blocked(&self) -> Option<AppScreenEvent> {
+        if self.library.borrow().updater.is_some() {
+            Some(AppScreenEvent::Notify(AppNotification::new(
+                "Wait for the update to finish",
+                NotificationPriority::High,
+            )))
+        } else {
+            None
         }
-        lib.bump_generation();
     }
 
-    fn open_external_url(&self, url: &str) -> Result<AppScreenEvent> {
-        if self.hooks.run_open_link(url) {
-            return Ok(AppScreenEvent::Notify(AppNotification::new(
-                "Link opened externally with user hook",
-                NotificationPriority::Low,
-            )));
+    fn open_add_dialog(&self) -> Result<AppScreenEvent> {
+        if let Some(event) = self.organize_blocked() {
+            return Ok(event);
         }
 
-        match open::that(url) {
-            Ok(_) => Ok(AppScreenEvent::Notify(AppNotification::new(
-                "Link opened externally",
-                NotificationPriority::Low,
+        let category = match self.feedtreestate.get_selected() {
+            Some(FeedItemInfo::Category(t)) => t.clone(),
+            Some(FeedItemInfo::Item(_, c, _)) => c.clone(),
+            _ => String::from(defs::DATA_CATEGORY_DEFAULT),
+        };
+
+        Ok(AppScreenEvent::OpenDialog(Box::new(AddFeedDialog::new(
+            self.library.clone(),
+            self.keymap.clone(),
+            category,
+        ))))
+    }
+
+    fn open_rename_dialog(&self) -> Result<AppScreenEvent> {
+        if let Some(event) = self.organize_blocked() {
+            return Ok(event);
+        }
+
+        match self.feedtreestate.get_selected() {
+            Some(FeedItemInfo::Category(t)) => Ok(AppScreenEvent::OpenDialog(Box::new(
+                RenameDialog::new(self.library.clone(), self.keymap.clone(), t.clone()),
             ))),
-            Err(_) => {
-                error!("Couldn't invoke system browser");
-                Ok(AppScreenEvent::OpenDialog(Box::new(UrlDialog::new(
-                    url.to_string(),
+            Some(FeedItemInfo::Item(_, c, s)) => {
+                let feed = self.library.borrow().get_feed(s, c).cloned();
+                match feed {
+                    Some(feed) => Ok(AppScreenEvent::OpenDialog(Box::new(EditFeedDialog::new(
+                        self.library.clone(),
+                        self.keymap.clone(),
+                        feed,
+                    )))),
+                    None => Ok(AppScreenEvent::None),
+                }
+            }
+            _ => Ok(AppScreenEvent::None),
+        }
+    }
+
+    fn open_move_dialog(&self) -> Result<AppScreenEvent> {
+        if let Some(event) = self.organize_blocked() {
+            return Ok(event);
+        }
+
+        match self.feedtreestate.get_selected() {
+            Some(FeedItemInfo::Item(t, c, s)) => {
+                Ok(AppScreenEvent::OpenDialog(Box::new(MoveDialog::new(
+                    self.library.clone(),
+                    self.keymap.clone(),
+                    t.clone(),
+                    c.clone(),
+                    s.clone(),
                 ))))
             }
+            _ => Ok(AppScreenEvent::None),
         }
     }
 
-    fn open_theme_selector(&self) -> Result<AppScreenEvent> {
-        Ok(AppScreenEvent::OpenDialog(Box::new(ThemeDialog::new(
-            self.library.clone(),
-        ))))
+    fn open_delete_dialog(&self) -> Result<AppScreenEvent> {
+        if let Some(event) = self.organize_blocked() {
+            return Ok(event);
+        }
+
+        match self.feedtreestate.get_selected() {
+            Some(FeedItemInfo::Category(t)) => Ok(AppScreenEvent::OpenDialog(Box::new(
+                DeleteDialog::new(self.library.clone(), DeleteTarget::Category(t.clone())),
+            ))),
+            Some(FeedItemInfo::Item(t, c, s)) => {
+                Ok(AppScreenEvent::OpenDialog(Box::new(DeleteDialog::new(
+                    self.library.clone(),
+                    DeleteTarget::Feed(t.clone(), c.clone(), s.clone()),
+                ))))
+            }
+            _ => Ok(AppScreenEvent::None),
+        }
     }
 
     fn toggle_read_later(&mut self, entry: &FeedEntry) -> bool {
@@ -140,6 +271,47 @@ impl MainScreen {
         }
     }
 
+    fn save_session(&self) {
+        let mut library = self.library.borrow_mut();
+        let entry = self
+            .feedentrystate
+            .get_selected()
+            .map(|entry| library.settings.session.entry_key(&entry.filepath));
+
+        let session = &mut library.settings.session;
+        session.node = self.feedtreestate.selected_node();
+        session.entry = entry;
+        if let Err(e) = session.save() {
+            error!("Couldn't save the session: {:?}", e);
+        }
+    }
+
+    /// Selects the node and entry that were selected when bulletty last closed
+    fn restore_session(&mut self) {
+        self.restore_session = false;
+
+        let (node, entry) = {
+            let session = &self.library.borrow().settings.session;
+            (session.node.clone(), session.entry.clone())
+        };
+        if let Some(node) = node
+            && !self.feedtreestate.select_node(&node)
+        {
+            return;
+        }
+
+        let mut library = self.library.borrow_mut();
+        self.feedentrystate
+            .update(&mut library, &self.feedtreestate);
+        if let Some(index) =
+            self.feedentrystate.entries.iter().position(|e| {
+                entry.as_ref() == Some(&library.settings.session.entry_key(&e.filepath))
+            })
+        {
+            self.feedentrystate.select_index(index);
+        }
+    }
+
     fn increase_tree_width(&mut self) -> color_eyre::Result<()> {
         let mut l = self.library.borrow_mut();
         l.settings.appearance.main_screen_tree_width = l
@@ -161,6 +333,236 @@ impl MainScreen {
             .min(100);
         l.settings.appearance.save()
     }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) -> Result<AppScreenEvent> {
+        let (column, row) = (mouse.column, mouse.row);
+
+        match mouse.kind {
+            MouseEventKind::ScrollDown | MouseEventKind::ScrollUp => {
+                let down = mouse.kind == MouseEventKind::ScrollDown;
+                if mouse::contains(self.tree_area, column, row) {
+                    if down {
+                        self.feedtreestate.select_next();
+                    } else {
+                        self.feedtreestate.select_previous();
+                    }
+                } else if mouse::contains(self.entries_area, column, row) {
+                    if down {
+                        self.feedentrystate.select_next();
+                    } else {
+                        self.feedentrystate.select_previous();
+                    }
+                }
+                Ok(AppScreenEvent::None)
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                // The divider is the padding on both sides of the tree's right edge
+                let divider = self.tree_area.right();
+                if (divider.saturating_sub(1)..=divider).contains(&column)
+                    && (self.tree_area.top()..self.tree_area.bottom()).contains(&row)
+                {
+                    self.dragging_divider = true;
+                    return Ok(AppScreenEvent::None);
+                }
+
+                let double = self.clicks.click(column, row);
+
+                if mouse::contains(self.tree_list_area, column, row) {
+                    let index = self.tree_offset + (row - self.tree_list_area.y) as usize;
+                    if self.feedtreestate.select_index(index) {
+                        self.inputstate = MainInputState::Menu;
+                        if double {
+                            return self.run_action(Action::FocusEntries);
+                        }
+                    }
+                } else if mouse::contains(self.entries_list_area, column, row) {
+                    let index = self.entries_offset
+                        + (row - self.entries_list_area.y) as usize / ENTRY_ITEM_HEIGHT;
+                    if self.feedentrystate.select_index(index) {
+                        self.inputstate = MainInputState::Content;
+                        if double {
+                            return self.run_action(Action::ReadEntry);
+                        }
+                    }
+                }
+                Ok(AppScreenEvent::None)
+            }
+            MouseEventKind::Drag(MouseButton::Left) if self.dragging_divider => {
+                let width = (column.saturating_sub(self.tree_area.x) + 1).clamp(1, 100);
+                self.library
+                    .borrow_mut()
+                    .settings
+                    .appearance
+                    .main_screen_tree_width = width;
+                Ok(AppScreenEvent::None)
+            }
+            MouseEventKind::Up(MouseButton::Left) if self.dragging_divider => {
+                self.dragging_divider = false;
+                self.library.borrow_mut().settings.appearance.save()?;
+                Ok(AppScreenEvent::None)
+            }
+            _ => Ok(AppScreenEvent::None),
+        }
+    }
+
+    fn run_action(&mut self, action: Action) -> Result<AppScreenEvent> {
+        let in_tree = self.inputstate == MainInputState::Menu;
+
+        match action {
+            Action::Quit => Ok(AppScreenEvent::ExitApp),
+            Action::Back => {
+                self.inputstate = MainInputState::Menu;
+                Ok(AppScreenEvent::None)
+            }
+            Action::FocusEntries => {
+                self.inputstate = MainInputState::Content;
+                Ok(AppScreenEvent::None)
+            }
+            Action::MoveDown => {
+                if in_tree {
+                    self.feedtreestate.select_next();
+                } else {
+                    self.feedentrystate.select_next();
+                }
+                Ok(AppScreenEvent::None)
+            }
+            Action::MoveUp => {
+                if in_tree {
+                    self.feedtreestate.select_previous();
+                } else {
+                    self.feedentrystate.select_previous();
+                }
+                Ok(AppScreenEvent::None)
+            }
+            Action::First => {
+                if in_tree {
+                    self.feedtreestate.select_first();
+                } else {
+                    self.feedentrystate.select_first();
+                }
+                Ok(AppScreenEvent::None)
+            }
+            Action::Last => {
+                if in_tree {
+                    self.feedtreestate.select_last();
+                } else {
+                    self.feedentrystate.select_last();
+                }
+                Ok(AppScreenEvent::None)
+            }
+            Action::NextCategory => {
+                self.feedtreestate.select_next_category();
+                Ok(AppScreenEvent::None)
+            }
+            Action::PreviousCategory => {
+                self.feedtreestate.select_previous_category();
+                Ok(AppScreenEvent::None)
+            }
+            Action::ReadEntry => {
+                if let Some(entry) = self.feedentrystate.get_selected() {
+                    self.library.borrow_mut().set_entry_seen(&entry);
+                    self.feedentrystate.set_current_read();
+
+                    Ok(AppScreenEvent::ChangeState(Box::new(ReaderScreen::new(
+                        self.library.clone(),
+                        self.feedentrystate.entries.clone(),
+                        self.feedentrystate.listatate.selected().unwrap_or(0),
+                        self.hooks.clone(),
+                        self.keymap.clone(),
+                        self.images,
+                    ))))
+                } else {
+                    Ok(AppScreenEvent::None)
+                }
+            }
+            Action::ToggleRead => {
+                if let Some(entry) = self.feedentrystate.get_selected() {
+                    let was_seen = entry.seen;
+                    self.library.borrow_mut().toggle_entry_seen(&entry);
+                    let message = if was_seen {
+                        "Marked as Unread"
+                    } else {
+                        "Marked as Read"
+                    };
+                    Ok(AppScreenEvent::Notify(AppNotification::new(
+                        message,
+                        NotificationPriority::Low,
+                    )))
+                } else {
+                    Ok(AppScreenEvent::None)
+                }
+            }
+            Action::MarkAllRead => {
+                self.set_all_read();
+                Ok(AppScreenEvent::Notify(AppNotification::new(
+                    "All marked as Read",
+                    NotificationPriority::Low,
+                )))
+            }
+            Action::WidenPane => {
+                self.increase_tree_width()?;
+                Ok(AppScreenEvent::None)
+            }
+            Action::NarrowPane => {
+                self.decrease_tree_width()?;
+                Ok(AppScreenEvent::None)
+            }
+            Action::OpenLink => {
+                if let Some(entry) = self.feedentrystate.get_selected() {
+                    self.library.borrow_mut().set_entry_seen(&entry);
+                    self.open_external_url(&entry.url, &entry)
+                } else {
+                    Ok(AppScreenEvent::Notify(AppNotification::new(
+                        "Couldn't open link externally",
+                        NotificationPriority::High,
+                    )))
+                }
+            }
+            Action::ToggleReadLater => {
+                if let Some(entry) = self.feedentrystate.get_selected() {
+                    let added = self.toggle_read_later(&entry);
+                    let message = if added {
+                        "Added to Read Later"
+                    } else {
+                        "Removed from Read Later"
+                    };
+                    Ok(AppScreenEvent::Notify(AppNotification::new(
+                        message,
+                        NotificationPriority::Low,
+                    )))
+                } else {
+                    Ok(AppScreenEvent::None)
+                }
+            }
+            Action::OpenInEditor | Action::OpenInPager => {
+                if let Some(entry) = self.feedentrystate.get_selected() {
+                    self.library.borrow_mut().set_entry_seen(&entry);
+                    self.feedentrystate.set_current_read();
+
+                    Ok(AppScreenEvent::OpenExternal(
+                        if action == Action::OpenInEditor {
+                            ExternalView::editor(&entry)
+                        } else {
+                            ExternalView::pager(&entry)
+                        },
+                    ))
+                } else {
+                    Ok(AppScreenEvent::None)
+                }
+            }
+            Action::AddFeed => self.open_add_dialog(),
+            Action::EditFeed => self.open_rename_dialog(),
+            Action::MoveFeed => self.open_move_dialog(),
+            Action::DeleteFeed => self.open_delete_dialog(),
+            Action::ThemePicker => self.open_theme_selector(),
+            Action::Help => Ok(AppScreenEvent::OpenDialog(Box::new(HelpDialog::new(
+                self.library.clone(),
+                self.keymap.clone(),
+                self.get_full_instructions(),
+            )))),
+            _ => Ok(AppScreenEvent::None),
+        }
+    }
 }
 
 impl AppScreen for MainScreen {
@@ -168,9 +570,13 @@ impl AppScreen for MainScreen {
         self.library.borrow_mut().start_updater();
     }
 
-    fn quit(&mut self) {}
+    fn quit(&mut self) {
+        self.save_session();
+    }
 
-    fn pause(&mut self) {}
+    fn pause(&mut self) {
+        self.save_session();
+    }
 
     fn unpause(&mut self) {}
 
@@ -198,6 +604,9 @@ impl AppScreen for MainScreen {
 
         // Feed tree
         self.feedtreestate.update(&mut self.library.borrow_mut());
+        if self.restore_session {
+            self.restore_session();
+        }
 
         let (treestyle, treeselectionstyle) = if self.inputstate == MainInputState::Menu {
             (
@@ -221,12 +630,16 @@ impl AppScreen for MainScreen {
             )
         };
 
+        self.tree_area = chunks[0];
+        self.tree_list_area = treestyle.inner(chunks[0]);
+
         let treelist = List::new(self.feedtreestate.get_items())
             .block(treestyle)
             .highlight_style(treeselectionstyle);
 
         let mut treestate = self.feedtreestate.listatate;
         frame.render_stateful_widget(treelist, chunks[0], &mut treestate);
+        self.tree_offset = treestate.offset();
 
         // The feed entries
         self.feedentrystate
@@ -242,15 +655,19 @@ impl AppScreen for MainScreen {
             Style::default().bg(Color::from_u32(theme.base[2]))
         };
 
+        let entriesblock = Block::default()
+            .style(Style::default().bg(Color::from_u32(theme.base[2])))
+            .padding(Padding::new(2, 2, 1, 1));
+
+        self.entries_area = chunks[1];
+        self.entries_list_area = entriesblock.inner(chunks[1]);
+
         let list_widget = List::new(self.feedentrystate.get_items())
-            .block(
-                Block::default()
-                    .style(Style::default().bg(Color::from_u32(theme.base[2])))
-                    .padding(Padding::new(2, 2, 1, 1)),
-            )
+            .block(entriesblock)
             .highlight_style(entryselectionstyle);
 
         frame.render_stateful_widget(list_widget, chunks[1], &mut entryliststate);
+        self.entries_offset = entryliststate.offset();
 
         // Scrollbar
         let mut scrollbarstate = ScrollbarState::new(self.feedentrystate.scroll_max())
@@ -266,178 +683,25 @@ impl AppScreen for MainScreen {
     fn handle_event(&mut self, event: Event) -> Result<AppScreenEvent> {
         match event {
             Event::Key(key) if key.kind == KeyEventKind::Press => self.handle_keypress(key),
-            Event::Mouse(_) => Ok(AppScreenEvent::None),
+            Event::Mouse(mouse) => self.handle_mouse(mouse),
             Event::Resize(_, _) => Ok(AppScreenEvent::None),
             _ => Ok(AppScreenEvent::None),
         }
     }
 
     fn handle_keypress(&mut self, key: crossterm::event::KeyEvent) -> Result<AppScreenEvent> {
-        match self.inputstate {
-            MainInputState::Menu => match (key.modifiers, key.code) {
-                (_, KeyCode::Esc | KeyCode::Char('q'))
-                | (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => {
-                    Ok(AppScreenEvent::ExitApp)
-                }
-                (_, KeyCode::Down | KeyCode::Char('j')) => {
-                    self.feedtreestate.select_next();
-                    Ok(AppScreenEvent::None)
-                }
-                (_, KeyCode::Up | KeyCode::Char('k')) => {
-                    self.feedtreestate.select_previous();
-                    Ok(AppScreenEvent::None)
-                }
-                (_, KeyCode::Home | KeyCode::Char('g')) => {
-                    self.feedtreestate.select_first();
-                    Ok(AppScreenEvent::None)
-                }
-                (_, KeyCode::End | KeyCode::Char('G')) => {
-                    self.feedtreestate.select_last();
-                    Ok(AppScreenEvent::None)
-                }
-                (_, KeyCode::Right | KeyCode::Enter | KeyCode::Tab | KeyCode::Char('l')) => {
-                    self.inputstate = MainInputState::Content;
-                    Ok(AppScreenEvent::None)
-                }
-                (_, KeyCode::Char('R')) => {
-                    self.set_all_read();
-                    Ok(AppScreenEvent::Notify(AppNotification::new(
-                        "All marked as Read",
-                        NotificationPriority::Low,
-                    )))
-                }
-                (_, KeyCode::Char('>')) => {
-                    self.increase_tree_width()?;
-                    Ok(AppScreenEvent::None)
-                }
-                (_, KeyCode::Char('<')) => {
-                    self.decrease_tree_width()?;
-                    Ok(AppScreenEvent::None)
-                }
-                (_, KeyCode::Char('n')) => {
-                    self.feedtreestate.select_next_category();
-                    Ok(AppScreenEvent::None)
-                }
-                (_, KeyCode::Char('p')) => {
-                    self.feedtreestate.select_previous_category();
-                    Ok(AppScreenEvent::None)
-                }
-                (_, KeyCode::Char('t')) => self.open_theme_selector(),
-                (_, KeyCode::Char('?')) => Ok(AppScreenEvent::OpenDialog(Box::new(
-                    HelpDialog::new(self.library.clone(), self.get_full_instructions()),
-                ))),
-                _ => Ok(AppScreenEvent::None),
-            },
-            MainInputState::Content => match (key.modifiers, key.code) {
-                (_, KeyCode::Char('q'))
-                | (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => {
-                    Ok(AppScreenEvent::ExitApp)
-                }
-                (_, KeyCode::Down | KeyCode::Char('j')) => {
-                    self.feedentrystate.select_next();
-                    Ok(AppScreenEvent::None)
-                }
-                (_, KeyCode::Up | KeyCode::Char('k')) => {
-                    self.feedentrystate.select_previous();
-                    Ok(AppScreenEvent::None)
-                }
-                (_, KeyCode::Home | KeyCode::Char('g')) => {
-                    self.feedentrystate.select_first();
-                    Ok(AppScreenEvent::None)
-                }
-                (_, KeyCode::End | KeyCode::Char('G')) => {
-                    self.feedentrystate.select_last();
-                    Ok(AppScreenEvent::None)
-                }
-                (_, KeyCode::Esc) => {
-                    self.inputstate = MainInputState::Menu;
-                    Ok(AppScreenEvent::None)
-                }
-                (_, KeyCode::Left | KeyCode::Char('h')) => {
-                    self.inputstate = MainInputState::Menu;
-                    Ok(AppScreenEvent::None)
-                }
-                (_, KeyCode::Enter) => {
-                    if let Some(entry) = self.feedentrystate.get_selected() {
-                        self.library.borrow_mut().set_entry_seen(&entry);
-                        self.feedentrystate.set_current_read();
-
-                        Ok(AppScreenEvent::ChangeState(Box::new(ReaderScreen::new(
-                            self.library.clone(),
-                            self.feedentrystate.entries.clone(),
-                            self.feedentrystate.listatate.selected().unwrap_or(0),
-                            self.hooks.clone(),
-                        ))))
-                    } else {
-                        Ok(AppScreenEvent::None)
-                    }
-                }
-                (_, KeyCode::Char('r')) => {
-                    if let Some(entry) = self.feedentrystate.get_selected() {
-                        let was_seen = entry.seen;
-                        self.library.borrow_mut().toggle_entry_seen(&entry);
-                        let message = if was_seen {
-                            "Marked as Unread"
-                        } else {
-                            "Marked as Read"
-                        };
-                        Ok(AppScreenEvent::Notify(AppNotification::new(
-                            message,
-                            NotificationPriority::Low,
-                        )))
-                    } else {
-                        Ok(AppScreenEvent::None)
-                    }
-                }
-                (_, KeyCode::Char('R')) => {
-                    self.set_all_read();
-                    Ok(AppScreenEvent::Notify(AppNotification::new(
-                        "All marked as Read",
-                        NotificationPriority::Low,
-                    )))
-                }
-                (_, KeyCode::Char('>')) => {
-                    self.increase_tree_width()?;
-                    Ok(AppScreenEvent::None)
-                }
-                (_, KeyCode::Char('<')) => {
-                    self.decrease_tree_width()?;
-                    Ok(AppScreenEvent::None)
-                }
-                (_, KeyCode::Char('o')) => {
-                    if let Some(entry) = self.feedentrystate.get_selected() {
-                        self.library.borrow_mut().set_entry_seen(&entry);
-                        self.open_external_url(&entry.url)
-                    } else {
-                        Ok(AppScreenEvent::Notify(AppNotification::new(
-                            "Couldn't open link externally",
-                            NotificationPriority::High,
-                        )))
-                    }
-                }
-                (_, KeyCode::Char('L')) => {
-                    if let Some(entry) = self.feedentrystate.get_selected() {
-                        let added = self.toggle_read_later(&entry);
-                        let message = if added {
-                            "Added to Read Later"
-                        } else {
-                            "Removed from Read Later"
-                        };
-                        Ok(AppScreenEvent::Notify(AppNotification::new(
-                            message,
-                            NotificationPriority::Low,
-                        )))
-                    } else {
-                        Ok(AppScreenEvent::None)
-                    }
-                }
-                (_, KeyCode::Char('t')) => self.open_theme_selector(),
-                (_, KeyCode::Char('?')) => Ok(AppScreenEvent::OpenDialog(Box::new(
-                    HelpDialog::new(self.library.clone(), self.get_full_instructions()),
-                ))),
-                _ => Ok(AppScreenEvent::None),
-            },
+        let actions = self
+            .keymap
+            .actions(self.key_context(), &mut self.pending_keys, key);
+
+        for action in actions {
+            let event = self.run_action(action)?;
+            if !matches!(event, AppScreenEvent::None) {
+                return Ok(event);
+            }
         }
+
+        Ok(AppScreenEvent::None)
     }
 
     fn get_title(&self) -> String {
@@ -446,12 +710,31 @@ impl AppScreen for MainScreen {
 
     fn get_instructions(&self) -> String {
         if self.inputstate == MainInputState::Menu {
-            String::from(
-                "?: Help | j/k/↓/↑: move | n/p: next/prev category | Enter: select | Esc: quit",
+            self.keymap.status_line(
+                KeyContext::Tree,
+                &[
+                    (&[Action::Help], "Help"),
+                    (&[Action::MoveDown, Action::MoveUp], "move"),
+                    (
+                        &[Action::NextCategory, Action::PreviousCategory],
+                        "next/prev category",
+                    ),
+                    (&[Action::AddFeed], "add feed"),
+                    (&[Action::FocusEntries], "select"),
+                    (&[Action::Quit], "quit"),
+                ],
             )
         } else {
-            String::from(
-                "?: Help | j/k/↓/↑: move | o: open | L: add/remove read later | Enter: read | Esc: back",
+            self.keymap.status_line(
+                KeyContext::Entries,
+                &[
+                    (&[Action::Help], "Help"),
+                    (&[Action::MoveDown, Action::MoveUp], "move"),
+                    (&[Action::OpenLink], "open"),
+                    (&[Action::ToggleReadLater], "add/remove read later"),
+                    (&[Action::ReadEntry], "read"),
+                    (&[Action::Back], "back"),
+                ],
             )
         }
     }
@@ -461,33 +744,7 @@ impl AppScreen for MainScreen {
     }
 
     fn get_full_instructions(&self) -> ScreenInstructions {
-        ScreenInstructions::new(vec![
-            InstructionCategory::new(
-                "Navigation",
-                vec![
-                    InstructionDetail::new("j/k/↓/↑", "move selection"),
-                    InstructionDetail::new("n/p", "next/previous category"),
-                    InstructionDetail::new("g/G/Home/End", "beginning and end of list"),
-                ],
-            ),
-            InstructionCategory::new(
-                "Actions",
-                vec![
-                    InstructionDetail::new("o", "open link externally"),
-                    InstructionDetail::new("L", "add/remove read later"),
-                    InstructionDetail::new("Enter", "select category or read entry"),
-                    InstructionDetail::new("r", "toggle item read state"),
-                    InstructionDetail::new("R", "mark all items as read"),
-                ],
-            ),
-            InstructionCategory::new(
-                "App",
-                vec![
-                    InstructionDetail::new("</>", "change feed column width"),
-                    InstructionDetail::new("t", "open theme picker"),
-                    InstructionDetail::new("Esc/q", "back from entries or quit"),
-                ],
-            ),
-        ])
+        self.keymap
+            .instructions(&[KeyContext::Tree, KeyContext::Entries])
     }
 }
@@ -1,6 +1,12 @@
+pub mod addfeeddialog;
+pub mod deletedialog;
+pub mod editfeeddialog;
 pub mod helpdialog;
+pub mod linksdialog;
 pub mod mainscreen;
+pub mod movedialog;
 pub mod readerscreen;
+pub mod renamedialog;
 pub mod themedialog;
 pub mod urldialog;
 pub mod welcomedialog;
@@ -1,29 +1,43 @@
 use std::{cell::RefCell, rc::Rc};
 
 use color_eyre::Result;
-use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
-use ratatui::layout::{Alignment, Constraint, Layout};
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::{Alignment, Constraint, Layout, Margin, Rect};
 use ratatui::style::{Color, Style};
+use ratatui::text::Line;
 use ratatui::widgets::{
     Block, Padding, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap,
 };
 use tracing::error;
-use unicode_width::UnicodeWidthStr;
+use url::Url;
 
 use crate::app::AppWorkStatus;
 use crate::core::ui::notification::{AppNotification, NotificationPriority};
 use crate::core::{
     feed::feedentry::FeedEntry,
     hooks::AppHooks,
-    library::feedlibrary::FeedLibrary,
+    library::{
+        feedlibrary::FeedLibrary,
+        imagecache::{CachedImage, ImageCache},
+    },
     ui::{
         appscreen::{AppScreen, AppScreenEvent},
-        instructiondetails::{InstructionCategory, InstructionDetail, ScreenInstructions},
+        external::ExternalView,
+        graphics::ImageProtocol,
+        instructiondetails::ScreenInstructions,
+        keymap::{Action, KeyContext, Keymap, PendingKeys},
     },
 };
+use crate::ui::screens::linksdialog::LinksDialog;
 use crate::ui::screens::themedialog::ThemeDialog;
-use crate::ui::screens::urldialog::UrlDialog;
-use crate::ui::tools::tuimarkdown;
+use crate::ui::states::searchstate::SearchState;
+use crate::ui::tools::images::{self, MAX_IMAGE_ROWS, TerminalImages};
+use crate::ui::tools::links;
+use crate::ui::tools::mouse::{self, ScreenText, WHEEL_STEP};
+use crate::ui::tools::styles;
+use crate::ui::tools::tuimarkdown::{self, Markdown, MarkdownLink};
 
 use super::helpdialog::HelpDialog;
 
@@ -33,8 +47,35 @@ pub struct ReaderScreen {
     current_index: usize,
     scroll: usize,
     scrollmax: usize,
+    // The saved position of the entry, turned into a row once it's wrapped to the width
+    restore: Option<f64>,
     viewport_height: usize,
     hooks: Rc<AppHooks>,
+    keymap: Rc<Keymap>,
+    pending_keys: PendingKeys,
+    // The link number typed so far, while following a link
+    link_hint: Option<String>,
+    // The links of the entry as last drawn, so the numbers match the ones on screen
+    links: Vec<MarkdownLink>,
+    search: SearchState,
+    search_pending_keys: PendingKeys,
+    // Scroll position when the search started, where its first match is looked for
+    search_origin: usize,
+    // Where the article was last drawn, to find what's under the mouse
+    layout_area: Rect,
+    scrollbar_area: Rect,
+    screen_text: ScreenText,
+    dragging_divider: bool,
+    // Only there when the terminal can draw images
+    images: Option<(ImageCache, TerminalImages)>,
+}
+
+/// An image drawn into the blank lines left for it after its placeholder
+struct InlineImage {
+    url: String,
+    line: usize,
+    size: (u16, u16),
+    escape: Rc<String>,
 }
 
 impl ReaderScreen {
@@ -43,15 +84,43 @@ impl ReaderScreen {
         entries: Vec<FeedEntry>,
         current_index: usize,
         hooks: Rc<AppHooks>,
+        keymap: Rc<Keymap>,
+        images: ImageProtocol,
     ) -> ReaderScreen {
+        let images = images
+            .resolve(|key| std::env::var(key).ok())
+            .map(|protocol| {
+                (
+                    ImageCache::new(&library.borrow().data.path),
+                    TerminalImages::new(protocol),
+                )
+            });
+
+        let restore = entries
+            .get(current_index)
+            .and_then(|entry| library.borrow().settings.session.position(&entry.filepath));
+
         ReaderScreen {
             library,
             entries,
             current_index,
             scroll: 0,
             scrollmax: 1,
+            restore,
             viewport_height: 24,
             hooks,
+            keymap,
+            pending_keys: PendingKeys::default(),
+            link_hint: None,
+            links: vec![],
+            search: SearchState::default(),
+            search_pending_keys: PendingKeys::default(),
+            search_origin: 0,
+            layout_area: Rect::default(),
+            scrollbar_area: Rect::default(),
+            screen_text: ScreenText::default(),
+            dragging_divider: false,
+            images,
         }
     }
 
@@ -76,43 +145,190 @@ impl ReaderScreen {
     }
 
     pub fn next_entry(&mut self) {
+        self.search.clear();
         if self.current_index < self.entries.len().saturating_sub(1) {
-            self.current_index += 1;
-            self.scroll = 0;
-            self.library
-                .borrow_mut()
-                .set_entry_seen(&self.entries[self.current_index]);
+            self.open_entry(self.current_index + 1);
         }
     }
 
     pub fn previous_entry(&mut self) {
+        self.search.clear();
         if self.current_index > 0 {
-            self.current_index -= 1;
-            self.scroll = 0;
-            self.library
-                .borrow_mut()
-                .set_entry_seen(&self.entries[self.current_index]);
+            self.open_entry(self.current_index - 1);
         }
     }
 
-    fn open_external_url(&self, url: &str) -> Result<AppScreenEvent> {
-        if self.hooks.run_open_link(url) {
-            return Ok(AppScreenEvent::Notify(AppNotification::new(
-                "Link opened externally with user hook",
-                NotificationPriority::Low,
-            )));
+    /// Switches to another entry, picking it up where it was left
+    fn open_entry(&mut self, index: usize) {
+        self.save_position();
+        self.current_index = index;
+        self.links.clear();
+
+        let mut library = self.library.borrow_mut();
+        let entry = &self.entries[self.current_index];
+        self.scroll = 0;
+        self.restore = library.settings.session.position(&entry.filepath);
+        library.set_entry_seen(entry);
+    }
+
+    /// Scrolls to the saved position of the entry, once the rows it's wrapped to are known
+    fn restore_position(&mut self) {
+        if let Some(progress) = self.restore.take() {
+            self.scroll = ((progress * self.scrollmax as f64).round() as usize).min(self.scrollmax);
         }
+    }
+
+    /// Remembers how far the current entry was read, to come back to it in another session
+    fn save_position(&mut self) {
+        let Some(entry) = self.entries.get(self.current_index) else {
+            return;
+        };
+
+        // An entry left before it was drawn keeps the position it had
+        let progress = match self.restore {
+            Some(progress) => progress,
+            None if self.scrollmax == 0 => 1.0,
+            None => self.scroll as f64 / self.scrollmax as f64,
+        };
+
+        let session = &mut self.library.borrow_mut().settings.session;
+        session.set_position(&entry.filepath, progress);
+        if let Err(e) = session.save() {
+            error!("Couldn't save the session: {:?}", e);
+        }
+    }
 
-        match open::that(url) {
-            Ok(_) => Ok(AppScreenEvent::Notify(AppNotification::new(
-                "Link opened externally",
+    fn open_external_url(&self, url: &str, entry: &FeedEntry) -> Result<AppScreenEvent> {
+        Ok(links::open_external_url(
+            &self.library.borrow(),
+            &self.hooks,
+            url,
+            entry,
+        ))
+    }
+
+    fn current_links(&self) -> &[MarkdownLink] {
+        &self.links
+    }
+
+    fn open_link_number(&self, number: usize) -> Result<AppScreenEvent> {
+        match self.current_links().get(number.wrapping_sub(1)) {
+            Some(link) => self.open_external_url(&link.url, &self.entries[self.current_index]),
+            None => Ok(AppScreenEvent::Notify(AppNotification::new(
+                format!("There's no link {number}"),
                 NotificationPriority::Low,
             ))),
-            Err(_) => {
-                error!("Couldn't invoke system browser");
-                Ok(AppScreenEvent::OpenDialog(Box::new(UrlDialog::new(
-                    url.to_string(),
-                ))))
+        }
+    }
+
+    /// Adds a digit to the link number being typed. Returns the number once no longer one
+    /// could match, so it opens without waiting for Enter.
+    fn push_hint_digit(&mut self, digit: char, link_count: usize) -> Option<usize> {
+        let hint = self.link_hint.as_mut()?;
+        hint.push(digit);
+
+        let number: usize = hint.parse().ok()?;
+        if number.saturating_mul(10) > link_count {
+            self.link_hint = None;
+            Some(number)
+        } else {
+            None
+        }
+    }
+
+    fn handle_link_hint_key(&mut self, key: KeyEvent) -> Result<AppScreenEvent> {
+        match key.code {
+            KeyCode::Char(digit) if digit.is_ascii_digit() => {
+                let link_count = self.current_links().len();
+                match self.push_hint_digit(digit, link_count) {
+                    Some(number) => self.open_link_number(number),
+                    None => Ok(AppScreenEvent::None),
+                }
+            }
+            KeyCode::Backspace => {
+                if let Some(hint) = self.link_hint.as_mut() {
+                    hint.pop();
+                }
+                Ok(AppScreenEvent::None)
+            }
+            KeyCode::Enter => match self.link_hint.take().and_then(|h| h.parse().ok()) {
+                Some(number) => self.open_link_number(number),
+                None => Ok(AppScreenEvent::None),
+            },
+            _ => {
+                self.link_hint = None;
+                Ok(AppScreenEvent::None)
+            }
+        }
+    }
+
+    /// Makes room for the images that are loaded under their placeholders, fitting them in
+    /// `width` columns. Images still loading or that failed keep only the placeholder.
+    fn insert_images(&mut self, markdown: &mut Markdown, width: u16) -> Vec<InlineImage> {
+        let Some((cache, terminal)) = self.images.as_mut() else {
+            return vec![];
+        };
+
+        let base = Url::parse(&self.entries[self.current_index].url).ok();
+        let cell = images::cell_size();
+        let mut inline = vec![];
+
+        for link in markdown.links.iter().filter(|l| l.is_image) {
+            let url = match &base {
+                Some(base) => base
+                    .join(&link.url)
+                    .map(|u| u.to_string())
+                    .unwrap_or_else(|_| link.url.clone()),
+                None => link.url.clone(),
+            };
+
+            let CachedImage::Ready(image) = cache.get(&url) else {
+                continue;
+            };
+
+            let size = images::fit(image, width, MAX_IMAGE_ROWS, cell);
+            if let Some(escape) = terminal.encoded(&url, image, size, cell) {
+                inline.push(InlineImage {
+                    url,
+                    line: link.line + 1,
+                    size,
+                    escape,
+                });
+            }
+        }
+
+        // From the bottom up, so the lines of the images above don't move
+        for image in inline.iter().rev() {
+            let at = image.line.min(markdown.text.lines.len());
+            markdown.text.lines.splice(
+                at..at,
+                std::iter::repeat_n(Line::default(), image.size.1 as usize),
+            );
+        }
+
+        let mut inserted = 0;
+        for image in inline.iter_mut() {
+            image.line += inserted;
+            inserted += image.size.1 as usize;
+        }
+
+        inline
+    }
+
+    /// Places the images that are fully in view. Images cut by the edges are left out, as the
+    /// protocols can't draw part of an image.
+    fn place_images(&mut self, frame: &mut ratatui::Frame, inline: &[InlineImage], area: Rect) {
+        let Some((_, terminal)) = self.images.as_mut() else {
+            return;
+        };
+
+        // The text comes wrapped already, so its lines are the rows on screen
+        for image in inline {
+            let row = image.line;
+            let (cols, rows) = image.size;
+            if row >= self.scroll && row + rows as usize <= self.scroll + area.height as usize {
+                let rect = Rect::new(area.x, area.y + (row - self.scroll) as u16, cols, rows);
+                terminal.place(frame.buffer_mut(), rect, &image.url, image.escape.clone());
             }
         }
     }
@@ -138,6 +354,177 @@ impl ReaderScreen {
             .min(100);
         l.settings.appearance.save()
     }
+
+    fn handle_search_input(&mut self, key: KeyEvent) -> Result<AppScreenEvent> {
+        match key.code {
+            KeyCode::Enter => {
+                self.search.editing = false;
+                if self.search.query().is_empty() {
+                    self.search.clear();
+                }
+            }
+            KeyCode::Esc => self.search.clear(),
+            _ => {
+                self.search.input.handle_key(key);
+            }
+        }
+        Ok(AppScreenEvent::None)
+    }
+
+    fn run_actions(&mut self, actions: Vec<Action>) -> Result<AppScreenEvent> {
+        for action in actions {
+            let event = self.run_action(action)?;
+            if !matches!(event, AppScreenEvent::None) {
+                return Ok(event);
+            }
+        }
+
+        Ok(AppScreenEvent::None)
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) -> Result<AppScreenEvent> {
+        match mouse.kind {
+            MouseEventKind::ScrollDown => {
+                self.scroll = (self.scroll + WHEEL_STEP).min(self.scrollmax);
+                Ok(AppScreenEvent::None)
+            }
+            MouseEventKind::ScrollUp => {
+                self.scroll = self.scroll.saturating_sub(WHEEL_STEP);
+                Ok(AppScreenEvent::None)
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                // The scrollbar doubles as the handle to resize the reader
+                if mouse::contains(self.scrollbar_area, mouse.column, mouse.row) {
+                    self.dragging_divider = true;
+                    return Ok(AppScreenEvent::None);
+                }
+
+                match self.screen_text.link_at(mouse.column, mouse.row) {
+                    Some(url) => {
+                        let entry = self.entries[self.current_index].clone();
+                        self.open_external_url(&url, &entry)
+                    }
+                    None => Ok(AppScreenEvent::None),
+                }
+            }
+            MouseEventKind::Drag(MouseButton::Left) if self.dragging_divider => {
+                let left = self.layout_area.x + 1;
+                let width = (mouse.column.saturating_sub(left) as u32 * 100
+                    / self.layout_area.width.max(1) as u32) as u16;
+                self.library.borrow_mut().settings.appearance.reader_width = width.clamp(10, 100);
+                Ok(AppScreenEvent::None)
+            }
+            MouseEventKind::Up(MouseButton::Left) if self.dragging_divider => {
+                self.dragging_divider = false;
+                self.library.borrow_mut().settings.appearance.save()?;
+                Ok(AppScreenEvent::None)
+            }
+            _ => Ok(AppScreenEvent::None),
+        }
+    }
+
+    fn run_action(&mut self, action: Action) -> Result<AppScreenEvent> {
+        match action {
+            Action::Back => Ok(AppScreenEvent::ExitState),
+            Action::MoveDown => {
+                self.scrolldown();
+                Ok(AppScreenEvent::None)
+            }
+            Action::MoveUp => {
+                self.scrollup();
+                Ok(AppScreenEvent::None)
+            }
+            Action::First => {
+                self.scroll = 0;
+                Ok(AppScreenEvent::None)
+            }
+            Action::Last => {
+                self.scroll = self.scrollmax;
+                Ok(AppScreenEvent::None)
+            }
+            Action::HalfPageDown => {
+                self.scroll_half_down();
+                Ok(AppScreenEvent::None)
+            }
+            Action::HalfPageUp => {
+                self.scroll_half_up();
+                Ok(AppScreenEvent::None)
+            }
+            Action::OpenLink => self.open_external_url(
+                &self.entries[self.current_index].url,
+                &self.entries[self.current_index],
+            ),
+            Action::FollowLink => {
+                if self.current_links().is_empty() {
+                    return Ok(AppScreenEvent::Notify(AppNotification::new(
+                        "This entry has no links",
+                        NotificationPriority::Low,
+                    )));
+                }
+                self.link_hint = Some(String::new());
+                Ok(AppScreenEvent::None)
+            }
+            Action::LinkList => Ok(AppScreenEvent::OpenDialog(Box::new(LinksDialog::new(
+                self.library.clone(),
+                self.hooks.clone(),
+                self.keymap.clone(),
+                self.entries[self.current_index].clone(),
+                self.current_links().to_vec(),
+            )))),
+            Action::OpenInEditor => Ok(AppScreenEvent::OpenExternal(ExternalView::editor(
+                &self.entries[self.current_index],
+            ))),
+            Action::OpenInPager => Ok(AppScreenEvent::OpenExternal(ExternalView::pager(
+                &self.entries[self.current_index],
+            ))),
+            Action::CopyLink => Ok(links::copy_to_clipboard(
+                &self.entries[self.current_index].url,
+            )),
+            Action::Search => {
+                self.search.start();
+                self.search_origin = self.scroll;
+                Ok(AppScreenEvent::None)
+            }
+            Action::NextMatch => {
+                self.search.next();
+                Ok(AppScreenEvent::None)
+            }
+            Action::PreviousMatch => {
+                self.search.previous();
+                Ok(AppScreenEvent::None)
+            }
+            Action::ClearSearch => {
+                self.search.clear();
+                Ok(AppScreenEvent::None)
+            }
+            Action::NextEntry => {
+                self.next_entry();
+                Ok(AppScreenEvent::None)
+            }
+            Action::PreviousEntry => {
+                self.previous_entry();
+                Ok(AppScreenEvent::None)
+            }
+            Action::WidenPane => {
+                self.increase_reader_width()?;
+                Ok(AppScreenEvent::None)
+            }
+            Action::NarrowPane => {
+                self.decrease_reader_width()?;
+                Ok(AppScreenEvent::None)
+            }
+            Action::ThemePicker => Ok(AppScreenEvent::OpenDialog(Box::new(ThemeDialog::new(
+                self.library.clone(),
+                self.keymap.clone(),
+            )))),
+            Action::Help => Ok(AppScreenEvent::OpenDialog(Box::new(HelpDialog::new(
+                self.library.clone(),
+                self.keymap.clone(),
+                self.get_full_instructions(),
+            )))),
+            _ => Ok(AppScreenEvent::None),
+        }
+    }
 }
 
 impl AppScreen for ReaderScreen {
@@ -210,35 +597,56 @@ impl AppScreen for ReaderScreen {
         frame.render_widget(date, contentlayout[2]);
 
         // Content
-        let text = tuimarkdown::from_str(&current_entry.text, Some(theme.clone()));
-        let textheight = text.height() as usize;
-
-        // This is a workaround to get more or less the amount of wrapped lines, to be used on the
-        // scrollbar
-        let mut wrapped_lines = 0;
-        for line in text.lines.iter() {
-            let content: String = line
-                .spans
-                .iter()
-                .map(|span| span.content.to_string())
-                .collect();
-            let line_width = UnicodeWidthStr::width(content.as_str());
-            let wrapped = line_width.div_ceil(contentlayout[3].width as usize);
-            wrapped_lines += wrapped - wrapped.min(1);
-        }
-
-        let scrollheight = textheight + (wrapped_lines as f32 * 1.06) as usize + 4;
-        self.scrollmax = scrollheight - (contentlayout[3].height as usize).min(scrollheight);
+        let content = current_entry.text.clone();
+        let mut markdown = tuimarkdown::parse_with_width(
+            &content,
+            Some(theme.clone()),
+            Some(contentlayout[3].width),
+        );
+        let inline = self.insert_images(&mut markdown, contentlayout[3].width);
+        self.links = markdown.links;
+        let mut text = markdown.text;
+
+        // The lines are wrapped to the width by the renderer, so each one is a row on screen
+        self.scrollmax = text
+            .lines
+            .len()
+            .saturating_sub(contentlayout[3].height as usize);
+        self.restore_position();
+        // Resizing can leave the scroll past the end
+        self.scroll = self.scroll.min(self.scrollmax);
         self.viewport_height = contentlayout[3].height as usize;
 
+        if self.search.is_active() {
+            self.search
+                .update(&text, self.search_origin.min(text.lines.len()));
+
+            // Keep the match a third of the way down, so what comes before it is visible too
+            if let Some(hit) = self.search.take_jump() {
+                self.scroll = hit
+                    .line
+                    .saturating_sub(self.viewport_height / 3)
+                    .min(self.scrollmax);
+            }
+
+            text = self.search.highlight(
+                text,
+                styles::search_match(Some(&theme)),
+                styles::search_current(Some(&theme)),
+            );
+        }
+
         // Content Paragraph component
         let paragraph = Paragraph::new(text)
             .scroll((self.scroll as u16, 0))
-            .alignment(Alignment::Left)
-            .wrap(Wrap { trim: true });
+            .alignment(Alignment::Left);
 
         frame.render_widget(paragraph, contentlayout[3]);
 
+        if !inline.is_empty() {
+            self.place_images(frame, &inline, contentlayout[3]);
+        }
+
         // Scrollbar
         let mut scrollbarstate = ScrollbarState::new(self.scrollmax).position(self.scroll);
         let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight).style(
@@ -247,12 +655,16 @@ impl AppScreen for ReaderScreen {
                 .bg(Color::from_u32(theme.base[1])),
         );
         frame.render_stateful_widget(scrollbar, sizelayout[2], &mut scrollbarstate);
+
+        self.layout_area = area.inner(Margin::new(2, 2));
+        self.scrollbar_area = sizelayout[2];
+        self.screen_text = ScreenText::capture(frame.buffer_mut(), sizelayout[1]);
     }
 
     fn handle_event(&mut self, event: Event) -> Result<AppScreenEvent> {
         match event {
             Event::Key(key) if key.kind == KeyEventKind::Press => self.handle_keypress(key),
-            Event::Mouse(_) => Ok(AppScreenEvent::None),
+            Event::Mouse(mouse) => self.handle_mouse(mouse),
             Event::Resize(_, _) => Ok(AppScreenEvent::None),
             _ => Ok(AppScreenEvent::None),
         }
@@ -262,77 +674,106 @@ impl AppScreen for ReaderScreen {
         &mut self,
         key: crossterm::event::KeyEvent,
     ) -> color_eyre::eyre::Result<AppScreenEvent> {
-        match (key.modifiers, key.code) {
-            (_, KeyCode::Esc | KeyCode::Char('q'))
-            | (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => {
-                Ok(AppScreenEvent::ExitState)
-            }
-            (_, KeyCode::Down | KeyCode::Char('j')) => {
-                self.scrolldown();
-                Ok(AppScreenEvent::None)
-            }
-            (_, KeyCode::Up | KeyCode::Char('k')) => {
-                self.scrollup();
-                Ok(AppScreenEvent::None)
-            }
-            (_, KeyCode::Home | KeyCode::Char('g')) => {
-                self.scroll = 0;
-                Ok(AppScreenEvent::None)
-            }
-            (_, KeyCode::End | KeyCode::Char('G')) => {
-                self.scroll = self.scrollmax;
-                Ok(AppScreenEvent::None)
-            }
-            (_, KeyCode::Char('o')) => {
-                self.open_external_url(&self.entries[self.current_index].url)
-            }
-            (_, KeyCode::Char('n')) => {
-                self.next_entry();
-                Ok(AppScreenEvent::None)
-            }
-            (_, KeyCode::Char('p')) => {
-                self.previous_entry();
-                Ok(AppScreenEvent::None)
-            }
-            (_, KeyCode::Char('>')) => {
-                self.increase_reader_width()?;
-                Ok(AppScreenEvent::None)
-            }
-            (_, KeyCode::Char('<')) => {
-                self.decrease_reader_width()?;
-                Ok(AppScreenEvent::None)
-            }
-            (KeyModifiers::CONTROL, KeyCode::Char('d') | KeyCode::Char('D')) => {
-                self.scroll_half_down();
-                Ok(AppScreenEvent::None)
-            }
-            (KeyModifiers::CONTROL, KeyCode::Char('u') | KeyCode::Char('U')) => {
-                self.scroll_half_up();
-                Ok(AppScreenEvent::None)
+        if self.link_hint.is_some() {
+            return self.handle_link_hint_key(key);
+        }
+
+        if self.search.editing {
+            return self.handle_search_input(key);
+        }
+
+        // While searching, the search keys come before the reader's own
+        if self.search.is_active() {
+            let actions =
+                self.keymap
+                    .actions(KeyContext::Search, &mut self.search_pending_keys, key);
+            if !actions.is_empty() {
+                return self.run_actions(actions);
             }
-            (_, KeyCode::Char('t')) => Ok(AppScreenEvent::OpenDialog(Box::new(ThemeDialog::new(
-                self.library.clone(),
-            )))),
-            (_, KeyCode::Char('?')) => Ok(AppScreenEvent::OpenDialog(Box::new(HelpDialog::new(
-                self.library.clone(),
-                self.get_full_instructions(),
-            )))),
-            _ => Ok(AppScreenEvent::None),
         }
+
+        let actions = self
+            .keymap
+            .actions(KeyContext::Reader, &mut self.pending_keys, key);
+        self.run_actions(actions)
     }
 
-    fn pause(&mut self) {}
+    fn pause(&mut self) {
+        if let Some((_, terminal)) = self.images.as_mut() {
+            terminal.clear();
+        }
+    }
+
+    /// The entry can have been edited while the reader was paused
+    fn unpause(&mut self) {
+        let entry = &self.entries[self.current_index];
+        let Ok(reloaded) = self.library.borrow().data.load_entry(&entry.filepath) else {
+            return;
+        };
 
-    fn unpause(&mut self) {}
+        if reloaded.title != entry.title || reloaded.text != entry.text {
+            self.entries[self.current_index] = reloaded;
+            self.search.clear();
+        }
+    }
+
+    fn quit(&mut self) {
+        self.save_position();
+        if let Some((_, terminal)) = self.images.as_mut() {
+            terminal.clear();
+        }
+    }
 
-    fn quit(&mut self) {}
+    fn after_draw(&mut self, covered: bool) {
+        if let Some((_, terminal)) = self.images.as_mut() {
+            terminal.flush(covered);
+        }
+    }
 
     fn get_title(&self) -> String {
         String::from("Reader")
     }
 
     fn get_instructions(&self) -> String {
-        String::from("?: Help | j/k/↓/↑: scroll | n/p: next/prev | o: open | Esc/q: leave")
+        if let Some(hint) = &self.link_hint {
+            return format!("Open link: {hint}_ | Enter: open | Esc: cancel");
+        }
+
+        if self.search.editing {
+            return format!(
+                "/{}_ [{}] | Enter: confirm | Esc: cancel",
+                self.search.query(),
+                self.search.counter()
+            );
+        }
+
+        if self.search.is_active() {
+            let keys = self.keymap.status_line(
+                KeyContext::Search,
+                &[
+                    (&[Action::NextMatch, Action::PreviousMatch], "next/prev"),
+                    (&[Action::ClearSearch], "clear"),
+                ],
+            );
+            return format!(
+                "/{} [{}] | {keys}",
+                self.search.query(),
+                self.search.counter()
+            );
+        }
+
+        self.keymap.status_line(
+            KeyContext::Reader,
+            &[
+                (&[Action::Help], "Help"),
+                (&[Action::MoveDown, Action::MoveUp], "scroll"),
+                (&[Action::NextEntry, Action::PreviousEntry], "next/prev"),
+                (&[Action::OpenLink], "open"),
+                (&[Action::Search], "search"),
+                (&[Action::FollowLink], "follow link"),
+                (&[Action::Back], "leave"),
+            ],
+        )
     }
 
     fn get_work_status(&self) -> AppWorkStatus {
@@ -340,29 +781,8 @@ impl AppScreen for ReaderScreen {
     }
 
     fn get_full_instructions(&self) -> ScreenInstructions {
-        ScreenInstructions::new(vec![
-            InstructionCategory::new(
-                "Navigation",
-                vec![
-                    InstructionDetail::new("j/k/↓/↑", "scroll"),
-                    InstructionDetail::new("Ctrl+d/u", "scroll half page down/up"),
-                    InstructionDetail::new("g/G", "go to beginning or end of file"),
-                    InstructionDetail::new("</>", "change reader width"),
-                    InstructionDetail::new("n/p", "next/previous entry"),
-                ],
-            ),
-            InstructionCategory::new(
-                "Actions",
-                vec![InstructionDetail::new("o", "open externally")],
-            ),
-            InstructionCategory::new(
-                "App",
-                vec![
-                    InstructionDetail::new("t", "open theme picker"),
-                    InstructionDetail::new("Esc/q", "leave"),
-                ],
-            ),
-        ])
+        self.keymap
+            .instructions(&[KeyContext::Reader, KeyContext::Search])
     }
 }
 
@@ -397,6 +817,8 @@ mod tests {
             entries,
             0,
             Rc::new(AppHooks::default()),
+            Rc::new(Keymap::default()),
+            ImageProtocol::Off,
         );
 
         // Test next_entry
@@ -418,4 +840,111 @@ mod tests {
         reader_screen.previous_entry();
         assert_eq!(reader_screen.current_index, 0);
     }
+
+    #[test]
+    fn test_remembers_position() {
+        let (library, temp_dir) = FeedLibrary::new_for_test();
+        let library = Rc::new(RefCell::new(library));
+        let mut entries = create_test_entries();
+        for (i, entry) in entries.iter_mut().enumerate() {
+            entry.filepath = temp_dir.path().join(format!("{i}.md"));
+        }
+
+        let mut reader_screen = ReaderScreen::new(
+            library.clone(),
+            entries.clone(),
+            0,
+            Rc::new(AppHooks::default()),
+            Rc::new(Keymap::default()),
+            ImageProtocol::Off,
+        );
+        reader_screen.scrollmax = 100;
+        reader_screen.scroll = 20;
+
+        reader_screen.next_entry();
+        reader_screen.restore_position();
+        assert_eq!(reader_screen.scroll, 0);
+        reader_screen.previous_entry();
+        reader_screen.restore_position();
+        assert_eq!(reader_screen.scroll, 20);
+
+        reader_screen.scroll = 30;
+        reader_screen.quit();
+
+        let mut reader_screen = ReaderScreen::new(
+            library,
+            entries,
+            0,
+            Rc::new(AppHooks::default()),
+            Rc::new(Keymap::default()),
+            ImageProtocol::Off,
+        );
+        // Drawn wider, the entry wraps to fewer rows, and the position follows
+        reader_screen.scrollmax = 50;
+        reader_screen.restore_position();
+        assert_eq!(reader_screen.scroll, 15);
+    }
+
+    #[test]
+    fn test_link_hints() {
+        let (library, _temp_dir) = FeedLibrary::new_for_test();
+        let mut reader_screen = ReaderScreen::new(
+            Rc::new(RefCell::new(library)),
+            create_test_entries(),
+            0,
+            Rc::new(AppHooks::default()),
+            Rc::new(Keymap::default()),
+            ImageProtocol::Off,
+        );
+
+        // With 12 links, "1" could still become 10, 11 or 12
+        reader_screen.link_hint = Some(String::new());
+        assert_eq!(reader_screen.push_hint_digit('1', 12), None);
+        assert_eq!(reader_screen.push_hint_digit('2', 12), Some(12));
+        assert_eq!(reader_screen.link_hint, None);
+
+        reader_screen.link_hint = Some(String::new());
+        assert_eq!(reader_screen.push_hint_digit('3', 12), Some(3));
+
+        // Digits are only taken while following a link
+        assert_eq!(reader_screen.push_hint_digit('3', 12), None);
+    }
+
+    #[test]
+    fn test_search_keys() {
+        let (library, _temp_dir) = FeedLibrary::new_for_test();
+        let mut reader_screen = ReaderScreen::new(
+            Rc::new(RefCell::new(library)),
+            create_test_entries(),
+            0,
+            Rc::new(AppHooks::default()),
+            Rc::new(Keymap::default()),
+            ImageProtocol::Off,
+        );
+        let key = |code| KeyEvent::new(code, crossterm::event::KeyModifiers::NONE);
+
+        for code in [
+            KeyCode::Char('/'),
+            KeyCode::Char('n'),
+            KeyCode::Char('e'),
+            KeyCode::Enter,
+        ] {
+            reader_screen.handle_keypress(key(code)).unwrap();
+        }
+        assert_eq!(reader_screen.search.query(), "ne");
+        assert!(!reader_screen.search.editing);
+
+        // n goes to the next match instead of the next entry while searching
+        reader_screen
+            .handle_keypress(key(KeyCode::Char('n')))
+            .unwrap();
+        assert_eq!(reader_screen.current_index, 0);
+
+        reader_screen.handle_keypress(key(KeyCode::Esc)).unwrap();
+        assert!(!reader_screen.search.is_active());
+        reader_screen
+            .handle_keypress(key(KeyCode::Char('n')))
+            .unwrap();
+        assert_eq!(reader_screen.current_index, 1);
+    }
 }
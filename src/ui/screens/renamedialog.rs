@@ -0,0 +1,160 @@
+use std::{cell::RefCell, rc::Rc};
+
+use color_eyre::eyre::Result;
+use crossterm::event::{Event, KeyEvent, KeyEventKind};
+use ratatui::layout::{Alignment, Constraint, Layout, Margin, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::widgets::{Block, Padding, Paragraph, Wrap};
+
+use crate::app::AppWorkStatus;
+use crate::core::library::feedlibrary::FeedLibrary;
+use crate::core::ui::appscreen::{AppScreen, AppScreenEvent};
+use crate::core::ui::dialog::Dialog;
+use crate::core::ui::instructiondetails::ScreenInstructions;
+use crate::core::ui::keymap::{Action, KeyContext, Keymap, PendingKeys};
+use crate::ui::states::textinputstate::TextInputState;
+use crate::ui::tools::widgets;
+
+/// Renames a category. Feeds are renamed through the edit dialog.
+pub struct RenameDialog {
+    library: Rc<RefCell<FeedLibrary>>,
+    keymap: Rc<Keymap>,
+    pending_keys: PendingKeys,
+    category: String,
+    input: TextInputState,
+    error: Option<String>,
+}
+
+impl RenameDialog {
+    pub fn new(library: Rc<RefCell<FeedLibrary>>, keymap: Rc<Keymap>, category: String) -> Self {
+        Self {
+            library,
+            keymap,
+            pending_keys: PendingKeys::default(),
+            input: TextInputState::new(category.clone()),
+            category,
+            error: None,
+        }
+    }
+
+    fn apply(&mut self) -> Result<AppScreenEvent> {
+        let new_name = self.input.value.trim().to_string();
+        let result = self
+            .library
+            .borrow_mut()
+            .rename_category(&self.category, &new_name);
+
+        match result {
+            Ok(()) => Ok(AppScreenEvent::CloseDialog),
+            Err(e) => {
+                self.error = Some(e.to_string());
+                Ok(AppScreenEvent::None)
+            }
+        }
+    }
+}
+
+impl Dialog for RenameDialog {
+    fn get_size(&self) -> Rect {
+        Rect::new(60, 12, 0, 0)
+    }
+
+    fn as_screen(&self) -> &dyn AppScreen {
+        self
+    }
+
+    fn as_screen_mut(&mut self) -> &mut dyn AppScreen {
+        self
+    }
+}
+
+impl AppScreen for RenameDialog {
+    fn start(&mut self) {}
+
+    fn quit(&mut self) {}
+
+    fn pause(&mut self) {}
+
+    fn unpause(&mut self) {}
+
+    fn render(&mut self, frame: &mut ratatui::Frame, area: Rect) {
+        let theme = {
+            let library = self.library.borrow();
+            library.settings.get_theme().unwrap().clone()
+        };
+
+        let layout = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Length(1),
+            Constraint::Length(3),
+            Constraint::Fill(1),
+        ])
+        .split(area.inner(Margin::new(2, 1)));
+
+        let title = Paragraph::new(self.get_title())
+            .style(Style::new().fg(Color::from_u32(theme.base[0x8])))
+            .alignment(Alignment::Center);
+
+        let current = Paragraph::new(format!("\u{f07c} {}", self.category))
+            .style(Style::new().fg(Color::from_u32(theme.base[0x4])))
+            .alignment(Alignment::Left);
+
+        frame.render_widget(title, layout[0]);
+        frame.render_widget(current, layout[1]);
+        widgets::text_input(frame, layout[2], &self.input, &theme, true);
+
+        if let Some(error) = &self.error {
+            let error = Paragraph::new(error.as_str())
+                .style(Style::new().fg(Color::from_u32(theme.base[0x8])))
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true })
+                .block(Block::new().padding(Padding::top(1)));
+            frame.render_widget(error, layout[3]);
+        }
+    }
+
+    fn handle_event(&mut self, event: Event) -> Result<AppScreenEvent> {
+        match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => self.handle_keypress(key),
+            Event::Mouse(_) => Ok(AppScreenEvent::None),
+            Event::Resize(_, _) => Ok(AppScreenEvent::None),
+            _ => Ok(AppScreenEvent::None),
+        }
+    }
+
+    fn handle_keypress(&mut self, key: KeyEvent) -> Result<AppScreenEvent> {
+        let actions = self
+            .keymap
+            .input_actions(KeyContext::Dialog, &mut self.pending_keys, key);
+
+        match actions.first() {
+            Some(Action::Close) => Ok(AppScreenEvent::CloseDialog),
+            Some(Action::Confirm) => self.apply(),
+            _ => {
+                if self.input.handle_key(key) {
+                    self.error = None;
+                }
+                Ok(AppScreenEvent::None)
+            }
+        }
+    }
+
+    fn get_work_status(&self) -> AppWorkStatus {
+        AppWorkStatus::None
+    }
+
+    fn get_title(&self) -> String {
+        String::from("Rename category")
+    }
+
+    fn get_instructions(&self) -> String {
+        self.keymap.status_line(
+            KeyContext::Dialog,
+            &[(&[Action::Confirm], "rename"), (&[Action::Close], "cancel")],
+        )
+    }
+
+    fn get_full_instructions(&self) -> ScreenInstructions {
+        ScreenInstructions::empty()
+    }
+}
@@ -1,7 +1,7 @@
 use std::{cell::RefCell, rc::Rc};
 
 use color_eyre::eyre::Result;
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::event::{Event, KeyEvent, KeyEventKind, MouseEventKind};
 use ratatui::layout::{Alignment, Constraint, Layout, Margin, Rect};
 use ratatui::style::{Color, Style};
 use ratatui::widgets::{
@@ -14,20 +14,45 @@ use crate::core::library::feedlibrary::FeedLibrary;
 use crate::core::ui::appscreen::{AppScreen, AppScreenEvent};
 use crate::core::ui::dialog::Dialog;
 use crate::core::ui::instructiondetails::ScreenInstructions;
+use crate::core::ui::keymap::{Action, KeyContext, Keymap, PendingKeys};
 use crate::ui::states::themestate::ThemeState;
 
 pub struct ThemeDialog {
     library: Rc<RefCell<FeedLibrary>>,
+    keymap: Rc<Keymap>,
+    pending_keys: PendingKeys,
     state: ThemeState,
 }
 
 impl ThemeDialog {
-    pub fn new(library: Rc<RefCell<FeedLibrary>>) -> Self {
+    pub fn new(library: Rc<RefCell<FeedLibrary>>, keymap: Rc<Keymap>) -> Self {
         Self {
             library,
+            keymap,
+            pending_keys: PendingKeys::default(),
             state: ThemeState::default(),
         }
     }
+
+    fn apply_selected(&mut self) -> Result<AppScreenEvent> {
+        let selected = self.state.get_selected();
+        self.library.borrow_mut().settings.appearance.theme = selected.unwrap();
+        self.library.borrow_mut().settings.appearance.save()?;
+        Ok(AppScreenEvent::None)
+    }
+
+    fn run_action(&mut self, action: Action) -> Result<AppScreenEvent> {
+        match action {
+            Action::Confirm | Action::Close => return Ok(AppScreenEvent::CloseDialog),
+            Action::MoveDown => self.state.select_next(),
+            Action::MoveUp => self.state.select_previous(),
+            Action::First => self.state.select_first(),
+            Action::Last => self.state.select_last(),
+            _ => return Ok(AppScreenEvent::None),
+        }
+
+        self.apply_selected()
+    }
 }
 
 impl Dialog for ThemeDialog {
@@ -106,34 +131,35 @@ impl AppScreen for ThemeDialog {
     fn handle_event(&mut self, event: Event) -> Result<AppScreenEvent> {
         match event {
             Event::Key(key) if key.kind == KeyEventKind::Press => self.handle_keypress(key),
-            Event::Mouse(_) => Ok(AppScreenEvent::None),
+            Event::Mouse(mouse) => match mouse.kind {
+                MouseEventKind::ScrollDown => {
+                    self.state.select_next();
+                    self.apply_selected()
+                }
+                MouseEventKind::ScrollUp => {
+                    self.state.select_previous();
+                    self.apply_selected()
+                }
+                _ => Ok(AppScreenEvent::None),
+            },
             Event::Resize(_, _) => Ok(AppScreenEvent::None),
             _ => Ok(AppScreenEvent::None),
         }
     }
 
     fn handle_keypress(&mut self, key: KeyEvent) -> Result<AppScreenEvent> {
-        match (key.modifiers, key.code) {
-            (_, KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter)
-            | (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => {
-                Ok(AppScreenEvent::CloseDialog)
-            }
-            (_, KeyCode::Down | KeyCode::Char('j')) => {
-                self.state.select_next();
-                let selected = self.state.get_selected();
-                self.library.borrow_mut().settings.appearance.theme = selected.unwrap();
-                self.library.borrow_mut().settings.appearance.save()?;
-                Ok(AppScreenEvent::None)
-            }
-            (_, KeyCode::Up | KeyCode::Char('k')) => {
-                self.state.select_previous();
-                let selected = self.state.get_selected();
-                self.library.borrow_mut().settings.appearance.theme = selected.unwrap();
-                self.library.borrow_mut().settings.appearance.save()?;
-                Ok(AppScreenEvent::None)
+        let actions = self
+            .keymap
+            .actions(KeyContext::Dialog, &mut self.pending_keys, key);
+
+        for action in actions {
+            let event = self.run_action(action)?;
+            if !matches!(event, AppScreenEvent::None) {
+                return Ok(event);
             }
-            _ => Ok(AppScreenEvent::None),
         }
+
+        Ok(AppScreenEvent::None)
     }
 
     fn get_work_status(&self) -> AppWorkStatus {
@@ -145,7 +171,13 @@ impl AppScreen for ThemeDialog {
     }
 
     fn get_instructions(&self) -> String {
-        String::from("j/k: select theme | Esc/q: close url")
+        self.keymap.status_line(
+            KeyContext::Dialog,
+            &[
+                (&[Action::MoveDown, Action::MoveUp], "select theme"),
+                (&[Action::Close], "close"),
+            ],
+        )
     }
 
     fn get_full_instructions(&self) -> ScreenInstructions {
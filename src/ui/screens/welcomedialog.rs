@@ -70,7 +70,8 @@ impl AppScreen for WelcomeDialog {
             .wrap(Wrap { trim: true });
 
         let instructions_text = concat!(
-            "To add a feed, run the following command from your terminal:\n\n",
+            "Dismiss this message and press `a` to add a feed, or run the\n",
+            "following command from your terminal:\n\n",
             "  bulletty add <feed_url> [category]\n\n",
             "Example:\n\n",
             "  bulletty add https://crocidb.com/index.xml Programming\n\n",
@@ -0,0 +1,78 @@
+use crossterm::event::KeyEvent;
+use ratatui::widgets::{ListItem, ListState};
+
+use crate::core::library::feedlibrary::FeedLibrary;
+use crate::ui::states::textinputstate::TextInputState;
+
+/// A category name input that can also pick from the existing categories
+pub struct CategoryPickerState {
+    pub categories: Vec<String>,
+    pub liststate: ListState,
+    pub input: TextInputState,
+}
+
+impl CategoryPickerState {
+    pub fn new(library: &FeedLibrary, category: &str) -> Self {
+        let categories: Vec<String> = library
+            .feedcategories
+            .iter()
+            .map(|c| c.title.clone())
+            .collect();
+
+        let selected = categories.iter().position(|c| c == category);
+
+        Self {
+            categories,
+            liststate: ListState::default().with_selected(selected),
+            input: TextInputState::new(category),
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        self.input.value.trim()
+    }
+
+    pub fn get_items(&self) -> Vec<ListItem<'_>> {
+        self.categories
+            .iter()
+            .map(|c| ListItem::new(format!("\u{f07c} {c}")))
+            .collect()
+    }
+
+    pub fn select_next(&mut self) {
+        let next = self
+            .liststate
+            .selected()
+            .map(|i| (i + 1).min(self.categories.len().saturating_sub(1)))
+            .unwrap_or(0);
+        self.select(next);
+    }
+
+    pub fn select_previous(&mut self) {
+        let previous = self
+            .liststate
+            .selected()
+            .map(|i| i.saturating_sub(1))
+            .unwrap_or(0);
+        self.select(previous);
+    }
+
+    /// Handles an editing key, keeping the list selection in sync with the typed name
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if !self.input.handle_key(key) {
+            return false;
+        }
+
+        let value = self.value().to_string();
+        self.liststate
+            .select(self.categories.iter().position(|c| *c == value));
+        true
+    }
+
+    fn select(&mut self, index: usize) {
+        if let Some(category) = self.categories.get(index) {
+            self.liststate.select(Some(index));
+            self.input.set_value(category.clone());
+        }
+    }
+}
@@ -15,6 +15,9 @@ use crate::{
     ui::states::feedtreestate::{FeedItemInfo, FeedTreeState},
 };
 
+/// Rows taken by each entry of the list: its title, date and description between blank lines
+pub const ENTRY_ITEM_HEIGHT: usize = 5;
+
 pub struct FeedEntryState {
     pub entries: Vec<FeedEntry>,
     pub listatate: ListState,
@@ -199,6 +202,16 @@ impl FeedEntryState {
         }
     }
 
+    /// Selects a clicked entry, returning whether there was one there
+    pub fn select_index(&mut self, index: usize) -> bool {
+        if index < self.entries.len() {
+            self.listatate.select(Some(index));
+            true
+        } else {
+            false
+        }
+    }
+
     pub fn select_first(&mut self) {
         if self.entries.is_empty() {
             return;
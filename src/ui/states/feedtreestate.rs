@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use ratatui::widgets::{ListItem, ListState};
 
-use crate::core::library::feedlibrary::FeedLibrary;
+use crate::core::library::{feedlibrary::FeedLibrary, settings::session::SessionNode};
 
 pub enum FeedItemInfo {
     /// Represents the category title
@@ -122,6 +122,36 @@ impl FeedTreeState {
         }
     }
 
+    /// The selected node, as remembered between sessions
+    pub fn selected_node(&self) -> Option<SessionNode> {
+        match self.get_selected()? {
+            FeedItemInfo::Category(t) => Some(SessionNode::Category { title: t.clone() }),
+            FeedItemInfo::Item(_, c, s) => Some(SessionNode::Feed {
+                category: c.clone(),
+                slug: s.clone(),
+            }),
+            FeedItemInfo::ReadLater => Some(SessionNode::ReadLater),
+            FeedItemInfo::Separator => None,
+        }
+    }
+
+    /// Selects a node remembered from a previous session, returning whether it's still there
+    pub fn select_node(&mut self, node: &SessionNode) -> bool {
+        let index = self.treeitems.iter().position(|item| match (item, node) {
+            (FeedItemInfo::Category(t), SessionNode::Category { title }) => t == title,
+            (FeedItemInfo::Item(_, c, s), SessionNode::Feed { category, slug }) => {
+                c == category && s == slug
+            }
+            (FeedItemInfo::ReadLater, SessionNode::ReadLater) => true,
+            _ => false,
+        });
+
+        match index {
+            Some(index) => self.select_index(index),
+            None => false,
+        }
+    }
+
     pub fn select_next(&mut self) {
         if self.treeitems.is_empty() {
             return;
@@ -175,6 +205,17 @@ impl FeedTreeState {
             .select(Some(self.treeitems.len().saturating_sub(1)));
     }
 
+    /// Selects a clicked item, returning whether there was a selectable one there
+    pub fn select_index(&mut self, index: usize) -> bool {
+        match self.treeitems.get(index) {
+            None | Some(FeedItemInfo::Separator) => false,
+            Some(_) => {
+                self.listatate.select(Some(index));
+                true
+            }
+        }
+    }
+
     pub fn select_next_category(&mut self) {
         let current = self.listatate.selected().unwrap_or(0);
         for (i, item) in self.treeitems.iter().enumerate().skip(current + 1) {
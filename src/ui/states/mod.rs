@@ -1,3 +1,6 @@
+pub mod categorypickerstate;
 pub mod feedentrystate;
 pub mod feedtreestate;
+pub mod searchstate;
+pub mod textinputstate;
 pub mod themestate;
@@ -0,0 +1,289 @@
+use ratatui::style::Style;
+use ratatui::text::{Line, Span, Text};
+
+use crate::ui::states::textinputstate::TextInputState;
+
+/// Where a search hit is: the line of the text and the char range inside it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextMatch {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Incremental search over a rendered `Text`. Lowercase queries ignore case, like vim's
+/// smartcase.
+#[derive(Default)]
+pub struct SearchState {
+    pub input: TextInputState,
+    /// Whether the query is still being typed
+    pub editing: bool,
+    /// The query the matches were last searched for
+    searched: String,
+    matches: Vec<TextMatch>,
+    current: usize,
+    /// Set when the current match changed, so the view scrolls to it on the next draw
+    jump: bool,
+}
+
+impl SearchState {
+    pub fn start(&mut self) {
+        self.input = TextInputState::default();
+        self.editing = true;
+        self.searched.clear();
+        self.matches.clear();
+        self.current = 0;
+    }
+
+    pub fn query(&self) -> &str {
+        &self.input.value
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.editing || !self.query().is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Finds the matches of the query in the text. A changed query jumps to its first match at
+    /// or after `from_line`.
+    pub fn update(&mut self, text: &Text, from_line: usize) {
+        self.matches = find_matches(text, self.query());
+
+        if self.searched != self.input.value {
+            self.searched = self.input.value.clone();
+            self.current = self
+                .matches
+                .iter()
+                .position(|m| m.line >= from_line)
+                .unwrap_or(0);
+            self.jump = !self.matches.is_empty();
+        } else if self.current >= self.matches.len() {
+            self.current = 0;
+        }
+    }
+
+    pub fn next(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = (self.current + 1) % self.matches.len();
+            self.jump = true;
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = (self.current + self.matches.len() - 1) % self.matches.len();
+            self.jump = true;
+        }
+    }
+
+    /// The match to scroll to, once per change of the current match
+    pub fn take_jump(&mut self) -> Option<TextMatch> {
+        if std::mem::take(&mut self.jump) {
+            self.matches.get(self.current).copied()
+        } else {
+            None
+        }
+    }
+
+    /// The match counter, like `3/12`
+    pub fn counter(&self) -> String {
+        if self.matches.is_empty() {
+            String::from("no matches")
+        } else {
+            format!("{}/{}", self.current + 1, self.matches.len())
+        }
+    }
+
+    /// Restyles the matched chars, with a different style for the current match
+    pub fn highlight<'a>(&self, text: Text<'a>, style: Style, current_style: Style) -> Text<'a> {
+        if self.matches.is_empty() {
+            return text;
+        }
+
+        let current = self.matches.get(self.current);
+        let mut text = text;
+        for (index, line) in text.lines.iter_mut().enumerate() {
+            let ranges: Vec<(usize, usize, Style)> = self
+                .matches
+                .iter()
+                .filter(|m| m.line == index)
+                .map(|m| {
+                    let style = if Some(m) == current {
+                        current_style
+                    } else {
+                        style
+                    };
+                    (m.start, m.end, style)
+                })
+                .collect();
+
+            if !ranges.is_empty() {
+                *line = highlight_line(std::mem::take(line), &ranges);
+            }
+        }
+
+        text
+    }
+}
+
+fn chars_match(a: char, b: char, ignore_case: bool) -> bool {
+    if ignore_case {
+        a.to_lowercase().eq(b.to_lowercase())
+    } else {
+        a == b
+    }
+}
+
+pub fn find_matches(text: &Text, query: &str) -> Vec<TextMatch> {
+    let needle: Vec<char> = query.chars().collect();
+    if needle.is_empty() {
+        return vec![];
+    }
+
+    let ignore_case = !query.chars().any(char::is_uppercase);
+    let mut matches = vec![];
+
+    for (index, line) in text.lines.iter().enumerate() {
+        let haystack: Vec<char> = line.spans.iter().flat_map(|s| s.content.chars()).collect();
+        let mut start = 0;
+        while start + needle.len() <= haystack.len() {
+            let found = haystack[start..start + needle.len()]
+                .iter()
+                .zip(&needle)
+                .all(|(a, b)| chars_match(*a, *b, ignore_case));
+
+            if found {
+                matches.push(TextMatch {
+                    line: index,
+                    start,
+                    end: start + needle.len(),
+                });
+                start += needle.len();
+            } else {
+                start += 1;
+            }
+        }
+    }
+
+    matches
+}
+
+/// Splits the spans of a line at the match boundaries, patching the matched parts
+fn highlight_line<'a>(line: Line<'a>, ranges: &[(usize, usize, Style)]) -> Line<'a> {
+    let mut spans: Vec<Span<'a>> = vec![];
+    let mut offset = 0;
+
+    for span in line.spans {
+        let chars: Vec<char> = span.content.chars().collect();
+        let span_end = offset + chars.len();
+
+        // Cut points inside this span
+        let mut cuts: Vec<usize> = vec![offset, span_end];
+        for (start, end, _) in ranges {
+            for cut in [*start, *end] {
+                if cut > offset && cut < span_end {
+                    cuts.push(cut);
+                }
+            }
+        }
+        cuts.sort_unstable();
+        cuts.dedup();
+
+        for pair in cuts.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            let content: String = chars[from - offset..to - offset].iter().collect();
+            let style = ranges
+                .iter()
+                .find(|(start, end, _)| *start <= from && to <= *end)
+                .map(|(_, _, style)| span.style.patch(*style))
+                .unwrap_or(span.style);
+            spans.push(Span::styled(content, style));
+        }
+
+        offset = span_end;
+    }
+
+    let mut highlighted = Line::from(spans).style(line.style);
+    highlighted.alignment = line.alignment;
+    highlighted
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::{Color, Stylize};
+
+    use super::*;
+
+    #[test]
+    fn finds_with_smartcase() {
+        let text = Text::from_iter([
+            Line::from_iter(["Rust ", "is fast"]),
+            Line::from("rust rust"),
+        ]);
+
+        assert_eq!(find_matches(&text, "rust").len(), 3);
+        assert_eq!(
+            find_matches(&text, "Rust"),
+            vec![TextMatch {
+                line: 0,
+                start: 0,
+                end: 4
+            }]
+        );
+        assert_eq!(
+            find_matches(&text, "t is"),
+            vec![TextMatch {
+                line: 0,
+                start: 3,
+                end: 7
+            }]
+        );
+        assert!(find_matches(&text, "").is_empty());
+    }
+
+    #[test]
+    fn highlights_across_spans() {
+        let mut search = SearchState::default();
+        search.start();
+        search.input.set_value("t i");
+
+        let text = Text::from(Line::from_iter([Span::from("Rust "), "is".bold()]));
+        search.update(&text, 0);
+        assert_eq!(search.counter(), "1/1");
+
+        let hit = Style::new().bg(Color::Red);
+        let highlighted = search.highlight(text, Style::new(), hit);
+        assert_eq!(
+            highlighted.lines[0].spans,
+            vec![
+                Span::from("Rus"),
+                Span::styled("t ", hit),
+                Span::styled("i", Style::new().bold().bg(Color::Red)),
+                Span::styled("s", Style::new().bold()),
+            ]
+        );
+    }
+
+    #[test]
+    fn cycles_matches() {
+        let mut search = SearchState::default();
+        search.start();
+        search.input.set_value("a");
+
+        let text = Text::from_iter(["a", "b", "a", "a"]);
+        search.update(&text, 1);
+        assert_eq!(search.counter(), "2/3");
+        assert_eq!(search.take_jump().map(|m| m.line), Some(2));
+        assert_eq!(search.take_jump(), None);
+
+        search.editing = false;
+        search.next();
+        search.next();
+        assert_eq!(search.counter(), "1/3");
+        search.previous();
+        assert_eq!(search.take_jump().map(|m| m.line), Some(3));
+    }
+}
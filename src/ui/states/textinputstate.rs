@@ -0,0 +1,112 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use unicode_width::UnicodeWidthStr;
+
+/// Single line text input used by the dialogs
+#[derive(Default)]
+pub struct TextInputState {
+    pub value: String,
+    /// Cursor position, in chars
+    cursor: usize,
+}
+
+impl TextInputState {
+    pub fn new(value: impl Into<String>) -> Self {
+        let value = value.into();
+        let cursor = value.chars().count();
+        Self { value, cursor }
+    }
+
+    pub fn set_value(&mut self, value: impl Into<String>) {
+        self.value = value.into();
+        self.cursor = self.value.chars().count();
+    }
+
+    /// Handles an editing key. Returns false when the key isn't an editing key.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match (key.modifiers, key.code) {
+            (KeyModifiers::CONTROL, KeyCode::Char('u') | KeyCode::Char('U')) => {
+                self.value.clear();
+                self.cursor = 0;
+            }
+            (KeyModifiers::NONE | KeyModifiers::SHIFT, KeyCode::Char(c)) => {
+                let index = self.byte_index();
+                self.value.insert(index, c);
+                self.cursor += 1;
+            }
+            (_, KeyCode::Backspace) => {
+                if self.cursor > 0 {
+                    self.cursor -= 1;
+                    let index = self.byte_index();
+                    self.value.remove(index);
+                }
+            }
+            (_, KeyCode::Delete) => {
+                if self.cursor < self.value.chars().count() {
+                    let index = self.byte_index();
+                    self.value.remove(index);
+                }
+            }
+            (_, KeyCode::Left) => {
+                self.cursor = self.cursor.saturating_sub(1);
+            }
+            (_, KeyCode::Right) => {
+                self.cursor = (self.cursor + 1).min(self.value.chars().count());
+            }
+            (_, KeyCode::Home) => {
+                self.cursor = 0;
+            }
+            (_, KeyCode::End) => {
+                self.cursor = self.value.chars().count();
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Cursor column, in terminal cells
+    pub fn cursor_offset(&self) -> u16 {
+        let before: String = self.value.chars().take(self.cursor).collect();
+        UnicodeWidthStr::width(before.as_str()) as u16
+    }
+
+    fn byte_index(&self) -> usize {
+        self.value
+            .char_indices()
+            .nth(self.cursor)
+            .map(|(i, _)| i)
+            .unwrap_or(self.value.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn edits_at_cursor() {
+        let mut input = TextInputState::new("tech");
+        input.handle_key(key(KeyCode::Home));
+        input.handle_key(key(KeyCode::Char('a')));
+        input.handle_key(key(KeyCode::End));
+        input.handle_key(key(KeyCode::Backspace));
+        assert_eq!(input.value, "atec");
+        assert_eq!(input.cursor_offset(), 4);
+    }
+
+    #[test]
+    fn handles_multibyte_chars() {
+        let mut input = TextInputState::new("notícias");
+        input.handle_key(key(KeyCode::Left));
+        input.handle_key(key(KeyCode::Left));
+        input.handle_key(key(KeyCode::Left));
+        input.handle_key(key(KeyCode::Left));
+        input.handle_key(key(KeyCode::Backspace));
+        assert_eq!(input.value, "notcias");
+        assert_eq!(input.cursor_offset(), 3);
+    }
+}
@@ -0,0 +1,266 @@
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{LazyLock, Mutex},
+};
+
+use syntect::{
+    highlighting::{
+        Color, FontStyle, ScopeSelectors, StyleModifier, Theme as SyntaxTheme, ThemeItem, ThemeSet,
+        ThemeSettings,
+    },
+    parsing::{SyntaxReference, SyntaxSet},
+};
+
+use crate::core::library::settings::theme::Theme;
+
+pub static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+static THEME_SET: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+
+/// Syntax themes made from bulletty themes, by palette. There's one per theme picked in a
+/// session, so they're kept for good.
+static SYNTAX_THEMES: LazyLock<Mutex<HashMap<[u32; 16], &'static SyntaxTheme>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Scopes and the base16 color they take, as in the base16 TextMate templates
+const SCOPES: &[(&str, usize, bool)] = &[
+    ("comment, punctuation.definition.comment", 0x03, true),
+    ("string, punctuation.definition.string", 0x0b, false),
+    ("constant.character.escape, string.regexp", 0x0c, false),
+    ("constant, constant.numeric, constant.language", 0x09, false),
+    ("variable, variable.other", 0x08, false),
+    ("variable.parameter", 0x09, false),
+    (
+        "keyword, storage, storage.type, storage.modifier",
+        0x0e,
+        false,
+    ),
+    ("keyword.operator, punctuation", 0x05, false),
+    (
+        "entity.name.function, support.function, meta.function-call",
+        0x0d,
+        false,
+    ),
+    (
+        "entity.name.type, entity.name.class, entity.other.inherited-class, support.type, support.class",
+        0x0a,
+        false,
+    ),
+    ("entity.name.tag, meta.tag", 0x08, false),
+    ("entity.other.attribute-name", 0x09, false),
+    ("entity.name.namespace, entity.name.module", 0x0a, false),
+    ("markup.heading, entity.name.section", 0x0d, false),
+    ("markup.inserted", 0x0b, false),
+    ("markup.deleted, invalid", 0x08, false),
+    ("markup.changed", 0x0e, false),
+    ("meta.preprocessor, keyword.control.import", 0x0e, false),
+];
+
+fn color(rgb: u32) -> Color {
+    Color {
+        r: (rgb >> 16) as u8,
+        g: (rgb >> 8) as u8,
+        b: rgb as u8,
+        a: 0xff,
+    }
+}
+
+/// A syntax theme in the colors of the bulletty theme, so code matches the rest of the reader
+fn derive_theme(theme: &Theme) -> SyntaxTheme {
+    let scopes = SCOPES
+        .iter()
+        .filter_map(|(scope, base, italic)| {
+            Some(ThemeItem {
+                scope: ScopeSelectors::from_str(scope).ok()?,
+                style: StyleModifier {
+                    foreground: Some(color(theme.base[*base])),
+                    background: None,
+                    font_style: italic.then_some(FontStyle::ITALIC),
+                },
+            })
+        })
+        .collect();
+
+    SyntaxTheme {
+        name: Some(theme.scheme.clone()),
+        author: Some(theme.author.clone()),
+        settings: ThemeSettings {
+            foreground: Some(color(theme.base[0x05])),
+            background: Some(color(theme.base[0x00])),
+            ..Default::default()
+        },
+        scopes,
+    }
+}
+
+/// The syntax theme for code in `theme`, or syntect's own when there's no theme
+pub fn syntax_theme(theme: Option<&Theme>) -> &'static SyntaxTheme {
+    let Some(theme) = theme else {
+        return &THEME_SET.themes["base16-ocean.dark"];
+    };
+
+    let mut themes = SYNTAX_THEMES.lock().unwrap_or_else(|e| e.into_inner());
+    themes
+        .entry(theme.base)
+        .or_insert_with(|| Box::leak(Box::new(derive_theme(theme))))
+}
+
+/// Telltale bits of code, by the token syntect knows the language by
+const SIGNS: &[(&str, &[&str])] = &[
+    (
+        "rs",
+        &[
+            "fn ", "let mut ", "impl ", "pub fn", "::", "-> ", "println!", "&self", "use std",
+            "match ",
+        ],
+    ),
+    (
+        "py",
+        &[
+            "def ", "import ", "self.", "elif ", "print(", "None", "__init__", "):\n", "True",
+        ],
+    ),
+    (
+        "go",
+        &["package ", "func ", ":= ", "fmt.", "err != nil", "go func"],
+    ),
+    (
+        "java",
+        &[
+            "public class",
+            "System.out",
+            "private ",
+            "public static void",
+            "import java",
+            "@Override",
+        ],
+    ),
+    (
+        "js",
+        &[
+            "function ",
+            "const ",
+            "=> ",
+            "console.log",
+            "let ",
+            "require(",
+            "export ",
+            "undefined",
+            "===",
+        ],
+    ),
+    (
+        "c",
+        &[
+            "#include", "printf(", "int main", "NULL", "malloc(", "sizeof(",
+        ],
+    ),
+    (
+        "sql",
+        &[
+            "SELECT ",
+            "FROM ",
+            "WHERE ",
+            "INSERT INTO",
+            "CREATE TABLE",
+            "JOIN ",
+        ],
+    ),
+    (
+        "html",
+        &[
+            "<div",
+            "</div>",
+            "<html",
+            "<p>",
+            "<span",
+            "<a href",
+            "<!DOCTYPE",
+        ],
+    ),
+    (
+        "sh",
+        &[
+            "$ ", "sudo ", "echo ", "#!/bin", "apt ", "cd ", "| grep", "export ", "&& ", "fi\n",
+        ],
+    ),
+];
+
+/// Guesses the language of code without an info string. Needs a couple of telltale bits to be
+/// sure, as a wrong guess reads worse than no highlighting.
+pub fn guess_language(code: &str) -> Option<&'static str> {
+    let trimmed = code.trim();
+    if (trimmed.starts_with('{') || trimmed.starts_with('['))
+        && serde_json::from_str::<serde_json::Value>(trimmed).is_ok()
+    {
+        return Some("json");
+    }
+
+    SIGNS
+        .iter()
+        .map(|(language, signs)| {
+            let score = signs.iter().filter(|s| code.contains(*s)).count();
+            (*language, score)
+        })
+        .filter(|(_, score)| *score >= 2)
+        // The first language wins ties
+        .rev()
+        .max_by_key(|(_, score)| *score)
+        .map(|(language, _)| language)
+}
+
+/// The syntax of code without an info string: from a shebang or modeline first, or a guess
+pub fn detect_syntax(code: &str) -> Option<&'static SyntaxReference> {
+    let first_line = code.lines().next().unwrap_or_default();
+    SYNTAX_SET
+        .find_syntax_by_first_line(first_line)
+        .or_else(|| SYNTAX_SET.find_syntax_by_token(guess_language(code)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_guess_language() {
+        assert_eq!(
+            guess_language(indoc! {"
+                fn main() {
+                    let mut total = 0;
+                    println!(\"{total}\");
+                }
+            "}),
+            Some("rs")
+        );
+        assert_eq!(
+            guess_language(indoc! {"
+                def greet(name):
+                    print(f\"Hello {name}\")
+            "}),
+            Some("py")
+        );
+        assert_eq!(guess_language("{\"a\": [1, 2]}"), Some("json"));
+        assert_eq!(guess_language("just some words, really"), None);
+        assert_eq!(guess_language("let total = first + second;"), None);
+
+        assert_eq!(
+            detect_syntax("#!/usr/bin/env python3\nx = 1").map(|s| s.name.as_str()),
+            Some("Python")
+        );
+    }
+
+    #[test]
+    fn test_syntax_theme() {
+        let mut theme = Theme::default();
+        theme.base[0x03] = 0x112233;
+        theme.base[0x05] = 0x445566;
+
+        let syntax = syntax_theme(Some(&theme));
+        assert_eq!(syntax.settings.foreground, Some(color(0x445566)));
+        assert_eq!(syntax.scopes[0].style.foreground, Some(color(0x112233)));
+        assert_eq!(syntax.scopes[0].style.font_style, Some(FontStyle::ITALIC));
+        assert!(std::ptr::eq(syntax, syntax_theme(Some(&theme))));
+    }
+}
@@ -0,0 +1,162 @@
+//! Just enough HTML to read the fragments left in Markdown articles
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HtmlToken {
+    Open(String),
+    Close(String),
+    Text(String),
+}
+
+/// Splits HTML into tags and text. Tag names are lowercased, attributes and comments are
+/// dropped, and self-closing tags only open.
+pub fn tokens(html: &str) -> Vec<HtmlToken> {
+    let mut tokens = vec![];
+    let mut text = String::new();
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        let after = &rest[start + 1..];
+        let is_tag = after.starts_with(|c: char| c.is_ascii_alphabetic() || c == '/' || c == '!');
+        if !is_tag {
+            text.push_str(&rest[..=start]);
+            rest = after;
+            continue;
+        }
+
+        text.push_str(&rest[..start]);
+        if !text.is_empty() {
+            tokens.push(HtmlToken::Text(decode_entities(&std::mem::take(&mut text))));
+        }
+
+        if let Some(comment) = after.strip_prefix("!--") {
+            rest = comment
+                .find("-->")
+                .map(|end| &comment[end + 3..])
+                .unwrap_or("");
+            continue;
+        }
+
+        let Some(end) = after.find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = &after[..end];
+        rest = &after[end + 1..];
+
+        let (closing, tag) = match tag.strip_prefix('/') {
+            Some(tag) => (true, tag),
+            None => (false, tag),
+        };
+        let name: String = tag
+            .chars()
+            .take_while(char::is_ascii_alphanumeric)
+            .collect::<String>()
+            .to_lowercase();
+        if name.is_empty() {
+            continue;
+        }
+
+        tokens.push(if closing {
+            HtmlToken::Close(name)
+        } else {
+            HtmlToken::Open(name)
+        });
+    }
+
+    text.push_str(rest);
+    if !text.is_empty() {
+        tokens.push(HtmlToken::Text(decode_entities(&text)));
+    }
+
+    tokens
+}
+
+const ENTITIES: &[(&str, &str)] = &[
+    ("amp", "&"),
+    ("lt", "<"),
+    ("gt", ">"),
+    ("quot", "\""),
+    ("apos", "'"),
+    ("nbsp", "\u{00A0}"),
+    ("mdash", "—"),
+    ("ndash", "–"),
+    ("hellip", "…"),
+    ("copy", "©"),
+    ("reg", "®"),
+    ("trade", "™"),
+    ("times", "×"),
+    ("rarr", "→"),
+    ("larr", "←"),
+];
+
+pub fn decode_entities(text: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+
+        let entity = after.find(';').and_then(|end| {
+            let name = &after[..end];
+            let value = match name.strip_prefix('#') {
+                Some(code) => {
+                    let code = match code.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                        None => code.parse().ok(),
+                    };
+                    code.and_then(char::from_u32).map(String::from)
+                }
+                None => ENTITIES
+                    .iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, v)| v.to_string()),
+            };
+            value.map(|v| (v, end))
+        });
+
+        match entity {
+            Some((value, end)) => {
+                decoded.push_str(&value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = after;
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens() {
+        assert_eq!(
+            tokens("<p class=\"x\">Press <KBD>Ctrl</kbd>&nbsp;+ 1 < 2<br/><!-- note --></p>"),
+            vec![
+                HtmlToken::Open(String::from("p")),
+                HtmlToken::Text(String::from("Press ")),
+                HtmlToken::Open(String::from("kbd")),
+                HtmlToken::Text(String::from("Ctrl")),
+                HtmlToken::Close(String::from("kbd")),
+                HtmlToken::Text(String::from("\u{00A0}+ 1 < 2")),
+                HtmlToken::Open(String::from("br")),
+                HtmlToken::Close(String::from("p")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(
+            decode_entities("a &amp; b &#8212; &#x41; &bogus; &"),
+            "a & b — A &bogus; &"
+        );
+    }
+}
@@ -0,0 +1,310 @@
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    io::{self, Cursor, Write},
+    rc::Rc,
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use crossterm::{cursor::MoveTo, queue, style::Print};
+use icy_sixel::{DiffusionMethod, MethodForLargest, MethodForRep, PixelFormat, Quality};
+use image::{DynamicImage, ImageFormat, imageops::FilterType};
+use ratatui::{
+    buffer::{Buffer, CellDiffOption},
+    layout::Rect,
+};
+use tracing::error;
+
+use crate::core::ui::graphics::ImageProtocol;
+
+/// Images are never taller than this, so a figure doesn't take over the whole reader
+pub const MAX_IMAGE_ROWS: u16 = 20;
+
+/// Cell size used when the terminal doesn't report its size in pixels
+const DEFAULT_CELL_SIZE: (u32, u32) = (8, 16);
+
+/// Kitty takes the image data in chunks of at most this size
+const KITTY_CHUNK: usize = 4096;
+
+/// Size of a terminal cell, in pixels
+pub fn cell_size() -> (u32, u32) {
+    match crossterm::terminal::window_size() {
+        Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => (
+            (size.width / size.columns) as u32,
+            (size.height / size.rows) as u32,
+        ),
+        _ => DEFAULT_CELL_SIZE,
+    }
+}
+
+/// The cells an image takes: as wide as its pixels, but no wider than `max_cols` or taller than
+/// `max_rows`, keeping its aspect ratio
+pub fn fit(image: &DynamicImage, max_cols: u16, max_rows: u16, cell: (u32, u32)) -> (u16, u16) {
+    let (width, height) = (image.width().max(1) as u64, image.height().max(1) as u64);
+    let (cell_width, cell_height) = (cell.0.max(1) as u64, cell.1.max(1) as u64);
+
+    let mut cols = width.div_ceil(cell_width).clamp(1, max_cols.max(1) as u64);
+    let mut rows = (height * cols * cell_width)
+        .div_ceil(width * cell_height)
+        .max(1);
+
+    if rows > max_rows as u64 {
+        rows = max_rows.max(1) as u64;
+        cols = (width * rows * cell_height)
+            .div_ceil(height * cell_width)
+            .clamp(1, max_cols.max(1) as u64);
+    }
+
+    (cols as u16, rows as u16)
+}
+
+/// Id of an image for protocols that keep images around, like kitty
+pub fn image_id(url: &str) -> u32 {
+    let mut hasher = DefaultHasher::new();
+    url.hash(&mut hasher);
+    // zero means "no id" to kitty
+    (hasher.finish() as u32).max(1)
+}
+
+fn encode_png(image: &DynamicImage) -> Option<Vec<u8>> {
+    let mut png = Cursor::new(vec![]);
+    match image.write_to(&mut png, ImageFormat::Png) {
+        Ok(_) => Some(png.into_inner()),
+        Err(e) => {
+            error!("Couldn't encode image: {e}");
+            None
+        }
+    }
+}
+
+/// The escape sequence that draws an image over `cols` x `rows` cells from the cursor
+pub fn encode(
+    protocol: ImageProtocol,
+    image: &DynamicImage,
+    (cols, rows): (u16, u16),
+    cell: (u32, u32),
+    id: u32,
+) -> Option<String> {
+    let image = image.resize(
+        cols as u32 * cell.0,
+        rows as u32 * cell.1,
+        FilterType::Triangle,
+    );
+
+    match protocol {
+        ImageProtocol::Kitty => {
+            let data = BASE64.encode(encode_png(&image)?);
+            let chunks: Vec<&[u8]> = data.as_bytes().chunks(KITTY_CHUNK).collect();
+            let mut escape = String::new();
+
+            for (index, chunk) in chunks.iter().enumerate() {
+                let more = u8::from(index + 1 < chunks.len());
+                let chunk = String::from_utf8_lossy(chunk);
+                if index == 0 {
+                    escape.push_str(&format!(
+                        "\x1b_Gf=100,a=T,t=d,i={id},p=1,c={cols},r={rows},C=1,q=2,m={more};{chunk}\x1b\\"
+                    ));
+                } else {
+                    escape.push_str(&format!("\x1b_Gm={more};{chunk}\x1b\\"));
+                }
+            }
+
+            Some(escape)
+        }
+        ImageProtocol::Iterm2 => {
+            let png = encode_png(&image)?;
+            Some(format!(
+                "\x1b]1337;File=inline=1;size={};width={cols};height={rows};preserveAspectRatio=1;doNotMoveCursor=1:{}\x07",
+                png.len(),
+                BASE64.encode(&png)
+            ))
+        }
+        ImageProtocol::Sixel => {
+            let rgba = image.to_rgba8();
+            match icy_sixel::sixel_string(
+                rgba.as_raw(),
+                rgba.width() as i32,
+                rgba.height() as i32,
+                PixelFormat::RGBA8888,
+                DiffusionMethod::Stucki,
+                MethodForLargest::Auto,
+                MethodForRep::Auto,
+                Quality::HIGH,
+            ) {
+                Ok(sixel) => Some(sixel),
+                Err(e) => {
+                    error!("Couldn't encode image as sixel: {e}");
+                    None
+                }
+            }
+        }
+        ImageProtocol::Auto | ImageProtocol::Off => None,
+    }
+}
+
+#[derive(Clone, PartialEq)]
+struct Placement {
+    x: u16,
+    y: u16,
+    id: u32,
+    escape: Rc<String>,
+}
+
+/// Images drawn straight to the terminal. Their escape sequences can't go through the
+/// ratatui buffer, which would take every char of them for a cell, so the area they cover is
+/// skipped when drawing the frame and the images are written once the frame is out.
+pub struct TerminalImages {
+    protocol: ImageProtocol,
+    pending: Vec<Placement>,
+    shown: Vec<Placement>,
+    /// Encoded images by URL and size in cells
+    encoded: HashMap<(String, u16, u16), Rc<String>>,
+}
+
+impl TerminalImages {
+    pub fn new(protocol: ImageProtocol) -> Self {
+        Self {
+            protocol,
+            pending: vec![],
+            shown: vec![],
+            encoded: HashMap::new(),
+        }
+    }
+
+    /// The escape sequence for an image at a size, encoding it the first time
+    pub fn encoded(
+        &mut self,
+        url: &str,
+        image: &DynamicImage,
+        size: (u16, u16),
+        cell: (u32, u32),
+    ) -> Option<Rc<String>> {
+        let key = (url.to_string(), size.0, size.1);
+        if let Some(escape) = self.encoded.get(&key) {
+            return Some(escape.clone());
+        }
+
+        let escape = Rc::new(encode(self.protocol, image, size, cell, image_id(url))?);
+        self.encoded.insert(key, escape.clone());
+        Some(escape)
+    }
+
+    /// Keeps the frame from drawing over `area`, and draws the image there after it
+    pub fn place(&mut self, buffer: &mut Buffer, area: Rect, url: &str, escape: Rc<String>) {
+        let area = area.intersection(buffer.area);
+        if area.is_empty() {
+            return;
+        }
+
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                buffer[(x, y)].set_diff_option(CellDiffOption::Skip);
+            }
+        }
+
+        self.pending.push(Placement {
+            x: area.x,
+            y: area.y,
+            id: image_id(url),
+            escape,
+        });
+    }
+
+    /// Writes the images placed in the last frame. Images that didn't move aren't written
+    /// again, unless something was drawn over them. When a dialog covers the screen, the images
+    /// are taken away until it's gone.
+    pub fn flush(&mut self, covered: bool) {
+        let placed = std::mem::take(&mut self.pending);
+        if covered {
+            self.delete(&self.shown);
+            self.shown.clear();
+            return;
+        }
+
+        if placed == self.shown {
+            return;
+        }
+
+        let gone: Vec<Placement> = self
+            .shown
+            .iter()
+            .filter(|s| !placed.iter().any(|p| p.id == s.id))
+            .cloned()
+            .collect();
+        self.delete(&gone);
+
+        let mut stdout = io::stdout();
+        for placement in &placed {
+            if let Err(e) = queue!(
+                stdout,
+                MoveTo(placement.x, placement.y),
+                Print(placement.escape.as_str())
+            ) {
+                error!("Couldn't draw image: {e}");
+            }
+        }
+        if let Err(e) = stdout.flush() {
+            error!("Couldn't draw images: {e}");
+        }
+
+        self.shown = placed;
+    }
+
+    /// Takes every image off the screen
+    pub fn clear(&mut self) {
+        self.delete(&self.shown);
+        self.shown.clear();
+        self.pending.clear();
+    }
+
+    /// Kitty keeps images around until they're deleted. The other protocols draw into the
+    /// cells, so the frame drawing over them is enough.
+    fn delete(&self, placements: &[Placement]) {
+        if self.protocol != ImageProtocol::Kitty || placements.is_empty() {
+            return;
+        }
+
+        let mut stdout = io::stdout();
+        for placement in placements {
+            let _ = queue!(
+                stdout,
+                Print(format!("\x1b_Ga=d,d=i,i={},q=2\x1b\\", placement.id))
+            );
+        }
+        let _ = stdout.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::RgbImage;
+
+    use super::*;
+
+    #[test]
+    fn test_fit() {
+        let wide = DynamicImage::ImageRgb8(RgbImage::new(800, 200));
+        assert_eq!(fit(&wide, 60, MAX_IMAGE_ROWS, (8, 16)), (60, 8));
+
+        let small = DynamicImage::ImageRgb8(RgbImage::new(80, 32));
+        assert_eq!(fit(&small, 60, MAX_IMAGE_ROWS, (8, 16)), (10, 2));
+
+        let tall = DynamicImage::ImageRgb8(RgbImage::new(200, 1600));
+        assert_eq!(fit(&tall, 60, MAX_IMAGE_ROWS, (8, 16)), (5, 20));
+    }
+
+    #[test]
+    fn test_encode() {
+        let image = DynamicImage::ImageRgb8(RgbImage::new(16, 16));
+
+        let kitty = encode(ImageProtocol::Kitty, &image, (2, 1), (8, 16), 7).unwrap();
+        assert!(kitty.starts_with("\x1b_Gf=100,a=T,t=d,i=7,p=1,c=2,r=1,"));
+        assert!(kitty.ends_with("\x1b\\"));
+
+        let iterm = encode(ImageProtocol::Iterm2, &image, (2, 1), (8, 16), 7).unwrap();
+        assert!(iterm.starts_with("\x1b]1337;File=inline=1;"));
+
+        assert!(encode(ImageProtocol::Sixel, &image, (2, 1), (8, 16), 7).is_some());
+        assert!(encode(ImageProtocol::Off, &image, (2, 1), (8, 16), 7).is_none());
+    }
+}
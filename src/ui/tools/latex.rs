@@ -0,0 +1,552 @@
+//! Turns LaTeX math into Unicode text, for the formulas simple enough to read that way
+
+/// Commands that stand for a single symbol
+const SYMBOLS: &[(&str, &str)] = &[
+    // Greek
+    ("alpha", "α"),
+    ("beta", "β"),
+    ("gamma", "γ"),
+    ("delta", "δ"),
+    ("epsilon", "ϵ"),
+    ("varepsilon", "ε"),
+    ("zeta", "ζ"),
+    ("eta", "η"),
+    ("theta", "θ"),
+    ("vartheta", "ϑ"),
+    ("iota", "ι"),
+    ("kappa", "κ"),
+    ("lambda", "λ"),
+    ("mu", "μ"),
+    ("nu", "ν"),
+    ("xi", "ξ"),
+    ("pi", "π"),
+    ("varpi", "ϖ"),
+    ("rho", "ρ"),
+    ("varrho", "ϱ"),
+    ("sigma", "σ"),
+    ("varsigma", "ς"),
+    ("tau", "τ"),
+    ("upsilon", "υ"),
+    ("phi", "ϕ"),
+    ("varphi", "φ"),
+    ("chi", "χ"),
+    ("psi", "ψ"),
+    ("omega", "ω"),
+    ("Gamma", "Γ"),
+    ("Delta", "Δ"),
+    ("Theta", "Θ"),
+    ("Lambda", "Λ"),
+    ("Xi", "Ξ"),
+    ("Pi", "Π"),
+    ("Sigma", "Σ"),
+    ("Upsilon", "Υ"),
+    ("Phi", "Φ"),
+    ("Psi", "Ψ"),
+    ("Omega", "Ω"),
+    // Operators and relations
+    ("times", "×"),
+    ("cdot", "⋅"),
+    ("div", "÷"),
+    ("pm", "±"),
+    ("mp", "∓"),
+    ("leq", "≤"),
+    ("le", "≤"),
+    ("geq", "≥"),
+    ("ge", "≥"),
+    ("neq", "≠"),
+    ("ne", "≠"),
+    ("approx", "≈"),
+    ("equiv", "≡"),
+    ("sim", "∼"),
+    ("simeq", "≃"),
+    ("cong", "≅"),
+    ("propto", "∝"),
+    ("ll", "≪"),
+    ("gg", "≫"),
+    ("infty", "∞"),
+    ("sum", "∑"),
+    ("prod", "∏"),
+    ("int", "∫"),
+    ("iint", "∬"),
+    ("oint", "∮"),
+    ("partial", "∂"),
+    ("nabla", "∇"),
+    ("to", "→"),
+    ("rightarrow", "→"),
+    ("leftarrow", "←"),
+    ("gets", "←"),
+    ("leftrightarrow", "↔"),
+    ("Rightarrow", "⇒"),
+    ("Leftarrow", "⇐"),
+    ("Leftrightarrow", "⇔"),
+    ("implies", "⟹"),
+    ("iff", "⟺"),
+    ("mapsto", "↦"),
+    ("in", "∈"),
+    ("notin", "∉"),
+    ("ni", "∋"),
+    ("subset", "⊂"),
+    ("subseteq", "⊆"),
+    ("supset", "⊃"),
+    ("supseteq", "⊇"),
+    ("cup", "∪"),
+    ("cap", "∩"),
+    ("setminus", "∖"),
+    ("emptyset", "∅"),
+    ("varnothing", "∅"),
+    ("forall", "∀"),
+    ("exists", "∃"),
+    ("neg", "¬"),
+    ("lnot", "¬"),
+    ("land", "∧"),
+    ("wedge", "∧"),
+    ("lor", "∨"),
+    ("vee", "∨"),
+    ("oplus", "⊕"),
+    ("otimes", "⊗"),
+    ("circ", "∘"),
+    ("bullet", "•"),
+    ("star", "⋆"),
+    ("ast", "∗"),
+    ("ldots", "…"),
+    ("dots", "…"),
+    ("cdots", "⋯"),
+    ("vdots", "⋮"),
+    ("ddots", "⋱"),
+    ("prime", "′"),
+    ("angle", "∠"),
+    ("perp", "⊥"),
+    ("parallel", "∥"),
+    ("mid", "∣"),
+    ("vert", "|"),
+    ("Vert", "‖"),
+    ("langle", "⟨"),
+    ("rangle", "⟩"),
+    ("lceil", "⌈"),
+    ("rceil", "⌉"),
+    ("lfloor", "⌊"),
+    ("rfloor", "⌋"),
+    ("hbar", "ℏ"),
+    ("ell", "ℓ"),
+    ("Re", "ℜ"),
+    ("Im", "ℑ"),
+    ("aleph", "ℵ"),
+    ("degree", "°"),
+    ("quad", "  "),
+    ("qquad", "    "),
+];
+
+/// Commands written as their name, like `\sin`
+const FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh",
+    "log", "ln", "lg", "exp", "lim", "liminf", "limsup", "max", "min", "sup", "inf", "det", "dim",
+    "ker", "deg", "gcd", "arg", "Pr", "mod", "bmod",
+];
+
+/// Commands whose argument is shown as it is, just without the font
+const FONTS: &[&str] = &[
+    "mathrm",
+    "mathit",
+    "mathbf",
+    "mathsf",
+    "mathtt",
+    "boldsymbol",
+    "operatorname",
+    "textbf",
+    "textit",
+];
+
+const SUPERSCRIPTS: &[(char, char)] = &[
+    ('0', '⁰'),
+    ('1', '¹'),
+    ('2', '²'),
+    ('3', '³'),
+    ('4', '⁴'),
+    ('5', '⁵'),
+    ('6', '⁶'),
+    ('7', '⁷'),
+    ('8', '⁸'),
+    ('9', '⁹'),
+    ('+', '⁺'),
+    ('-', '⁻'),
+    ('=', '⁼'),
+    ('(', '⁽'),
+    (')', '⁾'),
+    ('a', 'ᵃ'),
+    ('b', 'ᵇ'),
+    ('c', 'ᶜ'),
+    ('d', 'ᵈ'),
+    ('e', 'ᵉ'),
+    ('f', 'ᶠ'),
+    ('g', 'ᵍ'),
+    ('h', 'ʰ'),
+    ('i', 'ⁱ'),
+    ('j', 'ʲ'),
+    ('k', 'ᵏ'),
+    ('l', 'ˡ'),
+    ('m', 'ᵐ'),
+    ('n', 'ⁿ'),
+    ('o', 'ᵒ'),
+    ('p', 'ᵖ'),
+    ('r', 'ʳ'),
+    ('s', 'ˢ'),
+    ('t', 'ᵗ'),
+    ('u', 'ᵘ'),
+    ('v', 'ᵛ'),
+    ('w', 'ʷ'),
+    ('x', 'ˣ'),
+    ('y', 'ʸ'),
+    ('z', 'ᶻ'),
+    ('T', 'ᵀ'),
+    ('′', '′'),
+];
+
+const SUBSCRIPTS: &[(char, char)] = &[
+    ('0', '₀'),
+    ('1', '₁'),
+    ('2', '₂'),
+    ('3', '₃'),
+    ('4', '₄'),
+    ('5', '₅'),
+    ('6', '₆'),
+    ('7', '₇'),
+    ('8', '₈'),
+    ('9', '₉'),
+    ('+', '₊'),
+    ('-', '₋'),
+    ('=', '₌'),
+    ('(', '₍'),
+    (')', '₎'),
+    ('a', 'ₐ'),
+    ('e', 'ₑ'),
+    ('h', 'ₕ'),
+    ('i', 'ᵢ'),
+    ('j', 'ⱼ'),
+    ('k', 'ₖ'),
+    ('l', 'ₗ'),
+    ('m', 'ₘ'),
+    ('n', 'ₙ'),
+    ('o', 'ₒ'),
+    ('p', 'ₚ'),
+    ('r', 'ᵣ'),
+    ('s', 'ₛ'),
+    ('t', 'ₜ'),
+    ('u', 'ᵤ'),
+    ('v', 'ᵥ'),
+    ('x', 'ₓ'),
+];
+
+const DOUBLE_STRUCK: &[(char, char)] = &[
+    ('C', 'ℂ'),
+    ('H', 'ℍ'),
+    ('N', 'ℕ'),
+    ('P', 'ℙ'),
+    ('Q', 'ℚ'),
+    ('R', 'ℝ'),
+    ('Z', 'ℤ'),
+];
+
+fn map_chars(text: &str, table: &[(char, char)]) -> Option<String> {
+    text.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| table.iter().find(|(from, _)| *from == c).map(|(_, to)| *to))
+        .collect()
+}
+
+/// `text` in superscript characters, if they all have one
+pub fn superscript(text: &str) -> Option<String> {
+    map_chars(text, SUPERSCRIPTS)
+}
+
+/// `text` in subscript characters, if they all have one
+pub fn subscript(text: &str) -> Option<String> {
+    map_chars(text, SUBSCRIPTS)
+}
+
+/// Groups and arguments nested deeper than this aren't worth reading as text. The limit also
+/// keeps crafted formulas from running the converter out of stack.
+const MAX_DEPTH: usize = 32;
+
+/// The formula in Unicode, or `None` when it uses something that has no readable form, like
+/// environments or matrices
+pub fn to_unicode(latex: &str) -> Option<String> {
+    let mut converter = Converter {
+        chars: latex.chars().collect(),
+        pos: 0,
+        depth: 0,
+    };
+    let text = converter.convert(false)?;
+    Some(text.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+struct Converter {
+    chars: Vec<char>,
+    pos: usize,
+    /// How many groups and arguments are open
+    depth: usize,
+}
+
+impl Converter {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    /// Converts up to the end of the formula, or up to the closing brace when `in_group`
+    fn convert(&mut self, in_group: bool) -> Option<String> {
+        let mut out = String::new();
+
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '\\' => out.push_str(&self.command()?),
+                '{' => out.push_str(&self.group()?),
+                '}' => return in_group.then_some(out),
+                '^' | '_' => {
+                    let script = self.argument()?;
+                    let mapped = if c == '^' {
+                        superscript(&script)
+                    } else {
+                        subscript(&script)
+                    };
+                    match mapped {
+                        Some(mapped) => out.push_str(&mapped),
+                        None if script.chars().count() == 1 => {
+                            out.push(c);
+                            out.push_str(&script);
+                        }
+                        None => out.push_str(&format!("{c}({script})")),
+                    }
+                }
+                '~' | '&' => out.push(' '),
+                c => out.push(c),
+            }
+        }
+
+        // A group that never closes
+        (!in_group).then_some(out)
+    }
+
+    /// Runs `convert` one level deeper, giving up past `MAX_DEPTH`
+    fn nested(&mut self, convert: impl FnOnce(&mut Self) -> Option<String>) -> Option<String> {
+        if self.depth >= MAX_DEPTH {
+            return None;
+        }
+
+        self.depth += 1;
+        let converted = convert(self);
+        self.depth -= 1;
+        converted
+    }
+
+    /// A `{...}` group, converted, after its opening brace
+    fn group(&mut self) -> Option<String> {
+        self.nested(|converter| converter.convert(true))
+    }
+
+    /// A command argument, converted: a group, a command or a single char
+    fn argument(&mut self) -> Option<String> {
+        self.skip_whitespace();
+
+        self.nested(|converter| {
+            let c = converter.peek()?;
+            converter.pos += 1;
+            match c {
+                '{' => converter.convert(true),
+                '\\' => converter.command(),
+                '}' | '^' | '_' => None,
+                '~' | '&' => Some(String::from(" ")),
+                c => Some(c.to_string()),
+            }
+        })
+    }
+
+    /// The source of a command argument, for the commands that show it as it is
+    fn raw_argument(&mut self) -> Option<String> {
+        self.skip_whitespace();
+
+        match self.peek()? {
+            '{' => {
+                self.pos += 1;
+                self.until_closing()
+            }
+            '\\' => {
+                self.pos += 1;
+                let name = self.name();
+                if name.is_empty() {
+                    let c = self.peek()?;
+                    self.pos += 1;
+                    Some(format!("\\{c}"))
+                } else {
+                    Some(format!("\\{name}"))
+                }
+            }
+            c => {
+                self.pos += 1;
+                Some(c.to_string())
+            }
+        }
+    }
+
+    /// The text of a `{...}` group, after its opening brace
+    fn until_closing(&mut self) -> Option<String> {
+        let mut depth = 1;
+        let mut group = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(group);
+                    }
+                }
+                '\\' => {
+                    // Escaped braces don't count
+                    group.push(c);
+                    if let Some(next) = self.peek() {
+                        self.pos += 1;
+                        group.push(next);
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            group.push(c);
+        }
+        None
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn name(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(c) = self.peek().filter(char::is_ascii_alphabetic) {
+            name.push(c);
+            self.pos += 1;
+        }
+        name
+    }
+
+    /// A command, after its backslash
+    fn command(&mut self) -> Option<String> {
+        let name = self.name();
+
+        if name.is_empty() {
+            let c = self.peek()?;
+            self.pos += 1;
+            return match c {
+                ',' | ';' | ':' | ' ' => Some(String::from(" ")),
+                '!' => Some(String::new()),
+                '\\' => Some(String::from("\n")),
+                '{' | '}' | '%' | '$' | '#' | '_' | '&' | '|' => Some(c.to_string()),
+                _ => None,
+            };
+        }
+
+        if let Some((_, symbol)) = SYMBOLS.iter().find(|(n, _)| *n == name) {
+            return Some(symbol.to_string());
+        }
+
+        if FUNCTIONS.contains(&name.as_str()) {
+            return Some(format!("{name} "));
+        }
+
+        if FONTS.contains(&name.as_str()) {
+            return self.argument();
+        }
+
+        match name.as_str() {
+            "text" | "mbox" => self.raw_argument(),
+            "left" | "right" | "big" | "Big" | "bigg" | "Bigg" | "displaystyle" => {
+                // `\left.` is an invisible delimiter
+                if self.peek() == Some('.') {
+                    self.pos += 1;
+                }
+                Some(String::new())
+            }
+            "mathbb" => map_chars(&self.raw_argument()?, DOUBLE_STRUCK),
+            "frac" | "dfrac" | "tfrac" => {
+                let numerator = self.argument()?;
+                let denominator = self.argument()?;
+                Some(format!(
+                    "{}/{}",
+                    parenthesized(&numerator),
+                    parenthesized(&denominator)
+                ))
+            }
+            "sqrt" => {
+                if self.peek() == Some('[') {
+                    return None;
+                }
+                let radicand = self.argument()?;
+                Some(format!("√{}", parenthesized(&radicand)))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Wraps in parentheses what isn't a single number or symbol
+fn parenthesized(text: &str) -> String {
+    let text = text.trim();
+    if text.chars().count() <= 1 || text.chars().all(|c| c.is_alphanumeric()) {
+        text.to_string()
+    } else {
+        format!("({text})")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_unicode() {
+        assert_eq!(to_unicode("E = mc^2").as_deref(), Some("E = mc²"));
+        assert_eq!(
+            to_unicode(r"\sum_{i=0}^{n} x_i \leq \infty").as_deref(),
+            Some("∑ᵢ₌₀ⁿ xᵢ ≤ ∞")
+        );
+        assert_eq!(
+            to_unicode(r"\frac{a+b}{2} \cdot \sqrt{x}").as_deref(),
+            Some("(a+b)/2 ⋅ √x")
+        );
+        assert_eq!(
+            to_unicode(r"f: \mathbb{R} \to \mathbb{R}").as_deref(),
+            Some("f: ℝ → ℝ")
+        );
+        assert_eq!(
+            to_unicode(r"\sin\theta + \text{error}").as_deref(),
+            Some("sin θ + error")
+        );
+        assert_eq!(to_unicode("x^{q}").as_deref(), Some("x^q"));
+        assert_eq!(to_unicode(r"\begin{matrix} a \end{matrix}"), None);
+        assert_eq!(to_unicode(r"\frac{1}{2"), None);
+        assert_eq!(to_unicode("x}"), None);
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        let nested = |depth: usize| format!("{}x{}", "{".repeat(depth), "}".repeat(depth));
+        assert_eq!(to_unicode(&nested(MAX_DEPTH)).as_deref(), Some("x"));
+        assert_eq!(to_unicode(&nested(MAX_DEPTH + 1)), None);
+        assert_eq!(to_unicode(&nested(20_000)), None);
+        assert_eq!(to_unicode(&format!("{}x", r"\sqrt".repeat(20_000))), None);
+        assert_eq!(
+            to_unicode(r"\frac{\frac{1}{2}}{3}").as_deref(),
+            Some("(1/2)/3")
+        );
+    }
+
+    #[test]
+    fn test_scripts() {
+        assert_eq!(superscript("12").as_deref(), Some("¹²"));
+        assert_eq!(subscript("n+1").as_deref(), Some("ₙ₊₁"));
+        assert_eq!(superscript("q"), None);
+    }
+}
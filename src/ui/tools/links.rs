@@ -0,0 +1,60 @@
+use std::io;
+
+use crossterm::clipboard::CopyToClipboard;
+use tracing::error;
+
+use crate::core::{
+    feed::feedentry::FeedEntry,
+    hooks::{AppHooks, LinkContext},
+    library::feedlibrary::FeedLibrary,
+    ui::{
+        appscreen::AppScreenEvent,
+        notification::{AppNotification, NotificationPriority},
+    },
+};
+use crate::ui::screens::urldialog::UrlDialog;
+
+/// Opens a link of an entry with the `open_link` hook, falling back to the system browser, and
+/// to showing the URL when there's no browser either
+pub fn open_external_url(
+    library: &FeedLibrary,
+    hooks: &AppHooks,
+    url: &str,
+    entry: &FeedEntry,
+) -> AppScreenEvent {
+    let link = LinkContext::for_entry(url, entry, library.entry_feed(entry));
+    if hooks.run_open_link(&link) {
+        return AppScreenEvent::Notify(AppNotification::new(
+            "Link opened externally with user hook",
+            NotificationPriority::Low,
+        ));
+    }
+
+    match open::that(url) {
+        Ok(_) => AppScreenEvent::Notify(AppNotification::new(
+            "Link opened externally",
+            NotificationPriority::Low,
+        )),
+        Err(_) => {
+            error!("Couldn't invoke system browser");
+            AppScreenEvent::OpenDialog(Box::new(UrlDialog::new(url.to_string())))
+        }
+    }
+}
+
+/// Copies a link through the terminal (OSC 52), which also works over ssh
+pub fn copy_to_clipboard(url: &str) -> AppScreenEvent {
+    match crossterm::execute!(io::stdout(), CopyToClipboard::to_clipboard_from(url)) {
+        Ok(_) => AppScreenEvent::Notify(AppNotification::new(
+            "Link copied to the clipboard",
+            NotificationPriority::Low,
+        )),
+        Err(e) => {
+            error!("Couldn't copy to the clipboard: {e}");
+            AppScreenEvent::Notify(AppNotification::new(
+                "Couldn't copy the link",
+                NotificationPriority::High,
+            ))
+        }
+    }
+}
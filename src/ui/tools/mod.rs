@@ -1,2 +1,11 @@
+pub mod highlight;
+pub mod html;
+pub mod images;
+pub mod latex;
+pub mod links;
+pub mod mouse;
 pub mod styles;
+pub mod tables;
 pub mod tuimarkdown;
+pub mod widgets;
+pub mod wrap;
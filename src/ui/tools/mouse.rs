@@ -0,0 +1,172 @@
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Position, Rect},
+};
+use regex::Regex;
+
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
+/// Lines scrolled by one step of the mouse wheel
+pub const WHEEL_STEP: usize = 3;
+
+/// Terminals only report single clicks, so double clicks are told apart by their timing
+#[derive(Default)]
+pub struct ClickTracker {
+    last: Option<(Instant, u16, u16)>,
+}
+
+impl ClickTracker {
+    /// Records a click, returning whether it completes a double click on the same cell
+    pub fn click(&mut self, column: u16, row: u16) -> bool {
+        let now = Instant::now();
+        let double = matches!(self.last, Some((at, c, r))
+            if c == column && r == row && now.duration_since(at) <= DOUBLE_CLICK_INTERVAL);
+
+        self.last = if double {
+            None
+        } else {
+            Some((now, column, row))
+        };
+        double
+    }
+}
+
+pub fn contains(area: Rect, column: u16, row: u16) -> bool {
+    area.contains(Position::new(column, row))
+}
+
+/// The text of each row of an area as it was drawn, so clicks can be matched to what's under
+/// the pointer after the widgets wrapped it
+#[derive(Default)]
+pub struct ScreenText {
+    area: Rect,
+    rows: Vec<Vec<String>>,
+}
+
+impl ScreenText {
+    pub fn capture(buffer: &Buffer, area: Rect) -> Self {
+        let area = area.intersection(buffer.area);
+        let rows = (area.top()..area.bottom())
+            .map(|y| {
+                (area.left()..area.right())
+                    .map(|x| buffer[(x, y)].symbol().to_string())
+                    .collect()
+            })
+            .collect();
+
+        Self { area, rows }
+    }
+
+    fn row_text(&self, index: usize) -> (String, Vec<usize>) {
+        // Byte offset of every cell, so matches can be mapped back to columns
+        let mut text = String::new();
+        let mut offsets = vec![];
+        for symbol in &self.rows[index] {
+            offsets.push(text.len());
+            text.push_str(symbol);
+        }
+        (text, offsets)
+    }
+
+    /// The URL shown at a position. A URL cut by the right edge continues on the next row.
+    pub fn link_at(&self, column: u16, row: u16) -> Option<String> {
+        if !contains(self.area, column, row) {
+            return None;
+        }
+
+        let url_regex = Regex::new(r#"https?://[^\s<>"'\])]+"#).expect("URL regex is valid");
+        let index = (row - self.area.y) as usize;
+        let cell = (column - self.area.x) as usize;
+        let (text, offsets) = self.row_text(index);
+        let cell_offset = offsets[cell];
+
+        let Some(found) = url_regex
+            .find_iter(&text)
+            .find(|m| m.start() <= cell_offset && cell_offset < m.end())
+        else {
+            return self.continued_link_at(index, cell_offset, &url_regex);
+        };
+        let mut url = found.as_str().to_string();
+
+        // Only a URL that fills the row up to the edge was cut by the wrapping
+        if found.end() == text.len() && index + 1 < self.rows.len() {
+            let (next, _) = self.row_text(index + 1);
+            url.extend(
+                next.chars()
+                    .take_while(|c| !c.is_whitespace() && !"<>\"')]".contains(*c)),
+            );
+        }
+
+        Some(url)
+    }
+
+    /// The URL whose wrapped tail starts the row, when clicking on that tail
+    fn continued_link_at(
+        &self,
+        index: usize,
+        cell_offset: usize,
+        url_regex: &Regex,
+    ) -> Option<String> {
+        let (text, _) = self.row_text(index);
+        let tail = text
+            .find(|c: char| c.is_whitespace() || "<>\"')]".contains(c))
+            .unwrap_or(text.len());
+        if index == 0 || cell_offset >= tail {
+            return None;
+        }
+
+        let (previous, offsets) = self.row_text(index - 1);
+        let found = url_regex.find_iter(&previous).last()?;
+        if found.end() != previous.len() {
+            return None;
+        }
+
+        let cell = offsets.iter().position(|o| *o == found.start())?;
+        self.link_at(self.area.x + cell as u16, self.area.y + index as u16 - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ratatui::{
+        text::Text,
+        widgets::{Paragraph, Widget, Wrap},
+    };
+
+    #[test]
+    fn test_link_at() {
+        let area = Rect::new(0, 0, 24, 3);
+        let mut buffer = Buffer::empty(area);
+        Paragraph::new(Text::from("see (https://example.com/a/long/path) ok"))
+            .wrap(Wrap { trim: true })
+            .render(area, &mut buffer);
+
+        // The link is wrapped: "see" / "(https://example.com/a/l" / "ong/path) ok"
+        let text = ScreenText::capture(&buffer, area);
+        assert_eq!(text.link_at(0, 0), None);
+        assert_eq!(text.link_at(0, 1), None);
+        assert_eq!(
+            text.link_at(3, 1).as_deref(),
+            Some("https://example.com/a/long/path")
+        );
+        assert_eq!(
+            text.link_at(2, 2).as_deref(),
+            Some("https://example.com/a/long/path")
+        );
+        assert_eq!(text.link_at(10, 2), None);
+        assert_eq!(text.link_at(30, 0), None);
+    }
+
+    #[test]
+    fn test_double_click() {
+        let mut clicks = ClickTracker::default();
+        assert!(!clicks.click(1, 1));
+        assert!(clicks.click(1, 1));
+        assert!(!clicks.click(1, 1));
+        assert!(!clicks.click(2, 1));
+    }
+}
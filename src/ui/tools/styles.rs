@@ -150,3 +150,84 @@ pub fn metadata(theme: Option<&Theme>) -> Style {
 
     Style::new().fg(Color::from_u32(metadata_color))
 }
+
+pub fn link_hint(theme: Option<&Theme>) -> Style {
+    let hint_color = if let Some(t) = theme {
+        t.base[0x0a]
+    } else {
+        0xffffff
+    };
+
+    Style::new()
+        .fg(Color::from_u32(hint_color))
+        .add_modifier(Modifier::BOLD)
+}
+
+pub fn search_match(theme: Option<&Theme>) -> Style {
+    let (fg, bg) = if let Some(t) = theme {
+        (t.base[0x00], t.base[0x03])
+    } else {
+        (0x000000, 0xffffff)
+    };
+
+    Style::new().fg(Color::from_u32(fg)).bg(Color::from_u32(bg))
+}
+
+pub fn search_current(theme: Option<&Theme>) -> Style {
+    let (fg, bg) = if let Some(t) = theme {
+        (t.base[0x00], t.base[0x0a])
+    } else {
+        (0x000000, 0xffffff)
+    };
+
+    Style::new()
+        .fg(Color::from_u32(fg))
+        .bg(Color::from_u32(bg))
+        .add_modifier(Modifier::BOLD)
+}
+
+pub fn table_border(theme: Option<&Theme>) -> Style {
+    let border_color = if let Some(t) = theme {
+        t.base[0x03]
+    } else {
+        0xffffff
+    };
+
+    Style::new().fg(Color::from_u32(border_color))
+}
+
+pub fn table_head(theme: Option<&Theme>) -> Style {
+    let head_color = if let Some(t) = theme {
+        t.base[0x06]
+    } else {
+        0xffffff
+    };
+
+    Style::new()
+        .fg(Color::from_u32(head_color))
+        .add_modifier(Modifier::BOLD)
+}
+
+pub fn footnote(theme: Option<&Theme>) -> Style {
+    let footnote_color = if let Some(t) = theme {
+        t.base[0x0b]
+    } else {
+        0xffffff
+    };
+
+    Style::new()
+        .fg(Color::from_u32(footnote_color))
+        .add_modifier(Modifier::BOLD)
+}
+
+pub fn math(theme: Option<&Theme>) -> Style {
+    let math_color = if let Some(t) = theme {
+        t.base[0x0e]
+    } else {
+        0xffffff
+    };
+
+    Style::new()
+        .fg(Color::from_u32(math_color))
+        .add_modifier(Modifier::ITALIC)
+}
@@ -0,0 +1,200 @@
+use pulldown_cmark::Alignment;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use unicode_width::UnicodeWidthStr;
+
+use crate::ui::tools::wrap::wrap_spans;
+
+/// Columns aren't squeezed narrower than this to fit the reader, unless their content is
+const MIN_COLUMN_WIDTH: usize = 6;
+
+/// A GFM table, collected cell by cell while the Markdown is written and laid out once it
+/// ends, when the width of every column is known
+pub struct MarkdownTable<'a> {
+    alignments: Vec<Alignment>,
+    rows: Vec<Vec<Vec<Span<'a>>>>,
+    head_rows: usize,
+    /// How many links were collected before the table, to find the ones inside it
+    pub first_link: usize,
+}
+
+impl<'a> MarkdownTable<'a> {
+    pub fn new(alignments: Vec<Alignment>, first_link: usize) -> Self {
+        Self {
+            alignments,
+            rows: vec![],
+            head_rows: 0,
+            first_link,
+        }
+    }
+
+    pub fn start_row(&mut self) {
+        self.rows.push(vec![]);
+    }
+
+    pub fn end_head(&mut self) {
+        self.head_rows = self.rows.len();
+    }
+
+    pub fn start_cell(&mut self) {
+        if let Some(row) = self.rows.last_mut() {
+            row.push(vec![]);
+        }
+    }
+
+    pub fn push_span(&mut self, span: Span<'a>) {
+        if let Some(cell) = self.rows.last_mut().and_then(|row| row.last_mut()) {
+            cell.push(span);
+        }
+    }
+
+    /// Whether the cell being written has content already
+    pub fn cell_is_empty(&self) -> bool {
+        self.rows
+            .last()
+            .and_then(|row| row.last())
+            .is_none_or(|cell| cell.iter().all(|s| s.content.is_empty()))
+    }
+
+    fn columns(&self) -> usize {
+        self.rows
+            .iter()
+            .map(Vec::len)
+            .max()
+            .unwrap_or(0)
+            .max(self.alignments.len())
+    }
+
+    /// Widths of the columns that fit in `max_width`, wrapping their content when the table
+    /// is too wide. Columns that don't fit even at their narrowest are left out.
+    fn column_widths(&self, max_width: Option<usize>) -> Vec<usize> {
+        let natural: Vec<usize> = (0..self.columns())
+            .map(|column| {
+                self.rows
+                    .iter()
+                    .filter_map(|row| row.get(column))
+                    .map(|cell| cell.iter().map(|s| s.content.width()).sum::<usize>())
+                    .max()
+                    .unwrap_or(0)
+                    .max(1)
+            })
+            .collect();
+
+        let Some(max_width) = max_width else {
+            return natural;
+        };
+        if natural.iter().sum::<usize>() + borders_width(natural.len()) <= max_width {
+            return natural;
+        }
+
+        let narrowest: Vec<usize> = natural.iter().map(|w| (*w).min(MIN_COLUMN_WIDTH)).collect();
+        let mut shown = natural.len();
+        while shown > 1
+            && narrowest[..shown].iter().sum::<usize>() + borders_width(shown) > max_width
+        {
+            shown -= 1;
+        }
+
+        let available = max_width.saturating_sub(borders_width(shown)).max(1);
+        let mut widths = narrowest[..shown].to_vec();
+        if shown == 1 {
+            widths[0] = natural[0].min(available);
+            return widths;
+        }
+
+        // What's left goes evenly to the columns that still want more
+        let mut extra = available.saturating_sub(widths.iter().sum());
+        while extra > 0 {
+            let growing: Vec<usize> = (0..shown).filter(|c| widths[*c] < natural[*c]).collect();
+            if growing.is_empty() {
+                break;
+            }
+
+            let each = (extra / growing.len()).max(1);
+            for column in growing {
+                let add = each.min(natural[column] - widths[column]).min(extra);
+                widths[column] += add;
+                extra -= add;
+            }
+        }
+
+        widths
+    }
+
+    /// Lays the table out with box-drawing borders, no wider than `max_width` when given
+    pub fn lines(self, max_width: Option<usize>, border: Style, head: Style) -> Vec<Line<'a>> {
+        let columns = self.columns();
+        let widths = self.column_widths(max_width);
+        let rule = |left: &str, middle: &str, right: &str| {
+            let segments: Vec<String> = widths.iter().map(|w| "─".repeat(w + 2)).collect();
+            Line::from(Span::styled(
+                format!("{left}{}{right}", segments.join(middle)),
+                border,
+            ))
+        };
+
+        let mut lines = vec![rule("┌", "┬", "┐")];
+
+        for (index, row) in self.rows.iter().enumerate() {
+            let is_head = index < self.head_rows;
+            let cells: Vec<Vec<Vec<Span<'a>>>> = widths
+                .iter()
+                .enumerate()
+                .map(|(column, width)| {
+                    let cell = row.get(column).map(Vec::as_slice).unwrap_or_default();
+                    let cell: Vec<Span<'a>> = if is_head {
+                        cell.iter()
+                            .map(|s| Span::styled(s.content.clone(), head.patch(s.style)))
+                            .collect()
+                    } else {
+                        cell.to_vec()
+                    };
+                    wrap_spans(&cell, *width)
+                })
+                .collect();
+            let height = cells.iter().map(Vec::len).max().unwrap_or(1);
+
+            for row_line in 0..height {
+                let mut spans = vec![Span::styled("│", border)];
+                for (column, width) in widths.iter().enumerate() {
+                    let content = cells[column].get(row_line).cloned().unwrap_or_default();
+                    let content_width: usize = content.iter().map(|s| s.content.width()).sum();
+                    let padding = width.saturating_sub(content_width);
+                    let (before, after) = match self.alignments.get(column) {
+                        Some(Alignment::Right) => (padding, 0),
+                        Some(Alignment::Center) => (padding / 2, padding - padding / 2),
+                        _ => (0, padding),
+                    };
+
+                    spans.push(Span::from(" ".repeat(before + 1)));
+                    spans.extend(content);
+                    spans.push(Span::from(" ".repeat(after + 1)));
+                    spans.push(Span::styled("│", border));
+                }
+                lines.push(Line::from(spans));
+            }
+
+            if index + 1 == self.head_rows && self.rows.len() > self.head_rows {
+                lines.push(rule("├", "┼", "┤"));
+            }
+        }
+
+        lines.push(rule("└", "┴", "┘"));
+
+        let hidden = columns - widths.len();
+        if hidden > 0 {
+            let s = if hidden == 1 { "" } else { "s" };
+            lines.push(Line::from(Span::styled(
+                format!("… {hidden} more column{s} not shown"),
+                border,
+            )));
+        }
+
+        lines
+    }
+}
+
+/// Width the borders and padding of a table with `columns` columns take
+fn borders_width(columns: usize) -> usize {
+    columns * 3 + 1
+}
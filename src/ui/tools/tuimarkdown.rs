@@ -2,28 +2,59 @@
 //! Originally written by joshka
 //! https://github.com/joshka/tui-markdown
 
-use std::sync::LazyLock;
+use std::ops::Range;
 use std::vec;
 
 use ansi_to_tui::IntoText;
 use itertools::{Itertools, Position};
 use pulldown_cmark::{
-    BlockQuoteKind, CodeBlockKind, CowStr, Event, HeadingLevel, Options, Parser, Tag, TagEnd,
+    Alignment, BlockQuoteKind, CodeBlockKind, CowStr, Event, HeadingLevel, Options, Parser, Tag,
+    TagEnd,
 };
 use ratatui::style::Style;
 use ratatui::text::{Line, Span, Text};
 use syntect::{
     easy::HighlightLines,
-    highlighting::ThemeSet,
-    parsing::SyntaxSet,
+    parsing::SyntaxReference,
     util::{LinesWithEndings, as_24_bit_terminal_escaped},
 };
 use tracing::{debug, instrument, warn};
+use unicode_width::UnicodeWidthStr;
 
 use crate::core::library::settings::theme::Theme;
+use crate::ui::tools::highlight::{self, SYNTAX_SET};
+use crate::ui::tools::html::{self, HtmlToken};
+use crate::ui::tools::latex;
 use crate::ui::tools::styles;
+use crate::ui::tools::tables::MarkdownTable;
+use crate::ui::tools::wrap::{cut_spans, wrap_spans};
+
+/// A link or image of the article. Its hint number is its position in the list plus one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MarkdownLink {
+    pub url: String,
+    pub text: String,
+    pub is_image: bool,
+    /// The line of the rendered text it ends on
+    pub line: usize,
+}
+
+/// The rendered article, with the links it points to in reading order
+pub struct Markdown<'a> {
+    pub text: Text<'a>,
+    pub links: Vec<MarkdownLink>,
+}
 
 pub fn from_str(input: &str, theme: Option<Theme>) -> Text<'_> {
+    parse(input, theme).text
+}
+
+pub fn parse(input: &str, theme: Option<Theme>) -> Markdown<'_> {
+    parse_with_width(input, theme, None)
+}
+
+/// Renders for a view `width` columns wide, so tables can be fitted into it
+pub fn parse_with_width(input: &str, theme: Option<Theme>, width: Option<u16>) -> Markdown<'_> {
     let mut options = Options::empty();
     options.insert(Options::ENABLE_STRIKETHROUGH);
     options.insert(Options::ENABLE_TASKLISTS);
@@ -31,10 +62,38 @@ pub fn from_str(input: &str, theme: Option<Theme>) -> Text<'_> {
     options.insert(Options::ENABLE_YAML_STYLE_METADATA_BLOCKS);
     options.insert(Options::ENABLE_SUPERSCRIPT);
     options.insert(Options::ENABLE_SUBSCRIPT);
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_FOOTNOTES);
+    options.insert(Options::ENABLE_MATH);
     let parser = Parser::new_ext(input, options);
     let mut writer = TextWriter::new(parser, theme);
+    writer.width = width.map(usize::from);
     writer.run();
-    writer.text
+    Markdown {
+        text: writer.text,
+        links: writer.links,
+    }
+}
+
+// A footnote definition, written apart and appended at the end of the article.
+struct Footnote<'a> {
+    label: String,
+    text: Text<'a>,
+    /// The links collected inside it
+    links: Range<usize>,
+}
+
+// How a line is wrapped once it's complete.
+#[derive(Clone, Copy, Default)]
+struct LineWrap {
+    /// Number of prefix spans (blockquote bars) every wrapped line repeats
+    prefix: usize,
+    /// Hanging indent of the list item the line belongs to
+    indent: usize,
+    /// The first line of a list item, whose marker the other lines hang from
+    marker: bool,
+    /// Code is cut anywhere, with a continuation marker, instead of wrapped at words
+    code: bool,
 }
 
 // Heading attributes collected from pulldown-cmark to render after the heading text.
@@ -104,6 +163,9 @@ struct TextWriter<'a, I> {
     /// Used to highlight code blocks, set when  a codeblock is encountered
     code_highlighter: Option<HighlightLines<'a>>,
 
+    /// The code block has no info string, so its syntax is guessed from its text
+    detect_code_syntax: bool,
+
     /// Current list index as a stack of indices.
     list_indices: Vec<Option<u64>>,
 
@@ -113,6 +175,12 @@ struct TextWriter<'a, I> {
     /// The current image to be closed
     image: Option<CowStr<'a>>,
 
+    /// Text of the link or image being written, to show in the link list
+    link_text: String,
+
+    /// Every link and image written so far, numbered by their position
+    links: Vec<MarkdownLink>,
+
     /// Heading attributes to append after heading content.
     heading_meta: Option<HeadingMeta<'a>>,
 
@@ -122,13 +190,45 @@ struct TextWriter<'a, I> {
     /// True when last element requires a new line
     needs_newline: bool,
 
+    /// The table being written, laid out when it ends
+    table: Option<MarkdownTable<'a>>,
+
+    /// Width of the view the text is rendered for, when known
+    width: Option<usize>,
+
+    /// HTML tags that pushed an inline style, to pop it when they close
+    html_styles: Vec<String>,
+
+    /// Lines and `needs_newline` before the HTML block being written, to drop it if it shows
+    /// nothing
+    html_block: Option<(usize, bool)>,
+
+    /// Footnote labels, numbered by their position plus one
+    footnote_labels: Vec<String>,
+
+    /// Footnote definitions written so far
+    footnotes: Vec<Footnote<'a>>,
+
+    /// The article text, `needs_newline` and first link, kept while a footnote definition is
+    /// written
+    footnote_stash: Option<(Text<'a>, bool, usize)>,
+
+    /// Set after display math, which ends its line, until something else is written
+    after_display_math: bool,
+
+    /// How the last line wraps, until it's complete
+    open_line: Option<LineWrap>,
+
+    /// Hanging indents of the list items being written
+    item_indents: Vec<usize>,
+
+    /// Whether the lines being written are inside a code block
+    in_code_block: bool,
+
     /// bulletty Theme
     theme: Option<Theme>,
 }
 
-static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
-static THEME_SET: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
-
 impl<'a, I> TextWriter<'a, I>
 where
     I: Iterator<Item = Event<'a>>,
@@ -143,10 +243,24 @@ where
             list_indices: vec![],
             needs_newline: false,
             code_highlighter: None,
+            detect_code_syntax: false,
             link: None,
             image: None,
+            link_text: String::new(),
+            links: vec![],
             heading_meta: None,
             in_metadata_block: false,
+            table: None,
+            width: None,
+            html_styles: vec![],
+            html_block: None,
+            footnote_labels: vec![],
+            footnotes: vec![],
+            footnote_stash: None,
+            after_display_math: false,
+            open_line: None,
+            item_indents: vec![],
+            in_code_block: false,
             theme,
         }
     }
@@ -156,6 +270,8 @@ where
         while let Some(event) = self.iter.next() {
             self.handle_event(event);
         }
+        self.finish_line();
+        self.write_footnotes();
     }
 
     #[instrument(level = "debug", skip(self))]
@@ -165,15 +281,15 @@ where
             Event::End(tag) => self.end_tag(tag),
             Event::Text(text) => self.text(text),
             Event::Code(code) => self.code(code),
-            Event::Html(_html) => warn!("Html not yet supported"),
-            Event::InlineHtml(_html) => warn!("Inline html not yet supported"),
-            Event::FootnoteReference(_) => warn!("Footnote reference not yet supported"),
+            Event::Html(html) => self.html(html),
+            Event::InlineHtml(html) => self.html(html),
+            Event::FootnoteReference(label) => self.footnote_reference(label),
             Event::SoftBreak => self.soft_break(),
             Event::HardBreak => self.hard_break(),
             Event::Rule => self.rule(),
             Event::TaskListMarker(checked) => self.task_list_marker(checked),
-            Event::InlineMath(_) => warn!("Inline math not yet supported"),
-            Event::DisplayMath(_) => warn!("Display math not yet supported"),
+            Event::InlineMath(math) => self.inline_math(math),
+            Event::DisplayMath(math) => self.display_math(math),
         }
     }
 
@@ -188,14 +304,13 @@ where
             } => self.start_heading(level, HeadingMeta { id, classes, attrs }),
             Tag::BlockQuote(kind) => self.start_blockquote(kind),
             Tag::CodeBlock(kind) => self.start_codeblock(kind),
-            Tag::HtmlBlock => warn!("Html block not yet supported"),
+            Tag::HtmlBlock => self.start_html_block(),
             Tag::List(start_index) => self.start_list(start_index),
             Tag::Item => self.start_item(),
-            Tag::FootnoteDefinition(_) => warn!("Footnote definition not yet supported"),
-            Tag::Table(_) => warn!("Table not yet supported"),
-            Tag::TableHead => warn!("Table head not yet supported"),
-            Tag::TableRow => warn!("Table row not yet supported"),
-            Tag::TableCell => warn!("Table cell not yet supported"),
+            Tag::FootnoteDefinition(label) => self.start_footnote_definition(label),
+            Tag::Table(alignments) => self.start_table(alignments),
+            Tag::TableHead | Tag::TableRow => self.start_table_row(),
+            Tag::TableCell => self.start_table_cell(),
             Tag::Emphasis => self.push_inline_style(Style::new().italic()),
             Tag::Strong => self.push_inline_style(Style::new().bold()),
             Tag::Strikethrough => self.push_inline_style(Style::new().crossed_out()),
@@ -221,12 +336,14 @@ where
             TagEnd::Heading(_) => self.end_heading(),
             TagEnd::BlockQuote(_) => self.end_blockquote(),
             TagEnd::CodeBlock => self.end_codeblock(),
-            TagEnd::HtmlBlock => {}
+            TagEnd::HtmlBlock => self.end_html_block(),
             TagEnd::List(_is_ordered) => self.end_list(),
-            TagEnd::Item => {}
-            TagEnd::FootnoteDefinition => {}
-            TagEnd::Table => {}
-            TagEnd::TableHead => {}
+            TagEnd::Item => {
+                self.item_indents.pop();
+            }
+            TagEnd::FootnoteDefinition => self.end_footnote_definition(),
+            TagEnd::Table => self.end_table(),
+            TagEnd::TableHead => self.end_table_head(),
             TagEnd::TableRow => {}
             TagEnd::TableCell => {}
             TagEnd::Emphasis => self.pop_inline_style(),
@@ -254,6 +371,12 @@ where
     }
 
     fn end_paragraph(&mut self) {
+        // Display math ended the paragraph's last line already
+        if self.after_display_math && self.text.lines.last().is_some_and(|line| line.width() == 0) {
+            self.text.lines.pop();
+            self.open_line = None;
+        }
+        self.after_display_math = false;
         self.needs_newline = true
     }
 
@@ -302,7 +425,326 @@ where
         self.needs_newline = true;
     }
 
+    fn start_table(&mut self, alignments: Vec<Alignment>) {
+        if self.needs_newline {
+            self.push_line(Line::default());
+            self.needs_newline = false;
+        }
+        self.table = Some(MarkdownTable::new(alignments, self.links.len()));
+    }
+
+    fn start_table_row(&mut self) {
+        if let Some(table) = self.table.as_mut() {
+            table.start_row();
+        }
+    }
+
+    fn end_table_head(&mut self) {
+        if let Some(table) = self.table.as_mut() {
+            table.end_head();
+        }
+    }
+
+    fn start_table_cell(&mut self) {
+        if let Some(table) = self.table.as_mut() {
+            table.start_cell();
+        }
+    }
+
+    fn end_table(&mut self) {
+        let Some(table) = self.table.take() else {
+            return;
+        };
+
+        let first_link = table.first_link;
+        let prefix_width = match self.line_prefixes.len() {
+            0 => 0,
+            _ => {
+                self.line_prefixes
+                    .iter()
+                    .map(|p| p.content.width())
+                    .sum::<usize>()
+                    + 1
+            }
+        };
+        let max_width = self.width.map(|w| w.saturating_sub(prefix_width));
+
+        for line in table.lines(
+            max_width,
+            styles::table_border(self.theme.as_ref()),
+            styles::table_head(self.theme.as_ref()),
+        ) {
+            self.push_line(line.style(styles::p(self.theme.as_ref())));
+        }
+
+        // Links in the table were collected before it had lines
+        let last_line = self.text.lines.len().saturating_sub(1);
+        for link in self.links.iter_mut().skip(first_link) {
+            link.line = last_line;
+        }
+
+        self.needs_newline = true;
+    }
+
+    fn footnote_number(&mut self, label: &str) -> usize {
+        match self.footnote_labels.iter().position(|l| l == label) {
+            Some(index) => index + 1,
+            None => {
+                self.footnote_labels.push(label.to_string());
+                self.footnote_labels.len()
+            }
+        }
+    }
+
+    fn footnote_marker(number: usize) -> String {
+        latex::superscript(&number.to_string()).unwrap_or_else(|| format!("[{number}]"))
+    }
+
+    fn footnote_reference(&mut self, label: CowStr<'a>) {
+        let number = self.footnote_number(&label);
+        self.push_span(Span::styled(
+            Self::footnote_marker(number),
+            styles::footnote(self.theme.as_ref()),
+        ));
+    }
+
+    fn start_footnote_definition(&mut self, label: CowStr<'a>) {
+        self.footnote_number(&label);
+        self.finish_line();
+        let text = std::mem::take(&mut self.text);
+        self.footnote_stash = Some((text, self.needs_newline, self.links.len()));
+        self.footnotes.push(Footnote {
+            label: label.to_string(),
+            text: Text::default(),
+            links: 0..0,
+        });
+        self.needs_newline = false;
+    }
+
+    fn end_footnote_definition(&mut self) {
+        let Some((text, needs_newline, first_link)) = self.footnote_stash.take() else {
+            return;
+        };
+
+        self.finish_line();
+        let definition = std::mem::replace(&mut self.text, text);
+        self.needs_newline = needs_newline;
+        if let Some(footnote) = self.footnotes.last_mut() {
+            footnote.text = definition;
+            footnote.links = first_link..self.links.len();
+        }
+    }
+
+    /// Appends the footnote definitions, in the order of their numbers
+    fn write_footnotes(&mut self) {
+        if self.footnotes.is_empty() {
+            return;
+        }
+
+        let mut footnotes = std::mem::take(&mut self.footnotes);
+        footnotes.sort_by_key(|f| self.footnote_labels.iter().position(|l| *l == f.label));
+
+        self.rule();
+        self.push_line(Line::default());
+        self.finish_line();
+
+        for footnote in footnotes {
+            let number = self.footnote_number(&footnote.label);
+            let offset = self.text.lines.len();
+            let mut lines = footnote.text.lines;
+            if lines.is_empty() {
+                lines.push(Line::default().style(styles::p(self.theme.as_ref())));
+            }
+            lines[0].spans.insert(
+                0,
+                Span::styled(
+                    format!("{} ", Self::footnote_marker(number)),
+                    styles::footnote(self.theme.as_ref()),
+                ),
+            );
+            self.text.lines.extend(lines);
+
+            for link in &mut self.links[footnote.links] {
+                link.line += offset;
+            }
+        }
+    }
+
+    fn inline_math(&mut self, math: CowStr<'a>) {
+        let span = match latex::to_unicode(&math) {
+            Some(text) => Span::styled(text, styles::math(self.theme.as_ref())),
+            None => Span::styled(math, styles::code(self.theme.as_ref())),
+        };
+        self.push_span(span);
+    }
+
+    /// Display math goes on lines of its own, centered when it reads as Unicode
+    fn display_math(&mut self, math: CowStr<'a>) {
+        if self.text.lines.last().is_some_and(|line| line.width() > 0) {
+            self.push_line(Line::default().style(styles::p(self.theme.as_ref())));
+        }
+
+        match latex::to_unicode(&math) {
+            Some(text) => {
+                self.push_span(Span::styled(text, styles::math(self.theme.as_ref())));
+                if let Some(line) = self.text.lines.last_mut() {
+                    line.alignment = Some(ratatui::layout::Alignment::Center);
+                }
+            }
+            None => {
+                for (index, source) in math.trim().lines().enumerate() {
+                    if index > 0 {
+                        self.push_line(Line::default().style(styles::p(self.theme.as_ref())));
+                    }
+                    self.push_span(Span::styled(
+                        source.to_string(),
+                        styles::code(self.theme.as_ref()),
+                    ));
+                }
+            }
+        }
+
+        self.push_line(Line::default().style(styles::p(self.theme.as_ref())));
+        self.after_display_math = true;
+    }
+
+    fn start_html_block(&mut self) {
+        self.html_block = Some((self.text.lines.len(), self.needs_newline));
+        self.start_paragraph();
+    }
+
+    fn end_html_block(&mut self) {
+        let Some((start, needs_newline)) = self.html_block.take() else {
+            return;
+        };
+
+        // Lines left open by closing tags are dropped, and blocks of comments or unknown tags
+        // leave no trace
+        while self.text.lines.len() > start
+            && self.text.lines.last().is_some_and(|line| line.width() == 0)
+        {
+            self.text.lines.pop();
+            self.open_line = None;
+        }
+        self.needs_newline = self.text.lines.len() > start || needs_newline;
+    }
+
+    fn html(&mut self, html: CowStr<'a>) {
+        for token in html::tokens(&html) {
+            match token {
+                HtmlToken::Open(tag) => self.open_html_tag(tag),
+                HtmlToken::Close(tag) => self.close_html_tag(&tag),
+                HtmlToken::Text(text) => self.html_text(text),
+            }
+        }
+    }
+
+    /// Text of an HTML block flows like a paragraph, so its line breaks are spaces
+    fn html_text(&mut self, text: String) {
+        if self.html_block.is_none() {
+            self.text(text.into());
+            return;
+        }
+
+        let line_has_text = self.text.lines.last().is_some_and(|line| line.width() > 0);
+        let mut flowed = text.split_whitespace().join(" ");
+        if flowed.is_empty() {
+            if line_has_text && text.starts_with(char::is_whitespace) {
+                self.text(" ".into());
+            }
+            return;
+        }
+
+        if line_has_text && text.starts_with(char::is_whitespace) {
+            flowed.insert(0, ' ');
+        }
+        if text.ends_with(char::is_whitespace) {
+            flowed.push(' ');
+        }
+        self.text(flowed.into());
+    }
+
+    /// Starts a line for a block level tag, unless the current one is still empty
+    fn html_line(&mut self) {
+        if self.text.lines.last().is_some_and(|line| line.width() > 0) {
+            self.push_line(Line::default().style(styles::p(self.theme.as_ref())));
+        }
+    }
+
+    fn push_html_style(&mut self, tag: String, style: Style) {
+        self.push_inline_style(style);
+        self.html_styles.push(tag);
+    }
+
+    fn open_html_tag(&mut self, tag: String) {
+        match tag.as_str() {
+            "br" => self.hard_break(),
+            "hr" => self.rule(),
+            "kbd" | "code" | "tt" => {
+                let style = styles::code(self.theme.as_ref());
+                self.push_html_style(tag, style);
+            }
+            "sub" | "sup" => self.push_html_style(tag, Style::new().dim().italic()),
+            "b" | "strong" => self.push_html_style(tag, Style::new().bold()),
+            "i" | "em" => self.push_html_style(tag, Style::new().italic()),
+            "s" | "del" | "strike" => self.push_html_style(tag, Style::new().crossed_out()),
+            "summary" => {
+                self.html_line();
+                self.push_span(Span::from("▸ "));
+                self.push_html_style(tag, Style::new().bold());
+            }
+            "li" => {
+                self.html_line();
+                self.push_span(Span::styled("• ", styles::list_item(self.theme.as_ref())));
+            }
+            "p" | "div" | "details" | "blockquote" | "pre" | "ul" | "ol" | "table" | "tr"
+            | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => self.html_line(),
+            _ => debug!("Skipping html tag <{tag}>"),
+        }
+    }
+
+    fn close_html_tag(&mut self, tag: &str) {
+        if let Some(position) = self.html_styles.iter().rposition(|t| t == tag) {
+            while self.html_styles.len() > position {
+                self.html_styles.pop();
+                self.pop_inline_style();
+            }
+        }
+
+        if matches!(
+            tag,
+            "summary"
+                | "p"
+                | "div"
+                | "details"
+                | "blockquote"
+                | "pre"
+                | "li"
+                | "tr"
+                | "h1"
+                | "h2"
+                | "h3"
+                | "h4"
+                | "h5"
+                | "h6"
+        ) {
+            self.html_line();
+        }
+    }
+
     fn text(&mut self, text: CowStr<'a>) {
+        if self.link.is_some() || self.image.is_some() {
+            self.link_text.push_str(&text);
+        }
+
+        if self.detect_code_syntax {
+            self.detect_code_syntax = false;
+            if let Some(syntax) = highlight::detect_syntax(&text) {
+                debug!("Detected code block syntax: {:?}", syntax.name);
+                self.set_code_syntax(syntax);
+            }
+        }
+
         if let Some(highlighter) = &mut self.code_highlighter {
             let text: Text = LinesWithEndings::from(&text)
                 .filter_map(|line| highlighter.highlight_line(line, &SYNTAX_SET).ok())
@@ -311,7 +753,12 @@ where
                 .collect();
 
             for line in text.lines {
+                self.finish_line();
                 self.text.push_line(line);
+                self.open_line = Some(LineWrap {
+                    code: true,
+                    ..Default::default()
+                });
             }
             self.needs_newline = false;
             return;
@@ -397,7 +844,11 @@ where
                     Span::from(format!("\u{00A0}\u{00A0}{:width$}. ", *index - 1))
                 }
             };
+            self.item_indents.push(span.width());
             self.push_span(span.style(styles::list_item(self.theme.as_ref())));
+            if let Some(wrap) = self.open_line.as_mut() {
+                wrap.marker = true;
+            }
         }
         self.needs_newline = false;
     }
@@ -405,6 +856,9 @@ where
     fn task_list_marker(&mut self, checked: bool) {
         let marker = if checked { 'x' } else { ' ' };
         let marker_span = Span::from(format!("[{}] ", marker));
+        if let Some(indent) = self.item_indents.last_mut() {
+            *indent += marker_span.width();
+        }
         if let Some(line) = self.text.lines.last_mut() {
             if let Some(first_span) = line.spans.first_mut() {
                 let content = first_span.content.to_mut();
@@ -452,14 +906,21 @@ where
 
         self.line_styles.push(styles::code(self.theme.as_ref()));
 
-        self.set_code_highlighter(lang);
+        if lang.is_empty() {
+            self.detect_code_syntax = true;
+        } else {
+            self.set_code_highlighter(lang);
+        }
 
         let span = Span::from(format!("```{lang}"));
         self.push_line(span.into());
         self.needs_newline = true;
+        self.in_code_block = true;
     }
 
     fn end_codeblock(&mut self) {
+        self.in_code_block = false;
+        self.detect_code_syntax = false;
         let span = Span::from("```");
         self.push_line(span.into());
         self.needs_newline = true;
@@ -473,14 +934,17 @@ where
     fn set_code_highlighter(&mut self, lang: &str) {
         if let Some(syntax) = SYNTAX_SET.find_syntax_by_token(lang) {
             debug!("Starting code block with syntax: {:?}", lang);
-            let theme = &THEME_SET.themes["base16-ocean.dark"];
-            let highlighter = HighlightLines::new(syntax, theme);
-            self.code_highlighter = Some(highlighter);
+            self.set_code_syntax(syntax);
         } else {
             warn!("Could not find syntax for code block: {:?}", lang);
         }
     }
 
+    fn set_code_syntax(&mut self, syntax: &'static SyntaxReference) {
+        let theme = highlight::syntax_theme(self.theme.as_ref());
+        self.code_highlighter = Some(HighlightLines::new(syntax, theme));
+    }
+
     #[instrument(level = "trace", skip(self))]
     fn clear_code_highlighter(&mut self) {
         self.code_highlighter = None;
@@ -502,6 +966,17 @@ where
 
     #[instrument(level = "trace", skip(self))]
     fn push_line(&mut self, line: Line<'a>) {
+        // Table cells are a single line until the table is laid out
+        if let Some(table) = self.table.as_mut() {
+            if !table.cell_is_empty() {
+                table.push_span(" ".into());
+            }
+            for span in line.spans {
+                table.push_span(span);
+            }
+            return;
+        }
+
         let style = self.line_styles.last().copied().unwrap_or_default();
         let mut line = line.patch_style(style);
 
@@ -514,11 +989,103 @@ where
         for prefix in line_prefixes.iter().rev().cloned() {
             line.spans.insert(0, prefix);
         }
+
+        self.finish_line();
         self.text.lines.push(line);
+        self.open_line = Some(LineWrap {
+            prefix: if has_prefixes {
+                line_prefixes.len() + 1
+            } else {
+                0
+            },
+            indent: self.item_indents.last().copied().unwrap_or(0),
+            marker: false,
+            code: self.in_code_block,
+        });
+    }
+
+    /// Wraps the last line to the width, now that it's complete. Wrapped lines repeat the
+    /// blockquote bars and hang under their list item; code is cut and marked as continued.
+    fn finish_line(&mut self) {
+        let (Some(wrap), Some(width)) = (self.open_line.take(), self.width) else {
+            return;
+        };
+        let Some(index) = self.text.lines.len().checked_sub(1) else {
+            return;
+        };
+
+        let line_width = self.text.lines[index].width();
+        let hangs = wrap.indent > 0 && !wrap.marker && !wrap.code && line_width > 0;
+        if line_width <= width && !hangs {
+            return;
+        }
+
+        let line = std::mem::take(&mut self.text.lines[index]);
+        let (prefix, content) = line.spans.split_at(wrap.prefix.min(line.spans.len()));
+        let available = width.saturating_sub(prefix.iter().map(Span::width).sum());
+
+        let lines: Vec<Vec<Span<'a>>> = if wrap.code {
+            let continued = Span::styled("↪ ", styles::code(self.theme.as_ref()).dim());
+            cut_spans(content, available.saturating_sub(continued.width()))
+                .into_iter()
+                .enumerate()
+                .map(|(i, mut spans)| {
+                    if i > 0 {
+                        spans.insert(0, continued.clone());
+                    }
+                    spans
+                })
+                .collect()
+        } else if wrap.marker && !content.is_empty() {
+            let hang = content[0].width();
+            wrap_spans(&content[1..], available.saturating_sub(hang))
+                .into_iter()
+                .enumerate()
+                .map(|(i, mut spans)| {
+                    let lead = if i == 0 {
+                        content[0].clone()
+                    } else {
+                        Span::from("\u{00A0}".repeat(hang))
+                    };
+                    spans.insert(0, lead);
+                    spans
+                })
+                .collect()
+        } else {
+            let hang = if hangs { wrap.indent } else { 0 };
+            wrap_spans(content, available.saturating_sub(hang))
+                .into_iter()
+                .map(|mut spans| {
+                    if hang > 0 {
+                        spans.insert(0, Span::from("\u{00A0}".repeat(hang)));
+                    }
+                    spans
+                })
+                .collect()
+        };
+
+        let count = lines.len();
+        let wrapped = lines.into_iter().map(|spans| {
+            let mut wrapped = Line::from([prefix.to_vec(), spans].concat()).style(line.style);
+            wrapped.alignment = line.alignment;
+            wrapped
+        });
+        self.text.lines.splice(index..=index, wrapped);
+
+        for link in self.links.iter_mut().filter(|l| l.line == index) {
+            link.line = index + count - 1;
+        }
     }
 
     #[instrument(level = "trace", skip(self))]
     fn push_span(&mut self, span: Span<'a>) {
+        self.after_display_math = false;
+
+        if let Some(table) = self.table.as_mut() {
+            table.push_span(span);
+            return;
+        }
+
         if let Some(line) = self.text.lines.last_mut() {
             line.push_span(span);
         } else {
@@ -532,10 +1099,27 @@ where
         self.link = Some(dest_url);
     }
 
-    /// Append the link to the current line
+    /// Keep the link for the link list, returning its hint number
+    fn collect_link(&mut self, url: &str, is_image: bool) -> usize {
+        let text = std::mem::take(&mut self.link_text).trim().to_string();
+        self.links.push(MarkdownLink {
+            url: url.to_string(),
+            text,
+            is_image,
+            line: self.text.lines.len().saturating_sub(1),
+        });
+        self.links.len()
+    }
+
+    /// Append the hint number and the link to the current line
     #[instrument(level = "trace", skip(self))]
     fn pop_link(&mut self) {
         if let Some(link) = self.link.take() {
+            let number = self.collect_link(&link, false);
+            self.push_span(Span::styled(
+                format!(" [{number}]"),
+                styles::link_hint(self.theme.as_ref()),
+            ));
             self.push_span(" (".into());
             self.push_span(Span::styled(link, styles::link(self.theme.as_ref())));
             self.push_span(")".into());
@@ -558,6 +1142,11 @@ where
     #[instrument(level = "trace", skip(self))]
     fn pop_image(&mut self) {
         if let Some(image_link) = self.image.take() {
+            let number = self.collect_link(&image_link, true);
+            self.push_span(Span::styled(
+                format!(" [{number}]"),
+                styles::link_hint(self.theme.as_ref()),
+            ));
             self.push_span("  -> ".into());
             self.push_span(Span::styled(image_link, styles::link(self.theme.as_ref())));
             self.push_span("]".into());
@@ -986,6 +1575,7 @@ mod tests {
             Text::from(
                 Line::from_iter([
                     Span::from("Link"),
+                    Span::from(" [1]").style(styles::link_hint(None)),
                     Span::from(" ("),
                     Span::from("https://example.com")
                         .style(styles::p(None))
@@ -1006,6 +1596,7 @@ mod tests {
                 Line::from_iter([
                     Span::from("[Image: "),
                     Span::from("TestImage"),
+                    Span::from(" [1]").style(styles::link_hint(None)),
                     Span::from("  -> "),
                     Span::from("/test.html").style(styles::p(None)).underlined(),
                     Span::from("]"),
@@ -1014,4 +1605,389 @@ mod tests {
             ])
         );
     }
+
+    #[rstest]
+    fn links_collected(_with_tracing: DefaultGuard) {
+        let markdown = parse(
+            indoc! {"
+                See [the **docs**](https://example.com/docs) and <https://example.com>.
+
+                ![A chart](/chart.png)
+            "},
+            None,
+        );
+
+        assert_eq!(
+            markdown.links,
+            vec![
+                MarkdownLink {
+                    url: "https://example.com/docs".to_string(),
+                    text: "the docs".to_string(),
+                    is_image: false,
+                    line: 0,
+                },
+                MarkdownLink {
+                    url: "https://example.com".to_string(),
+                    text: "https://example.com".to_string(),
+                    is_image: false,
+                    line: 0,
+                },
+                MarkdownLink {
+                    url: "/chart.png".to_string(),
+                    text: "A chart".to_string(),
+                    is_image: true,
+                    line: 3,
+                },
+            ]
+        );
+    }
+
+    fn plain(text: &Text) -> Vec<String> {
+        text.lines
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect()
+    }
+
+    #[rstest]
+    fn table(_with_tracing: DefaultGuard) {
+        let text = from_str(
+            indoc! {"
+                Before
+
+                | Name | Runs |
+                |------|-----:|
+                | a    | 10   |
+                | bb   | 2    |
+            "},
+            None,
+        );
+
+        assert_eq!(
+            plain(&text),
+            vec![
+                "Before",
+                "",
+                "┌──────┬──────┐",
+                "│ Name │ Runs │",
+                "├──────┼──────┤",
+                "│ a    │   10 │",
+                "│ bb   │    2 │",
+                "└──────┴──────┘",
+            ]
+        );
+        assert_eq!(text.lines[2].style, styles::p(None));
+        assert_eq!(text.lines[3].spans[0].style, styles::table_border(None));
+        assert_eq!(
+            text.lines[3].spans[2],
+            Span::styled("Name", styles::table_head(None))
+        );
+    }
+
+    #[rstest]
+    fn table_alignment(_with_tracing: DefaultGuard) {
+        let text = from_str(
+            indoc! {"
+                | Left | Center | Right |
+                |:-----|:------:|------:|
+                | a    | b      | c     |
+            "},
+            None,
+        );
+
+        assert_eq!(plain(&text)[3], "│ a    │   b    │     c │");
+    }
+
+    #[rstest]
+    fn table_wraps_to_width(_with_tracing: DefaultGuard) {
+        let markdown = parse_with_width(
+            indoc! {"
+                | Version | Changes |
+                |---------|---------|
+                | 1.0 | Adds tables to the reader, and `more` |
+            "},
+            None,
+            Some(30),
+        );
+
+        assert_eq!(
+            plain(&markdown.text),
+            vec![
+                "┌─────────┬──────────────────┐",
+                "│ Version │ Changes          │",
+                "├─────────┼──────────────────┤",
+                "│ 1.0     │ Adds tables to   │",
+                "│         │ the reader, and  │",
+                "│         │ more             │",
+                "└─────────┴──────────────────┘",
+            ]
+        );
+        assert_eq!(
+            markdown.text.lines[5].spans[5],
+            Span::styled("more", styles::code(None))
+        );
+    }
+
+    #[rstest]
+    fn table_too_wide(_with_tracing: DefaultGuard) {
+        let markdown = parse_with_width(
+            indoc! {"
+                | Benchmark | Before | After | Change |
+                |-----------|--------|-------|--------|
+                | parse     | 120ms  | 80ms  | -33%   |
+            "},
+            None,
+            Some(20),
+        );
+
+        assert_eq!(
+            plain(&markdown.text),
+            vec![
+                "┌─────────┬────────┐",
+                "│ Benchma │ Before │",
+                "│ rk      │        │",
+                "├─────────┼────────┤",
+                "│ parse   │ 120ms  │",
+                "└─────────┴────────┘",
+                "… 2 more columns not",
+                "shown",
+            ]
+        );
+    }
+
+    #[rstest]
+    fn table_links(_with_tracing: DefaultGuard) {
+        let markdown = parse(
+            indoc! {"
+                | Project | Site |
+                |---------|------|
+                | bulletty | [home](https://example.com) |
+            "},
+            None,
+        );
+
+        assert_eq!(markdown.links.len(), 1);
+        assert_eq!(markdown.links[0].line, markdown.text.lines.len() - 1);
+        assert!(plain(&markdown.text)[3].contains("home [1] (https://example.com)"));
+    }
+
+    #[rstest]
+    fn footnotes(_with_tracing: DefaultGuard) {
+        let markdown = parse(
+            indoc! {"
+                Claim[^a] and [another](https://example.com)[^b].
+
+                [^b]: Second, see [source](https://example.org).
+                [^a]: First.
+            "},
+            None,
+        );
+
+        assert_eq!(
+            plain(&markdown.text),
+            vec![
+                "Claim¹ and another [1] (https://example.com)².",
+                "",
+                "---",
+                "",
+                "¹ First.",
+                "² Second, see source [2] (https://example.org).",
+            ]
+        );
+        assert_eq!(
+            markdown.text.lines[0].spans[1],
+            Span::styled("¹", styles::footnote(None))
+        );
+        assert_eq!(markdown.links[1].line, 5);
+    }
+
+    #[rstest]
+    fn math(_with_tracing: DefaultGuard) {
+        let text = from_str(
+            indoc! {r"
+                Energy is $E = mc^2$, or $\begin{x}$.
+
+                $$\sum_{i=1}^{n} i$$
+
+                After
+            "},
+            None,
+        );
+
+        assert_eq!(
+            plain(&text),
+            vec![
+                "Energy is E = mc², or \\begin{x}.",
+                "",
+                "∑ᵢ₌₁ⁿ i",
+                "",
+                "After"
+            ]
+        );
+        assert_eq!(
+            text.lines[0].spans[1],
+            Span::styled("E = mc²", styles::math(None))
+        );
+        assert_eq!(
+            text.lines[0].spans[3],
+            Span::styled("\\begin{x}", styles::code(None))
+        );
+        assert_eq!(
+            text.lines[2].alignment,
+            Some(ratatui::layout::Alignment::Center)
+        );
+    }
+
+    #[rstest]
+    fn html(_with_tracing: DefaultGuard) {
+        let text = from_str(
+            indoc! {"
+                Press <kbd>Ctrl</kbd>+<kbd>C</kbd>, H<sub>2</sub>O<br>next line
+
+                <!-- a comment -->
+
+                <details>
+                <summary>More &amp; more</summary>
+
+                Hidden text
+
+                </details>
+            "},
+            None,
+        );
+
+        assert_eq!(
+            plain(&text),
+            vec![
+                "Press Ctrl+C, H2O",
+                "next line",
+                "",
+                "▸ More & more",
+                "",
+                "Hidden text",
+            ]
+        );
+        assert_eq!(
+            text.lines[0].spans[1],
+            Span::styled("Ctrl", styles::code(None))
+        );
+        assert_eq!(
+            text.lines[0].spans[5],
+            Span::styled("2", Style::new().dim().italic())
+        );
+    }
+
+    #[rstest]
+    fn wrap_blockquote(_with_tracing: DefaultGuard) {
+        let markdown = parse_with_width(
+            indoc! {"
+                > Quoted text that goes on
+                >
+                > > and a nested reply too
+            "},
+            None,
+            Some(16),
+        );
+
+        assert_eq!(
+            plain(&markdown.text),
+            vec![
+                "> Quoted text",
+                "> that goes on",
+                "> ",
+                ">> and a nested",
+                ">> reply too",
+            ]
+        );
+        assert_eq!(
+            markdown.text.lines[1].style,
+            styles::p(None).patch(styles::blockquote(None))
+        );
+    }
+
+    #[rstest]
+    fn wrap_list(_with_tracing: DefaultGuard) {
+        let markdown = parse_with_width(
+            indoc! {"
+                - first item wraps here
+                - [x] done with a long task
+                1. ordered and long enough
+            "},
+            None,
+            Some(16),
+        );
+
+        assert_eq!(
+            plain(&markdown.text),
+            vec![
+                "\u{00A0}\u{00A0}• first item",
+                "\u{00A0}\u{00A0}\u{00A0}\u{00A0}wraps here",
+                "\u{00A0}\u{00A0}• [x] done",
+                "\u{00A0}\u{00A0}\u{00A0}\u{00A0}\u{00A0}\u{00A0}\u{00A0}\u{00A0}with a",
+                "\u{00A0}\u{00A0}\u{00A0}\u{00A0}\u{00A0}\u{00A0}\u{00A0}\u{00A0}long",
+                "\u{00A0}\u{00A0}\u{00A0}\u{00A0}\u{00A0}\u{00A0}\u{00A0}\u{00A0}task",
+                "",
+                "\u{00A0}\u{00A0}1. ordered and",
+                "\u{00A0}\u{00A0}\u{00A0}\u{00A0}\u{00A0}long enough",
+            ]
+        );
+    }
+
+    #[rstest]
+    fn wrap_code(_with_tracing: DefaultGuard) {
+        let markdown = parse_with_width(
+            indoc! {"
+                ```
+                let total = first + second;
+                ```
+            "},
+            None,
+            Some(16),
+        );
+
+        assert_eq!(
+            plain(&markdown.text),
+            vec!["```", "let total = fi", "↪ rst + second;", "```"]
+        );
+    }
+
+    #[rstest]
+    fn code_follows_theme(_with_tracing: DefaultGuard) {
+        let mut theme = Theme::default();
+        theme.base[0x0e] = 0xaa00aa;
+        let markdown = parse(
+            indoc! {"
+                ```
+                fn main() {
+                    let mut total = 0;
+                }
+                ```
+            "},
+            Some(theme),
+        );
+
+        let keyword = markdown.text.lines[1]
+            .spans
+            .iter()
+            .find(|s| s.content == "fn")
+            .expect("the code is highlighted as Rust");
+        assert_eq!(keyword.style.fg, Some(Color::Rgb(0xaa, 0x00, 0xaa)));
+    }
+
+    #[rstest]
+    fn wrap_keeps_link_lines(_with_tracing: DefaultGuard) {
+        let markdown = parse_with_width(
+            indoc! {"
+                Some words before ![a chart](/chart.png)
+
+                After
+            "},
+            None,
+            Some(20),
+        );
+
+        let line = markdown.links[0].line;
+        assert!(plain(&markdown.text)[line].ends_with("/chart.png]"));
+        assert_eq!(plain(&markdown.text)[line + 1], "");
+    }
 }
@@ -0,0 +1,61 @@
+use ratatui::{
+    Frame,
+    layout::{Position, Rect},
+    style::{Color, Style},
+    widgets::{Block, List, Padding, Paragraph},
+};
+
+use crate::{
+    core::library::settings::theme::Theme,
+    ui::states::{categorypickerstate::CategoryPickerState, textinputstate::TextInputState},
+};
+
+/// Renders a text input box. The terminal cursor is only placed on the focused input.
+pub fn text_input(
+    frame: &mut Frame,
+    area: Rect,
+    input: &TextInputState,
+    theme: &Theme,
+    focused: bool,
+) {
+    let (fg, bg) = if focused {
+        (theme.base[0x5], theme.base[0x2])
+    } else {
+        (theme.base[0x4], theme.base[0x1])
+    };
+
+    let paragraph = Paragraph::new(input.value.as_str())
+        .style(Style::new().fg(Color::from_u32(fg)).bg(Color::from_u32(bg)))
+        .block(Block::new().padding(Padding::new(1, 1, 1, 1)));
+
+    frame.render_widget(paragraph, area);
+
+    if focused {
+        frame.set_cursor_position(Position::new(
+            area.x + 1 + input.cursor_offset().min(area.width.saturating_sub(3)),
+            area.y + 1,
+        ));
+    }
+}
+
+/// Renders the list of existing categories of a category picker
+pub fn category_list(frame: &mut Frame, area: Rect, picker: &CategoryPickerState, theme: &Theme) {
+    let list = List::new(picker.get_items())
+        .block(
+            Block::default()
+                .style(
+                    Style::default()
+                        .fg(Color::from_u32(theme.base[0x5]))
+                        .bg(Color::from_u32(theme.base[0x1])),
+                )
+                .padding(Padding::new(1, 1, 1, 1)),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::from_u32(theme.base[0x2]))
+                .bg(Color::from_u32(theme.base[0x8])),
+        );
+
+    let mut liststate = picker.liststate;
+    frame.render_stateful_widget(list, area, &mut liststate);
+}
@@ -0,0 +1,164 @@
+use ratatui::style::Style;
+use ratatui::text::Span;
+use unicode_width::UnicodeWidthChar;
+
+/// Styled text being split into lines, as runs of chars with the same style
+struct Lines {
+    lines: Vec<Vec<(String, Style)>>,
+    width: usize,
+}
+
+impl Lines {
+    fn new() -> Self {
+        Self {
+            lines: vec![vec![]],
+            width: 0,
+        }
+    }
+
+    fn push(&mut self, c: char, style: Style) {
+        self.width += c.width().unwrap_or(0);
+        let line = self.lines.last_mut().expect("there's always a line");
+        match line.last_mut() {
+            Some((text, last_style)) if *last_style == style => text.push(c),
+            _ => line.push((c.to_string(), style)),
+        }
+    }
+
+    fn break_line(&mut self) {
+        self.lines.push(vec![]);
+        self.width = 0;
+    }
+
+    fn into_spans<'a>(self) -> Vec<Vec<Span<'a>>> {
+        self.lines
+            .into_iter()
+            .map(|line| {
+                line.into_iter()
+                    .map(|(text, style)| Span::styled(text, style))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+fn chars_width(chars: &[(char, Style)]) -> usize {
+    chars.iter().map(|(c, _)| c.width().unwrap_or(0)).sum()
+}
+
+/// Wraps styled text at spaces to lines no wider than `width`, breaking words longer than a
+/// line. The spaces a line is broken at are dropped; other spaces, and non-breaking ones, stay.
+pub fn wrap_spans<'a>(spans: &[Span<'a>], width: usize) -> Vec<Vec<Span<'a>>> {
+    let width = width.max(1);
+
+    // Alternating runs of spaces and words
+    let mut runs: Vec<(bool, Vec<(char, Style)>)> = vec![];
+    for span in spans {
+        for c in span.content.chars() {
+            let is_space = c == ' ';
+            match runs.last_mut() {
+                Some((space, run)) if *space == is_space => run.push((c, span.style)),
+                _ => runs.push((is_space, vec![(c, span.style)])),
+            }
+        }
+    }
+
+    let mut lines = Lines::new();
+    let mut spaces: Vec<(char, Style)> = vec![];
+    for (is_space, run) in runs {
+        if is_space {
+            spaces = run;
+            continue;
+        }
+
+        let word_width = chars_width(&run);
+        let spaces_width = chars_width(&spaces);
+        if lines.width > 0 && lines.width + spaces_width + word_width > width {
+            lines.break_line();
+        } else {
+            for (c, style) in spaces.drain(..) {
+                lines.push(c, style);
+            }
+        }
+        spaces.clear();
+
+        for (c, style) in run {
+            let char_width = c.width().unwrap_or(0);
+            if lines.width > 0 && lines.width + char_width > width {
+                lines.break_line();
+            }
+            lines.push(c, style);
+        }
+    }
+
+    lines.into_spans()
+}
+
+/// Cuts styled text into lines of `width` columns, wherever that falls, for text like code
+/// where every char matters
+pub fn cut_spans<'a>(spans: &[Span<'a>], width: usize) -> Vec<Vec<Span<'a>>> {
+    let width = width.max(1);
+    let mut lines = Lines::new();
+
+    for span in spans {
+        for c in span.content.chars() {
+            let char_width = c.width().unwrap_or(0);
+            if lines.width > 0 && lines.width + char_width > width {
+                lines.break_line();
+            }
+            lines.push(c, span.style);
+        }
+    }
+
+    lines.into_spans()
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Stylize;
+
+    use super::*;
+
+    #[test]
+    fn test_wrap_spans() {
+        let spans = [Span::from("a quick  "), "brown".bold(), Span::from(" fox")];
+        assert_eq!(
+            wrap_spans(&spans, 9),
+            vec![
+                vec![Span::from("a quick")],
+                vec!["brown".bold(), Span::from(" fox")],
+            ]
+        );
+
+        assert_eq!(
+            wrap_spans(&[Span::from("abcdefgh")], 3),
+            vec![
+                vec![Span::from("abc")],
+                vec![Span::from("def")],
+                vec![Span::from("gh")],
+            ]
+        );
+
+        assert_eq!(
+            wrap_spans(&[Span::from("\u{00A0}\u{00A0}• one two")], 9),
+            vec![
+                vec![Span::from("\u{00A0}\u{00A0}• one")],
+                vec![Span::from("two")],
+            ]
+        );
+
+        assert_eq!(wrap_spans(&[], 3), vec![Vec::<Span>::new()]);
+    }
+
+    #[test]
+    fn test_cut_spans() {
+        assert_eq!(
+            cut_spans(&[Span::from("let x"), " = 1;".bold()], 4),
+            vec![
+                vec![Span::from("let ")],
+                vec![Span::from("x"), " = ".bold()],
+                vec!["1;".bold()],
+            ]
+        );
+    }
+}